@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use svd_parser::{Config, Limits, ValidateLevel};
+
+fuzz_target!(|data: &[u8]| {
+    let limits = Limits::default()
+        .max_elements(100_000)
+        .max_depth(64)
+        .max_dim(1024)
+        .max_expanded_registers(100_000);
+    let config = Config::default()
+        .validate_level(ValidateLevel::Disabled)
+        .limits(limits);
+    if let Ok(device) = svd_parser::parse_bytes_with_config(data, &config) {
+        let _ = svd_parser::expand_with_config(&device, &config);
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use svd_parser::{Config, Limits, ValidateLevel};
+
+fuzz_target!(|data: &[u8]| {
+    let limits = Limits::default()
+        .max_elements(100_000)
+        .max_depth(64)
+        .max_dim(1024);
+    for lvl in [ValidateLevel::Disabled, ValidateLevel::Strict] {
+        let config = Config::default().validate_level(lvl).limits(limits);
+        let _ = svd_parser::parse_bytes_with_config(data, &config);
+    }
+});
@@ -3,7 +3,7 @@ use super::{
 };
 
 use crate::{
-    config::{change_case, format_number},
+    config::{change_case_with_config, format_number},
     svd::{Cluster, ClusterInfo},
 };
 
@@ -11,6 +11,7 @@ impl Encode for Cluster {
     type Error = EncodeError;
 
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
+        let config = &*config.enter(&self.name);
         match self {
             Self::Single(i) => i.encode_with_config(config),
             Self::Array(i, a) => {
@@ -31,7 +32,7 @@ impl Encode for ClusterInfo {
 
         e.children.push(new_node(
             "name",
-            change_case(&self.name, config.cluster_name),
+            change_case_with_config(&self.name, config.cluster_name, config),
         ));
 
         if let Some(v) = &self.description {
@@ -41,7 +42,7 @@ impl Encode for ClusterInfo {
         if let Some(v) = &self.alternate_cluster {
             e.children.push(new_node(
                 "alternateCluster",
-                change_case(v, config.cluster_name),
+                change_case_with_config(v, config.cluster_name, config),
             ));
         }
 
@@ -67,7 +68,7 @@ impl Encode for ClusterInfo {
         if let Some(v) = &self.derived_from {
             e.attributes.insert(
                 String::from("derivedFrom"),
-                change_case(v, config.cluster_name),
+                change_case_with_config(v, config.cluster_name, config),
             );
         }
 
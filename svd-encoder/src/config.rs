@@ -1,10 +1,15 @@
+use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use convert_case::{Boundary, Case, Casing};
+use convert_case::{Boundary, Converter, Pattern};
 
+use crate::overrides::Override;
+use crate::sorting::Comparator;
 use crate::svd::BitRangeType;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum IdentifierFormat {
     /// `Camel` case
     ///
@@ -22,6 +27,8 @@ pub enum IdentifierFormat {
     ///
     /// `A_CONSTANT_CASE_EXAMPLE`
     Constant,
+    /// Converted by [`Config::identifier_callback`]
+    Custom,
 }
 
 impl FromStr for IdentifierFormat {
@@ -33,36 +40,166 @@ impl FromStr for IdentifierFormat {
             "Pascal" => Ok(IdentifierFormat::Pascal),
             "Snake" => Ok(IdentifierFormat::Snake),
             "Constant" => Ok(IdentifierFormat::Constant),
+            "Custom" => Ok(IdentifierFormat::Custom),
             _ => Err(()),
         }
     }
 }
 
-pub fn change_case(s: &str, case: Option<IdentifierFormat>) -> String {
-    match case {
-        None => s.to_string(),
-        Some(case) => {
-            let boundary = [
-                Boundary::Underscore,
-                Boundary::Hyphen,
-                Boundary::Space,
-                Boundary::LowerUpper,
-                Boundary::UpperLower,
-                Boundary::Acronym,
-            ];
-
-            s.with_boundaries(&boundary)
-                .to_case(match case {
-                    IdentifierFormat::Camel => Case::Camel,
-                    IdentifierFormat::Pascal => Case::Pascal,
-                    IdentifierFormat::Snake => Case::Snake,
-                    IdentifierFormat::Constant => Case::UpperSnake,
-                })
-                .replace("%S", "%s")
+impl fmt::Display for IdentifierFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Boundary between words of identifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordBoundary {
+    /// `A_B`
+    Underscore,
+    /// `A-B`
+    Hyphen,
+    /// `A B`
+    Space,
+    /// `aB`
+    LowerUpper,
+    /// `Ab` after other capital letters
+    UpperLower,
+    /// `AB` before capital and lowercase letters, like `ABc`
+    Acronym,
+    /// `1B`
+    DigitUpper,
+    /// `A1`
+    UpperDigit,
+    /// `1b`
+    DigitLower,
+    /// `a1`
+    LowerDigit,
+}
+
+impl WordBoundary {
+    /// Boundaries used by default
+    pub const DEFAULT: &'static [Self] = &[
+        Self::Underscore,
+        Self::Hyphen,
+        Self::Space,
+        Self::LowerUpper,
+        Self::UpperLower,
+        Self::Acronym,
+    ];
+
+    fn boundary(self) -> Boundary {
+        match self {
+            Self::Underscore => Boundary::Underscore,
+            Self::Hyphen => Boundary::Hyphen,
+            Self::Space => Boundary::Space,
+            Self::LowerUpper => Boundary::LowerUpper,
+            Self::UpperLower => Boundary::UpperLower,
+            Self::Acronym => Boundary::Acronym,
+            Self::DigitUpper => Boundary::DigitUpper,
+            Self::UpperDigit => Boundary::UpperDigit,
+            Self::DigitLower => Boundary::DigitLower,
+            Self::LowerDigit => Boundary::LowerDigit,
+        }
+    }
+}
+
+impl FromStr for WordBoundary {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Underscore" => Ok(WordBoundary::Underscore),
+            "Hyphen" => Ok(WordBoundary::Hyphen),
+            "Space" => Ok(WordBoundary::Space),
+            "LowerUpper" => Ok(WordBoundary::LowerUpper),
+            "UpperLower" => Ok(WordBoundary::UpperLower),
+            "Acronym" => Ok(WordBoundary::Acronym),
+            "DigitUpper" => Ok(WordBoundary::DigitUpper),
+            "UpperDigit" => Ok(WordBoundary::UpperDigit),
+            "DigitLower" => Ok(WordBoundary::DigitLower),
+            "LowerDigit" => Ok(WordBoundary::LowerDigit),
+            _ => Err(()),
         }
     }
 }
 
+impl fmt::Display for WordBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// User-supplied conversion used by [`IdentifierFormat::Custom`]
+pub type IdentifierCallback = fn(&str) -> String;
+
+/// Converts identifier `s` to `case` with default [`Config`] options
+pub fn change_case(s: &str, case: Option<IdentifierFormat>) -> String {
+    change_case_with_config(s, case, &Config::default())
+}
+
+/// Converts identifier `s` to `case`.
+///
+/// Words are split on [`Config::identifier_boundaries`]. In `Camel` and `Pascal` cases
+/// words which are [`Config::identifier_acronyms`] followed by digits or `%s`
+/// are written as in the list instead of being capitalized
+pub fn change_case_with_config(s: &str, case: Option<IdentifierFormat>, config: &Config) -> String {
+    let Some(case) = case else {
+        return s.to_string();
+    };
+    if case == IdentifierFormat::Custom {
+        return match config.identifier_callback {
+            Some(f) => f(s),
+            None => s.to_string(),
+        };
+    }
+    let boundaries = config
+        .identifier_boundaries
+        .iter()
+        .map(|b| b.boundary())
+        .collect::<Vec<_>>();
+    // Unit separator doesn't occur in identifiers
+    let lower = Converter::new()
+        .set_boundaries(&boundaries)
+        .set_pattern(Pattern::Lowercase)
+        .set_delim('\u{1f}')
+        .convert(s);
+    let words = lower.split('\u{1f}').filter(|w| !w.is_empty());
+    let converted = match case {
+        IdentifierFormat::Snake => words.collect::<Vec<_>>().join("_"),
+        IdentifierFormat::Constant => words.collect::<Vec<_>>().join("_").to_uppercase(),
+        _ => words
+            .enumerate()
+            .map(|(i, w)| {
+                if i == 0 && case == IdentifierFormat::Camel {
+                    w.to_string()
+                } else {
+                    acronym(w, &config.identifier_acronyms).unwrap_or_else(|| capitalize(w))
+                }
+            })
+            .collect(),
+    };
+    converted.replace("%S", "%s")
+}
+
+/// Acronym from the list which `word` consists of, with digits or `%s` after it
+fn acronym(word: &str, acronyms: &[String]) -> Option<String> {
+    acronyms.iter().find_map(|a| {
+        let rest = word.get(a.len()..)?;
+        let matches = word[..a.len()].eq_ignore_ascii_case(a)
+            && rest.replace("%s", "").chars().all(|c| c.is_ascii_digit());
+        matches.then(|| format!("{a}{rest}"))
+    })
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum NumberFormat {
     /// `UpperHex` format
@@ -117,6 +254,12 @@ impl FromStr for NumberFormat {
     }
 }
 
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 pub fn format_number<T>(value: T, format: NumberFormat) -> String
 where
     T: std::fmt::UpperHex
@@ -166,9 +309,21 @@ impl FromStr for FieldBitRangeFormat {
     }
 }
 
+impl fmt::Display for FieldBitRangeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.0 {
+            BitRangeType::BitRange => "BitRange",
+            BitRangeType::OffsetWidth => "OffsetWidth",
+            BitRangeType::MsbLsb => "MsbLsb",
+        })
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 /// Apply a kind of sorting
+///
+/// Enumerated values and interrupts are sorted by their values instead of offsets
 pub enum Sorting {
     /// Sort by addresses of offsets
     Offset,
@@ -176,6 +331,12 @@ pub enum Sorting {
     OffsetReversed,
     /// Sort by name
     Name,
+    /// Sort by name with numbers compared by value, so `CH2` goes before `CH10`
+    NaturalName,
+    /// Sort by addresses of offsets, then by [`Sorting::NaturalName`]
+    OffsetName,
+    /// Sort with [`Config::comparator`]
+    Custom,
 }
 
 impl Sorting {
@@ -186,10 +347,81 @@ impl Sorting {
             Some(Self::OffsetReversed)
         } else if parts.contains(&"Name") {
             Some(Self::Name)
+        } else if parts.contains(&"NaturalName") {
+            Some(Self::NaturalName)
+        } else if parts.contains(&"OffsetName") {
+            Some(Self::OffsetName)
+        } else if parts.contains(&"Custom") {
+            Some(Self::Custom)
         } else {
             None
         }
     }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Offset => "Offset",
+            Self::OffsetReversed => "OffsetReversed",
+            Self::Name => "Name",
+            Self::NaturalName => "NaturalName",
+            Self::OffsetName => "OffsetName",
+            Self::Custom => "Custom",
+        }
+    }
+}
+
+impl FromStr for Sorting {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match sorting_parts(s)?.as_slice() {
+            [part] => Self::from_parts(&[part]).ok_or(()),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Sorting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Known parts of sorting values
+const SORTING_PARTS: &[&str] = &[
+    "Unchanged",
+    "Offset",
+    "OffsetReversed",
+    "Name",
+    "NaturalName",
+    "OffsetName",
+    "Custom",
+    "DerivedLast",
+    "RegistersFirst",
+    "ClustersFirst",
+];
+
+/// Splits sorting value on commas. Returns `Err` on unknown parts
+fn sorting_parts(s: &str) -> Result<Vec<&str>, ()> {
+    let parts = s
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>();
+    if parts.iter().all(|p| SORTING_PARTS.contains(p)) {
+        Ok(parts)
+    } else {
+        Err(())
+    }
+}
+
+/// Joins sorting parts with commas
+fn join_parts(f: &mut fmt::Formatter<'_>, parts: &[&str]) -> fmt::Result {
+    if parts.is_empty() {
+        f.write_str("Unchanged")
+    } else {
+        f.write_str(&parts.join(","))
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -207,17 +439,35 @@ impl DerivableSorting {
             Self::Unchanged(sorting)
         }
     }
+
+    fn parts(self) -> Vec<&'static str> {
+        let (sorting, derived_last) = match self {
+            Self::Unchanged(sorting) => (sorting, false),
+            Self::DeriveLast(sorting) => (sorting, true),
+        };
+        let mut parts = sorting.map(Sorting::as_str).into_iter().collect::<Vec<_>>();
+        if derived_last {
+            parts.push("DerivedLast");
+        }
+        parts
+    }
 }
 
 impl FromStr for DerivableSorting {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split(',').collect::<Vec<_>>();
+        let parts = sorting_parts(s)?;
         Ok(DerivableSorting::from_parts(&parts))
     }
 }
 
+impl fmt::Display for DerivableSorting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        join_parts(f, &self.parts())
+    }
+}
+
 impl Default for DerivableSorting {
     fn default() -> Self {
         Self::Unchanged(None)
@@ -241,7 +491,7 @@ impl FromStr for RcSorting {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split(',').collect::<Vec<_>>();
+        let parts = sorting_parts(s)?;
         let derivable_sorting = DerivableSorting::from_parts(&parts);
         Ok(if parts.contains(&"RegistersFirst") {
             Self::RegistersFirst(derivable_sorting)
@@ -253,7 +503,338 @@ impl FromStr for RcSorting {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+impl fmt::Display for RcSorting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, sorting) = match self {
+            Self::Unchanged(sorting) => (None, sorting),
+            Self::RegistersFirst(sorting) => (Some("RegistersFirst"), sorting),
+            Self::ClustersFirst(sorting) => (Some("ClustersFirst"), sorting),
+        };
+        let mut parts = first.into_iter().collect::<Vec<_>>();
+        parts.extend(sorting.parts());
+        join_parts(f, &parts)
+    }
+}
+
+/// Version of CMSIS-SVD schema to encode for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SchemaVersion {
+    /// CMSIS-SVD 1.0. Has no `cpu` and `cluster` elements
+    V1_0,
+    /// CMSIS-SVD 1.1. Has no peripheral arrays, `dimName`, `dimArrayIndex`,
+    /// `protection`, `dspPresent` and `sauNumRegions`.
+    /// Registers precede nested clusters
+    V1_1,
+    /// CMSIS-SVD 1.3
+    V1_3,
+}
+
+impl SchemaVersion {
+    /// Value of `schemaVersion` attribute
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1_0 => "1.0",
+            Self::V1_1 => "1.1",
+            Self::V1_3 => "1.3",
+        }
+    }
+
+    /// Value of `xs:noNamespaceSchemaLocation` attribute
+    pub fn schema_location(self) -> &'static str {
+        match self {
+            Self::V1_0 => "CMSIS-SVD_Schema_1_0.xsd",
+            Self::V1_1 => "CMSIS-SVD_Schema_1_1.xsd",
+            Self::V1_3 => "CMSIS-SVD.xsd",
+        }
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1.0" => Ok(SchemaVersion::V1_0),
+            "1.1" => Ok(SchemaVersion::V1_1),
+            "1.3" => Ok(SchemaVersion::V1_3),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What to do with elements which target schema version can't express
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unsupported {
+    /// Return [`EncodeError::Unsupported`](crate::EncodeError::Unsupported)
+    #[default]
+    Error,
+    /// Skip them. Peripheral arrays are written as separate peripherals
+    Drop,
+}
+
+impl FromStr for Unsupported {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Error" => Ok(Unsupported::Error),
+            "Drop" => Ok(Unsupported::Drop),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// How to write register properties (`size`, `access`, `protection`, `resetValue`, `resetMask`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PropertiesMode {
+    /// Write properties as they are
+    #[default]
+    Unchanged,
+    /// Omit properties which are equal to ones inherited from the parent element
+    OmitInherited,
+    /// Move the most common properties of registers to the peripheral
+    /// and the most common properties of peripherals to the device,
+    /// then omit inherited properties
+    Hoist,
+}
+
+impl FromStr for PropertiesMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Unchanged" => Ok(PropertiesMode::Unchanged),
+            "OmitInherited" => Ok(PropertiesMode::OmitInherited),
+            "Hoist" => Ok(PropertiesMode::Hoist),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for PropertiesMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Indentation of nested elements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+    /// Number of spaces per level
+    Spaces(usize),
+    /// One tab per level
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Self::Spaces(2)
+    }
+}
+
+impl Indent {
+    pub(crate) fn as_string(self) -> String {
+        match self {
+            Self::Spaces(n) => " ".repeat(n),
+            Self::Tab => "\t".into(),
+        }
+    }
+}
+
+/// Parses `Tab` or number of spaces
+impl FromStr for Indent {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Tab" => Ok(Indent::Tab),
+            _ => s.parse().map(Indent::Spaces).map_err(|_| ()),
+        }
+    }
+}
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spaces(n) => write!(f, "{n}"),
+            Self::Tab => f.write_str("Tab"),
+        }
+    }
+}
+
+/// Line endings of written file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+impl FromStr for LineEnding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Lf" => Ok(LineEnding::Lf),
+            "CrLf" => Ok(LineEnding::CrLf),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Encoding written in the XML declaration.
+///
+/// Output is always UTF-8, so only encodings which agree with it on ASCII are allowed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// `UTF-8`
+    #[default]
+    Utf8,
+    /// `US-ASCII`
+    UsAscii,
+    /// `ISO-8859-1` to `ISO-8859-16`
+    Iso8859(u8),
+    /// `windows-1250` to `windows-1258`
+    Windows(u16),
+}
+
+impl FromStr for Encoding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_ascii_uppercase();
+        let encoding = match upper.as_str() {
+            "UTF-8" => Encoding::Utf8,
+            "US-ASCII" => Encoding::UsAscii,
+            _ => {
+                if let Some(part) = upper.strip_prefix("ISO-8859-") {
+                    Encoding::Iso8859(part.parse().map_err(|_| ())?)
+                } else if let Some(page) = upper.strip_prefix("WINDOWS-") {
+                    Encoding::Windows(page.parse().map_err(|_| ())?)
+                } else {
+                    return Err(());
+                }
+            }
+        };
+        match encoding {
+            // ISO-8859-12 was never published
+            Encoding::Iso8859(part) if !(1..=16).contains(&part) || part == 12 => Err(()),
+            Encoding::Windows(page) if !(1250..=1258).contains(&page) => Err(()),
+            _ => Ok(encoding),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8 => f.write_str("UTF-8"),
+            Self::UsAscii => f.write_str("US-ASCII"),
+            Self::Iso8859(part) => write!(f, "ISO-8859-{part}"),
+            Self::Windows(page) => write!(f, "windows-{page}"),
+        }
+    }
+}
+
+/// How to write elements without children
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelfClosing {
+    /// `<name/>`
+    #[default]
+    Compact,
+    /// `<name />`
+    Padded,
+    /// `<name></name>`
+    Expanded,
+}
+
+impl FromStr for SelfClosing {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Compact" => Ok(SelfClosing::Compact),
+            "Padded" => Ok(SelfClosing::Padded),
+            "Expanded" => Ok(SelfClosing::Expanded),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for SelfClosing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// How to write text of `description` elements
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DescriptionFormat {
+    /// Write as is
+    #[default]
+    Unchanged,
+    /// Join all lines with single spaces
+    SingleLine,
+    /// Join all lines and wrap them at given width, not counting indentation.
+    /// Continuation lines are indented one level deeper than `<description>`
+    Wrap(usize),
+}
+
+/// Parses `Unchanged`, `SingleLine` or `Wrap(width)`
+impl FromStr for DescriptionFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Unchanged" => Ok(DescriptionFormat::Unchanged),
+            "SingleLine" => Ok(DescriptionFormat::SingleLine),
+            _ => {
+                let width = s.strip_prefix("Wrap(").and_then(|s| s.strip_suffix(')'));
+                match width.map(str::parse) {
+                    Some(Ok(width)) if width > 0 => Ok(DescriptionFormat::Wrap(width)),
+                    _ => Err(()),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DescriptionFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Debug)]
 #[non_exhaustive]
 /// Advanced encoder options
 pub struct Config {
@@ -288,6 +869,9 @@ pub struct Config {
     /// - `name`
     pub interrupt_name: Option<IdentifierFormat>,
 
+    /// Sort interrupts of peripheral in specified order
+    pub interrupt_sorting: Option<Sorting>,
+
     /// Format of cluster's name-kind elements
     /// - `derivedFrom`
     /// - `name`
@@ -346,6 +930,9 @@ pub struct Config {
     /// - `name`
     pub enumerated_values_name: Option<IdentifierFormat>,
 
+    /// Sort enumerated values in specified order. Default value goes last
+    pub enumerated_values_sorting: Option<Sorting>,
+
     /// Format of enumeratedValue's name-kind elements
     /// - `name`
     pub enumerated_value_name: Option<IdentifierFormat>,
@@ -367,6 +954,132 @@ pub struct Config {
 
     /// Format of dimArrayIndex's headerEnumName element
     pub dim_array_index_header_enum_name: Option<IdentifierFormat>,
+
+    /// Version of CMSIS-SVD schema to encode for
+    ///
+    /// `None` means write all elements and keep `schemaVersion` of the device
+    pub schema_version: Option<SchemaVersion>,
+
+    /// What to do with elements which `schema_version` can't express
+    pub unsupported: Unsupported,
+
+    /// How to write register properties of device, peripherals, clusters and registers.
+    ///
+    /// Elements with `derivedFrom` are kept as is
+    pub register_properties: PropertiesMode,
+
+    /// Options for peripherals, clusters, registers and fields selected by path
+    pub overrides: Arc<[Override]>,
+
+    /// Comparison for [`Sorting::Custom`]
+    pub comparator: Option<Comparator>,
+
+    /// Indentation of nested elements
+    pub indent: Indent,
+
+    /// Line endings, including ones in text
+    pub line_ending: LineEnding,
+
+    /// Encoding written in the XML declaration.
+    ///
+    /// Characters other than ASCII are written as character references for other encodings than UTF-8
+    pub declaration_encoding: Encoding,
+
+    /// How to write elements without children
+    pub self_closing: SelfClosing,
+
+    /// How to write text of `description` elements
+    pub description_format: DescriptionFormat,
+
+    /// Attributes which are written first in this order.
+    /// Other attributes follow in the encoder's order
+    pub attribute_order: Arc<[String]>,
+
+    /// Words which are kept as written in `Camel` and `Pascal` identifiers, like `I2C`
+    pub identifier_acronyms: Arc<[String]>,
+
+    /// Boundaries on which identifiers are split to words
+    pub identifier_boundaries: Arc<[WordBoundary]>,
+
+    /// Conversion for [`IdentifierFormat::Custom`]
+    pub identifier_callback: Option<IdentifierCallback>,
+}
+
+/// Names of all options of [`Config`] accepted by [`Config::try_update`]
+pub const CONFIG_KEYS: &[&str] = &[
+    "schema_version",
+    "unsupported",
+    "register_properties",
+    "indent",
+    "line_ending",
+    "declaration_encoding",
+    "self_closing",
+    "description_format",
+    "attribute_order",
+    "identifier_acronyms",
+    "identifier_boundaries",
+    "peripheral_name",
+    "peripheral_base_address",
+    "peripheral_sorting",
+    "address_block_offset",
+    "address_block_size",
+    "interrupt_name",
+    "interrupt_sorting",
+    "cluster_name",
+    "cluster_address_offset",
+    "register_cluster_sorting",
+    "register_name",
+    "register_address_offset",
+    "register_size",
+    "register_reset_value",
+    "register_reset_mask",
+    "field_name",
+    "field_bit_range",
+    "field_sorting",
+    "enumerated_values_name",
+    "enumerated_values_sorting",
+    "enumerated_value_name",
+    "enumerated_value_value",
+    "dim_dim",
+    "dim_increment",
+    "dim_array_index_header_enum_name",
+];
+
+/// Errors of loading encoder [`Config`]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ConfigError {
+    #[error("Unknown config key `{0}`")]
+    UnknownKey(String),
+    #[error("Invalid value `{value}` of config key `{key}`")]
+    InvalidValue { key: String, value: String },
+    #[error("Expected `key=value`, found `{0}`")]
+    InvalidEntry(String),
+    #[error("Unsupported config file format `{0}`")]
+    UnsupportedFormat(String),
+    #[error("Config file error: {0}")]
+    Format(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Parses `name=value` pairs separated by new lines or semicolons
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+        for entry in s.split(['\n', ';']).map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+            let (name, value) = entry
+                .split_once('=')
+                .ok_or_else(|| ConfigError::InvalidEntry(entry.into()))?;
+            config.try_update(name.trim(), value)?;
+        }
+        Ok(config)
+    }
 }
 
 impl Default for Config {
@@ -378,6 +1091,7 @@ impl Default for Config {
             address_block_offset: NumberFormat::UpperHex,
             address_block_size: NumberFormat::UpperHex,
             interrupt_name: None,
+            interrupt_sorting: None,
             cluster_name: None,
             cluster_address_offset: NumberFormat::UpperHex,
             register_cluster_sorting: Default::default(),
@@ -390,11 +1104,26 @@ impl Default for Config {
             field_bit_range: None,
             field_sorting: Default::default(),
             enumerated_values_name: None,
+            enumerated_values_sorting: None,
             enumerated_value_name: None,
             enumerated_value_value: NumberFormat::Dec,
             dim_dim: NumberFormat::Dec,
             dim_increment: NumberFormat::UpperHex,
             dim_array_index_header_enum_name: None,
+            schema_version: None,
+            unsupported: Unsupported::Error,
+            register_properties: PropertiesMode::Unchanged,
+            overrides: Arc::from([]),
+            comparator: None,
+            indent: Indent::default(),
+            line_ending: LineEnding::Lf,
+            declaration_encoding: Encoding::Utf8,
+            self_closing: SelfClosing::Compact,
+            description_format: DescriptionFormat::Unchanged,
+            attribute_order: Arc::from([]),
+            identifier_acronyms: Arc::from([]),
+            identifier_boundaries: Arc::from(WordBoundary::DEFAULT),
+            identifier_callback: None,
         }
     }
 }
@@ -402,37 +1131,202 @@ impl Default for Config {
 impl Config {
     /// Update the config with given name and value
     ///
-    /// panic if the value is not valid
+    /// panic if the value is not valid. Unknown keys are reported to stderr.
+    /// See [`Config::try_update`] for the fallible version
     pub fn update(&mut self, name: &str, value: &str) {
+        match self.try_update(name, value) {
+            Ok(()) => {}
+            Err(ConfigError::UnknownKey(_)) => {
+                eprintln!("Unknown config key: {}", name);
+            }
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Update the config with given name and value
+    pub fn try_update(&mut self, name: &str, value: &str) -> Result<(), ConfigError> {
+        fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, ConfigError> {
+            value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+                key: name.into(),
+                value: value.into(),
+            })
+        }
+        fn list(value: &str) -> impl Iterator<Item = &str> {
+            value.split(',').map(str::trim).filter(|a| !a.is_empty())
+        }
+        fn sorting(name: &str, value: &str) -> Result<Option<Sorting>, ConfigError> {
+            match value.trim() {
+                "Unchanged" => Ok(None),
+                _ => parse(name, value).map(Some),
+            }
+        }
+        let v = value;
         match name {
-            "peripheral_name" => self.peripheral_name = Some(value.parse().unwrap()),
-            "peripheral_base_address" => self.peripheral_base_address = value.parse().unwrap(),
-            "peripheral_sorting" => self.peripheral_sorting = value.parse().unwrap(),
-            "address_block_offset" => self.address_block_offset = value.parse().unwrap(),
-            "address_block_size" => self.address_block_size = value.parse().unwrap(),
-            "interrupt_name" => self.interrupt_name = Some(value.parse().unwrap()),
-            "cluster_name" => self.cluster_name = Some(value.parse().unwrap()),
-            "cluster_address_offset" => self.cluster_address_offset = value.parse().unwrap(),
-            "register_cluster_sorting" => self.register_cluster_sorting = value.parse().unwrap(),
-            "register_name" => self.register_name = Some(value.parse().unwrap()),
-            "register_address_offset" => self.register_address_offset = value.parse().unwrap(),
-            "register_size" => self.register_size = value.parse().unwrap(),
-            "register_reset_value" => self.register_reset_value = value.parse().unwrap(),
-            "register_reset_mask" => self.register_reset_mask = value.parse().unwrap(),
-            "field_name" => self.field_name = Some(value.parse().unwrap()),
-            "field_bit_range" => self.field_bit_range = Some(value.parse().unwrap()),
-            "field_sorting" => self.field_sorting = value.parse().unwrap(),
-            "enumerated_values_name" => self.enumerated_values_name = Some(value.parse().unwrap()),
-            "enumerated_value_name" => self.enumerated_value_name = Some(value.parse().unwrap()),
-            "enumerated_value_value" => self.enumerated_value_value = value.parse().unwrap(),
-            "dim_dim" => self.dim_dim = value.parse().unwrap(),
-            "dim_increment" => self.dim_increment = value.parse().unwrap(),
+            "peripheral_name" => self.peripheral_name = Some(parse(name, v)?),
+            "peripheral_base_address" => self.peripheral_base_address = parse(name, v)?,
+            "peripheral_sorting" => self.peripheral_sorting = parse(name, v)?,
+            "address_block_offset" => self.address_block_offset = parse(name, v)?,
+            "address_block_size" => self.address_block_size = parse(name, v)?,
+            "interrupt_name" => self.interrupt_name = Some(parse(name, v)?),
+            "interrupt_sorting" => self.interrupt_sorting = sorting(name, v)?,
+            "cluster_name" => self.cluster_name = Some(parse(name, v)?),
+            "cluster_address_offset" => self.cluster_address_offset = parse(name, v)?,
+            "register_cluster_sorting" => self.register_cluster_sorting = parse(name, v)?,
+            "register_name" => self.register_name = Some(parse(name, v)?),
+            "register_address_offset" => self.register_address_offset = parse(name, v)?,
+            "register_size" => self.register_size = parse(name, v)?,
+            "register_reset_value" => self.register_reset_value = parse(name, v)?,
+            "register_reset_mask" => self.register_reset_mask = parse(name, v)?,
+            "field_name" => self.field_name = Some(parse(name, v)?),
+            "field_bit_range" => self.field_bit_range = Some(parse(name, v)?),
+            "field_sorting" => self.field_sorting = parse(name, v)?,
+            "enumerated_values_name" => self.enumerated_values_name = Some(parse(name, v)?),
+            "enumerated_values_sorting" => self.enumerated_values_sorting = sorting(name, v)?,
+            "enumerated_value_name" => self.enumerated_value_name = Some(parse(name, v)?),
+            "enumerated_value_value" => self.enumerated_value_value = parse(name, v)?,
+            "dim_dim" => self.dim_dim = parse(name, v)?,
+            "dim_increment" => self.dim_increment = parse(name, v)?,
             "dim_array_index_header_enum_name" => {
-                self.dim_array_index_header_enum_name = Some(value.parse().unwrap())
+                self.dim_array_index_header_enum_name = Some(parse(name, v)?)
             }
-            _ => {
-                eprintln!("Unknown config key: {}", name);
+            "schema_version" => self.schema_version = Some(parse(name, v)?),
+            "unsupported" => self.unsupported = parse(name, v)?,
+            "register_properties" => self.register_properties = parse(name, v)?,
+            "indent" => self.indent = parse(name, v)?,
+            "line_ending" => self.line_ending = parse(name, v)?,
+            "declaration_encoding" => self.declaration_encoding = parse(name, v)?,
+            "self_closing" => self.self_closing = parse(name, v)?,
+            "description_format" => self.description_format = parse(name, v)?,
+            "attribute_order" => self.attribute_order = list(v).map(String::from).collect(),
+            "identifier_acronyms" => self.identifier_acronyms = list(v).map(String::from).collect(),
+            "identifier_boundaries" => {
+                self.identifier_boundaries = list(v)
+                    .map(|b| parse(name, b))
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| ConfigError::InvalidValue {
+                        key: name.into(),
+                        value: value.into(),
+                    })?
+            }
+            _ => return Err(ConfigError::UnknownKey(name.into())),
+        }
+        Ok(())
+    }
+
+    /// Copy option with given name from `other`
+    pub(crate) fn copy_option(&mut self, name: &str, other: &Config) {
+        match name {
+            "peripheral_name" => self.peripheral_name = other.peripheral_name,
+            "peripheral_base_address" => {
+                self.peripheral_base_address = other.peripheral_base_address
+            }
+            "peripheral_sorting" => self.peripheral_sorting = other.peripheral_sorting,
+            "address_block_offset" => self.address_block_offset = other.address_block_offset,
+            "address_block_size" => self.address_block_size = other.address_block_size,
+            "interrupt_name" => self.interrupt_name = other.interrupt_name,
+            "interrupt_sorting" => self.interrupt_sorting = other.interrupt_sorting,
+            "cluster_name" => self.cluster_name = other.cluster_name,
+            "cluster_address_offset" => self.cluster_address_offset = other.cluster_address_offset,
+            "register_cluster_sorting" => {
+                self.register_cluster_sorting = other.register_cluster_sorting
+            }
+            "register_name" => self.register_name = other.register_name,
+            "register_address_offset" => {
+                self.register_address_offset = other.register_address_offset
+            }
+            "register_size" => self.register_size = other.register_size,
+            "register_reset_value" => self.register_reset_value = other.register_reset_value,
+            "register_reset_mask" => self.register_reset_mask = other.register_reset_mask,
+            "field_name" => self.field_name = other.field_name,
+            "field_bit_range" => self.field_bit_range = other.field_bit_range,
+            "field_sorting" => self.field_sorting = other.field_sorting,
+            "enumerated_values_name" => self.enumerated_values_name = other.enumerated_values_name,
+            "enumerated_values_sorting" => {
+                self.enumerated_values_sorting = other.enumerated_values_sorting
             }
+            "enumerated_value_name" => self.enumerated_value_name = other.enumerated_value_name,
+            "enumerated_value_value" => self.enumerated_value_value = other.enumerated_value_value,
+            "dim_dim" => self.dim_dim = other.dim_dim,
+            "dim_increment" => self.dim_increment = other.dim_increment,
+            "dim_array_index_header_enum_name" => {
+                self.dim_array_index_header_enum_name = other.dim_array_index_header_enum_name
+            }
+            "schema_version" => self.schema_version = other.schema_version,
+            "unsupported" => self.unsupported = other.unsupported,
+            "register_properties" => self.register_properties = other.register_properties,
+            "indent" => self.indent = other.indent,
+            "line_ending" => self.line_ending = other.line_ending,
+            "declaration_encoding" => self.declaration_encoding = other.declaration_encoding,
+            "self_closing" => self.self_closing = other.self_closing,
+            "description_format" => self.description_format = other.description_format,
+            "attribute_order" => self.attribute_order = other.attribute_order.clone(),
+            "identifier_acronyms" => self.identifier_acronyms = other.identifier_acronyms.clone(),
+            "identifier_boundaries" => {
+                self.identifier_boundaries = other.identifier_boundaries.clone()
+            }
+            _ => {}
+        }
+    }
+
+    /// Value of the option with given name as accepted by [`Config::try_update`].
+    /// `None` for unknown names and unset optional values
+    pub fn get(&self, name: &str) -> Option<String> {
+        fn opt<T: fmt::Display>(val: Option<T>) -> Option<String> {
+            val.map(|v| v.to_string())
+        }
+        match name {
+            "peripheral_name" => opt(self.peripheral_name),
+            "peripheral_base_address" => opt(Some(self.peripheral_base_address)),
+            "peripheral_sorting" => opt(Some(self.peripheral_sorting)),
+            "address_block_offset" => opt(Some(self.address_block_offset)),
+            "address_block_size" => opt(Some(self.address_block_size)),
+            "interrupt_name" => opt(self.interrupt_name),
+            "interrupt_sorting" => opt(Some(
+                self.interrupt_sorting.map_or("Unchanged", Sorting::as_str),
+            )),
+            "cluster_name" => opt(self.cluster_name),
+            "cluster_address_offset" => opt(Some(self.cluster_address_offset)),
+            "register_cluster_sorting" => opt(Some(self.register_cluster_sorting)),
+            "register_name" => opt(self.register_name),
+            "register_address_offset" => opt(Some(self.register_address_offset)),
+            "register_size" => opt(Some(self.register_size)),
+            "register_reset_value" => opt(Some(self.register_reset_value)),
+            "register_reset_mask" => opt(Some(self.register_reset_mask)),
+            "field_name" => opt(self.field_name),
+            "field_bit_range" => opt(self.field_bit_range),
+            "field_sorting" => opt(Some(self.field_sorting)),
+            "enumerated_values_name" => opt(self.enumerated_values_name),
+            "enumerated_values_sorting" => opt(Some(
+                self.enumerated_values_sorting
+                    .map_or("Unchanged", Sorting::as_str),
+            )),
+            "enumerated_value_name" => opt(self.enumerated_value_name),
+            "enumerated_value_value" => opt(Some(self.enumerated_value_value)),
+            "dim_dim" => opt(Some(self.dim_dim)),
+            "dim_increment" => opt(Some(self.dim_increment)),
+            "dim_array_index_header_enum_name" => opt(self.dim_array_index_header_enum_name),
+            "schema_version" => opt(self.schema_version),
+            "unsupported" => opt(Some(self.unsupported)),
+            "register_properties" => opt(Some(self.register_properties)),
+            "indent" => opt(Some(self.indent)),
+            "line_ending" => opt(Some(self.line_ending)),
+            "declaration_encoding" => opt(Some(self.declaration_encoding)),
+            "self_closing" => opt(Some(self.self_closing)),
+            "description_format" => opt(Some(self.description_format)),
+            "attribute_order" => {
+                Some(self.attribute_order.join(",")).filter(|order| !order.is_empty())
+            }
+            "identifier_acronyms" => {
+                Some(self.identifier_acronyms.join(",")).filter(|list| !list.is_empty())
+            }
+            "identifier_boundaries" => Some(
+                self.identifier_boundaries
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => None,
         }
     }
 
@@ -480,6 +1374,14 @@ impl Config {
         self
     }
 
+    /// Sort interrupts of peripheral in specified order
+    ///
+    /// `None` means keep the original order
+    pub fn interrupt_sorting(mut self, val: Option<Sorting>) -> Self {
+        self.interrupt_sorting = val;
+        self
+    }
+
     /// Format of cluster's name-kind elements
     pub fn cluster_name(mut self, val: Option<IdentifierFormat>) -> Self {
         self.cluster_name = val;
@@ -568,6 +1470,14 @@ impl Config {
         self
     }
 
+    /// Sort enumerated values in specified order
+    ///
+    /// `None` means keep the original order
+    pub fn enumerated_values_sorting(mut self, val: Option<Sorting>) -> Self {
+        self.enumerated_values_sorting = val;
+        self
+    }
+
     /// Format of enumeratedValue's name-kind elements
     pub fn enumerated_value_name(mut self, val: Option<IdentifierFormat>) -> Self {
         self.enumerated_value_name = val;
@@ -597,4 +1507,90 @@ impl Config {
         self.dim_increment = val;
         self
     }
+
+    /// Version of CMSIS-SVD schema to encode for
+    ///
+    /// `None` means write all elements and keep `schemaVersion` of the device
+    pub fn schema_version(mut self, val: Option<SchemaVersion>) -> Self {
+        self.schema_version = val;
+        self
+    }
+
+    /// What to do with elements which `schema_version` can't express
+    pub fn unsupported(mut self, val: Unsupported) -> Self {
+        self.unsupported = val;
+        self
+    }
+
+    /// How to write register properties of device, peripherals, clusters and registers
+    pub fn register_properties(mut self, val: PropertiesMode) -> Self {
+        self.register_properties = val;
+        self
+    }
+
+    /// Options for elements selected by path. Later overrides take precedence
+    pub fn overrides(mut self, val: impl Into<Arc<[Override]>>) -> Self {
+        self.overrides = val.into();
+        self
+    }
+
+    /// Comparison for [`Sorting::Custom`]
+    pub fn comparator(mut self, f: Comparator) -> Self {
+        self.comparator = Some(f);
+        self
+    }
+
+    /// Indentation of nested elements
+    pub fn indent(mut self, val: Indent) -> Self {
+        self.indent = val;
+        self
+    }
+
+    /// Line endings, including ones in text
+    pub fn line_ending(mut self, val: LineEnding) -> Self {
+        self.line_ending = val;
+        self
+    }
+
+    /// Encoding written in the XML declaration
+    pub fn declaration_encoding(mut self, val: Encoding) -> Self {
+        self.declaration_encoding = val;
+        self
+    }
+
+    /// How to write elements without children
+    pub fn self_closing(mut self, val: SelfClosing) -> Self {
+        self.self_closing = val;
+        self
+    }
+
+    /// How to write text of `description` elements
+    pub fn description_format(mut self, val: DescriptionFormat) -> Self {
+        self.description_format = val;
+        self
+    }
+
+    /// Words which are kept as written in `Camel` and `Pascal` identifiers
+    pub fn identifier_acronyms(mut self, val: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.identifier_acronyms = val.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Boundaries on which identifiers are split to words
+    pub fn identifier_boundaries(mut self, val: impl IntoIterator<Item = WordBoundary>) -> Self {
+        self.identifier_boundaries = val.into_iter().collect();
+        self
+    }
+
+    /// Conversion for [`IdentifierFormat::Custom`]
+    pub fn identifier_callback(mut self, f: IdentifierCallback) -> Self {
+        self.identifier_callback = Some(f);
+        self
+    }
+
+    /// Attributes which are written first in this order
+    pub fn attribute_order(mut self, val: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.attribute_order = val.into_iter().map(Into::into).collect();
+        self
+    }
 }
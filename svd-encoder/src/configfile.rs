@@ -0,0 +1,311 @@
+//! Encoder configuration files.
+//!
+//! Options are grouped in sections by element kind, the remaining options are
+//! written at the top level. Values are the same as accepted by [`Config::try_update`]:
+//!
+//! ```toml
+//! schema_version = "1.1"
+//!
+//! [peripheral]
+//! base_address = "UpperHex8"
+//! sorting = "Offset,DerivedLast"
+//!
+//! [register]
+//! name = "Constant"
+//! cluster_sorting = "RegistersFirst"
+//!
+//! [[overrides]]
+//! path = "TIM*"
+//! register_address_offset = "Dec"
+//! ```
+
+use indexmap::IndexMap;
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::config::CONFIG_KEYS;
+use super::{Config, ConfigError, Override};
+
+/// Sections of the config file, by element kind
+const SECTIONS: &[&str] = &[
+    "peripheral",
+    "address_block",
+    "interrupt",
+    "cluster",
+    "register",
+    "field",
+    "enumerated_values",
+    "enumerated_value",
+    "dim",
+];
+
+/// Options which are written at the top level
+const TOP_LEVEL: &[&str] = &["schema_version", "unsupported", "register_properties"];
+
+/// Splits option name on section and key in section
+fn split_key(name: &str) -> (Option<&'static str>, &str) {
+    if TOP_LEVEL.contains(&name) {
+        return (None, name);
+    }
+    SECTIONS
+        .iter()
+        .filter_map(|s| Some((*s, name.strip_prefix(s)?.strip_prefix('_')?)))
+        .max_by_key(|(s, _)| s.len())
+        .map_or((None, name), |(s, key)| (Some(s), key))
+}
+
+/// Scalar value. Numbers and booleans are accepted for unquoted values like `1.1`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    String(String),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
+
+impl Scalar {
+    fn into_string(self) -> String {
+        match self {
+            Self::String(s) => s,
+            Self::Bool(b) => b.to_string(),
+            Self::Int(i) => i.to_string(),
+            Self::Float(f) => format!("{f:?}"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Value(Scalar),
+    Section(IndexMap<String, Scalar>),
+    List(Vec<IndexMap<String, Scalar>>),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum OutEntry {
+    Value(String),
+    Section(IndexMap<&'static str, String>),
+    List(Vec<IndexMap<String, String>>),
+}
+
+impl Config {
+    /// Options grouped in sections as they are written to config files
+    fn sections(&self) -> IndexMap<&'static str, OutEntry> {
+        let mut map = IndexMap::new();
+        for name in CONFIG_KEYS {
+            let Some(value) = self.get(name) else {
+                continue;
+            };
+            match split_key(name) {
+                (Some(section), key) => {
+                    let entry = map
+                        .entry(section)
+                        .or_insert_with(|| OutEntry::Section(IndexMap::new()));
+                    if let OutEntry::Section(s) = entry {
+                        s.insert(key, value);
+                    }
+                }
+                (None, _) => {
+                    map.insert(name, OutEntry::Value(value));
+                }
+            }
+        }
+        if !self.overrides.is_empty() {
+            let overrides = self
+                .overrides
+                .iter()
+                .map(|o| {
+                    let mut table = IndexMap::new();
+                    table.insert("path".to_string(), o.path.clone());
+                    table.extend(o.options().map(|(key, value)| (key.to_string(), value)));
+                    table
+                })
+                .collect();
+            map.insert("overrides", OutEntry::List(overrides));
+        }
+        map
+    }
+
+    fn from_sections(map: IndexMap<String, Entry>) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+        let mut overrides = Vec::new();
+        for (name, entry) in map {
+            match entry {
+                Entry::Value(value) => config.try_update(&name, &value.into_string())?,
+                Entry::Section(section) => {
+                    if !SECTIONS.contains(&name.as_str()) {
+                        return Err(ConfigError::UnknownKey(name));
+                    }
+                    for (key, value) in section {
+                        let full = format!("{name}_{key}");
+                        if split_key(&full).0 != Some(name.as_str()) {
+                            return Err(ConfigError::UnknownKey(format!("{name}.{key}")));
+                        }
+                        config
+                            .try_update(&full, &value.into_string())
+                            .map_err(|e| in_section(e, &name, &key))?;
+                    }
+                }
+                Entry::List(list) if name == "overrides" => {
+                    for (i, table) in list.into_iter().enumerate() {
+                        overrides.push(override_from_table(table, i)?);
+                    }
+                }
+                Entry::List(_) => return Err(ConfigError::UnknownKey(name)),
+            }
+        }
+        if !overrides.is_empty() {
+            config.overrides = overrides.into();
+        }
+        Ok(config)
+    }
+
+    /// Reads config file. Format is selected by extension: `.toml`, `.yaml`, `.yml` or `.json`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let parse: fn(&str) -> Result<Self, ConfigError> = match extension(path) {
+            #[cfg(feature = "toml")]
+            "toml" => Self::from_toml,
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Self::from_yaml,
+            #[cfg(feature = "json")]
+            "json" => Self::from_json,
+            ext => return Err(ConfigError::UnsupportedFormat(ext.into())),
+        };
+        parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Writes config file. Format is selected by extension: `.toml`, `.yaml`, `.yml` or `.json`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let text = match extension(path) {
+            #[cfg(feature = "toml")]
+            "toml" => self.to_toml()?,
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => self.to_yaml()?,
+            #[cfg(feature = "json")]
+            "json" => self.to_json()?,
+            ext => return Err(ConfigError::UnsupportedFormat(ext.into())),
+        };
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Parses config from TOML
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, ConfigError> {
+        let map = toml::from_str(s).map_err(|e| ConfigError::Format(e.to_string()))?;
+        Self::from_sections(map)
+    }
+
+    /// Writes config as TOML
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        toml::to_string(self).map_err(|e| ConfigError::Format(e.to_string()))
+    }
+
+    /// Parses config from YAML
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(s: &str) -> Result<Self, ConfigError> {
+        let map = serde_yaml_ng::from_str(s).map_err(|e| ConfigError::Format(e.to_string()))?;
+        Self::from_sections(map)
+    }
+
+    /// Writes config as YAML
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> Result<String, ConfigError> {
+        serde_yaml_ng::to_string(self).map_err(|e| ConfigError::Format(e.to_string()))
+    }
+
+    /// Parses config from JSON
+    #[cfg(feature = "json")]
+    pub fn from_json(s: &str) -> Result<Self, ConfigError> {
+        let map = serde_json::from_str(s).map_err(|e| ConfigError::Format(e.to_string()))?;
+        Self::from_sections(map)
+    }
+
+    /// Writes config as JSON
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, ConfigError> {
+        serde_json::to_string_pretty(self).map_err(|e| ConfigError::Format(e.to_string()))
+    }
+}
+
+/// Reads override from table with `path` and full option names
+fn override_from_table(
+    mut table: IndexMap<String, Scalar>,
+    index: usize,
+) -> Result<Override, ConfigError> {
+    let section = format!("overrides[{index}]");
+    let path = table
+        .shift_remove("path")
+        .ok_or_else(|| ConfigError::Format(format!("`{section}` has no `path`")))?;
+    let mut o = Override::new(path.into_string());
+    for (key, value) in table {
+        o = o
+            .set(&key, &value.into_string())
+            .map_err(|e| in_section(e, &section, &key))?;
+    }
+    Ok(o)
+}
+
+/// Names the key with its section in the error
+fn in_section(e: ConfigError, section: &str, key: &str) -> ConfigError {
+    match e {
+        ConfigError::InvalidValue { value, .. } => ConfigError::InvalidValue {
+            key: format!("{section}.{key}"),
+            value,
+        },
+        ConfigError::UnknownKey(_) => ConfigError::UnknownKey(format!("{section}.{key}")),
+        e => e,
+    }
+}
+
+fn extension(path: &Path) -> &str {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+}
+
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.sections().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = IndexMap::<String, Entry>::deserialize(deserializer)?;
+        Self::from_sections(map).map_err(de::Error::custom)
+    }
+}
+
+#[test]
+fn test_split_key() {
+    assert_eq!(split_key("schema_version"), (None, "schema_version"));
+    assert_eq!(
+        split_key("register_properties"),
+        (None, "register_properties")
+    );
+    assert_eq!(
+        split_key("register_cluster_sorting"),
+        (Some("register"), "cluster_sorting")
+    );
+    assert_eq!(
+        split_key("enumerated_value_name"),
+        (Some("enumerated_value"), "name")
+    );
+    assert_eq!(
+        split_key("enumerated_values_name"),
+        (Some("enumerated_values"), "name")
+    );
+    assert_eq!(
+        split_key("dim_array_index_header_enum_name"),
+        (Some("dim"), "array_index_header_enum_name")
+    );
+}
@@ -1,119 +1,138 @@
+use std::borrow::Cow;
 use svd_rs::Peripheral;
 
-use super::{new_node, Config, Element, Encode, EncodeChildren, EncodeError, XMLNode};
-use crate::{
-    config::{DerivableSorting, Sorting},
-    svd::Device,
+use super::{
+    new_node, properties::Defaults, schemaversion, Config, Element, Encode, EncodeChildren,
+    EncodeError, XMLNode,
 };
+use crate::{config::DerivableSorting, sorting::sort, svd::Device};
 
 impl Encode for Device {
     type Error = EncodeError;
 
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
-        let mut elem = Element::new("device");
-        if let Some(v) = &self.vendor {
-            elem.children.push(new_node("vendor", v.clone()));
-        }
-        if let Some(v) = &self.vendor_id {
-            elem.children.push(new_node("vendorID", v.clone()));
-        }
+        let defaults = Defaults::new(self, config);
+        let mut elem = encode_header(self, &defaults, config)?;
+        let peripherals: Result<Vec<_>, _> = sorted_peripherals(self, config)
+            .into_iter()
+            .map(|peripheral| {
+                encode_peripheral(peripheral, &defaults, config).map(XMLNode::Element)
+            })
+            .collect();
 
-        elem.children.push(new_node("name", self.name.clone()));
+        elem.children.push({
+            let mut e = Element::new("peripherals");
+            e.children = peripherals?;
+            XMLNode::Element(e)
+        });
 
-        if let Some(v) = &self.series {
-            elem.children.push(new_node("series", v.clone()));
-        }
+        Ok(elem)
+    }
+}
 
-        elem.children
-            .push(new_node("version", self.version.clone()));
+/// Encodes the device without `peripherals` element
+pub(crate) fn encode_header(
+    device: &Device,
+    defaults: &Defaults,
+    config: &Config,
+) -> Result<Element, EncodeError> {
+    let mut elem = Element::new("device");
+    if let Some(v) = &device.vendor {
+        elem.children.push(new_node("vendor", v.clone()));
+    }
+    if let Some(v) = &device.vendor_id {
+        elem.children.push(new_node("vendorID", v.clone()));
+    }
 
-        elem.children
-            .push(new_node("description", self.description.clone()));
+    elem.children.push(new_node("name", device.name.clone()));
 
-        if let Some(v) = &self.license_text {
-            elem.children.push(new_node("licenseText", v.clone()));
-        }
+    if let Some(v) = &device.series {
+        elem.children.push(new_node("series", v.clone()));
+    }
 
-        if let Some(v) = &self.cpu {
-            elem.children
-                .push(XMLNode::Element(v.encode_with_config(config)?));
-        }
+    elem.children
+        .push(new_node("version", device.version.clone()));
 
-        if let Some(v) = &self.header_system_filename {
-            elem.children
-                .push(new_node("headerSystemFilename", v.clone()));
-        }
+    elem.children
+        .push(new_node("description", device.description.clone()));
 
-        if let Some(v) = &self.header_definitions_prefix {
-            elem.children
-                .push(new_node("headerDefinitionsPrefix", v.clone()));
-        }
+    if let Some(v) = &device.license_text {
+        elem.children.push(new_node("licenseText", v.clone()));
+    }
 
-        elem.children.push(new_node(
-            "addressUnitBits",
-            format!("{}", self.address_unit_bits),
-        ));
+    if let Some(v) = &device.cpu {
+        elem.children
+            .push(XMLNode::Element(v.encode_with_config(config)?));
+    }
 
+    if let Some(v) = &device.header_system_filename {
         elem.children
-            .push(new_node("width", format!("{}", self.width)));
-
-        elem.children.extend(
-            self.default_register_properties
-                .encode_with_config(config)?,
-        );
-
-        let peripherals: Result<Vec<_>, _> =
-            if config.peripheral_sorting == DerivableSorting::Unchanged(None) {
-                self.peripherals
-                    .iter()
-                    .map(|peripheral| peripheral.encode_node_with_config(config))
-                    .collect()
-            } else {
-                sort_derived_peripherals(&self.peripherals, config.peripheral_sorting)
-                    .into_iter()
-                    .map(|peripheral| peripheral.encode_node_with_config(config))
-                    .collect()
-            };
+            .push(new_node("headerSystemFilename", v.clone()));
+    }
 
-        elem.children.push({
-            let mut e = Element::new("peripherals");
-            e.children = peripherals?;
-            XMLNode::Element(e)
-        });
+    if let Some(v) = &device.header_definitions_prefix {
+        elem.children
+            .push(new_node("headerDefinitionsPrefix", v.clone()));
+    }
 
-        elem.attributes
-            .insert(String::from("schemaVersion"), self.schema_version.clone());
-        elem.attributes
-            .insert(String::from("xmlns:xs"), self.xmlns_xs.clone());
-        elem.attributes.insert(
-            String::from("xs:noNamespaceSchemaLocation"),
-            self.no_namespace_schema_location.clone(),
-        );
+    elem.children.push(new_node(
+        "addressUnitBits",
+        format!("{}", device.address_unit_bits),
+    ));
+
+    elem.children
+        .push(new_node("width", format!("{}", device.width)));
+
+    elem.children
+        .extend(defaults.device.encode_with_config(config)?);
+
+    elem.attributes
+        .insert(String::from("schemaVersion"), device.schema_version.clone());
+    elem.attributes
+        .insert(String::from("xmlns:xs"), device.xmlns_xs.clone());
+    elem.attributes.insert(
+        String::from("xs:noNamespaceSchemaLocation"),
+        device.no_namespace_schema_location.clone(),
+    );
+    schemaversion::device_attributes(&mut elem, config);
+    schemaversion::restrict(&mut elem, config)?;
+
+    Ok(elem)
+}
 
-        Ok(elem)
-    }
+/// Encodes the peripheral with properties relative to `defaults` for the target schema version
+pub(crate) fn encode_peripheral(
+    p: Cow<Peripheral>,
+    defaults: &Defaults,
+    config: &Config,
+) -> Result<Element, EncodeError> {
+    let mut elem = defaults.peripheral(p).encode_with_config(config)?;
+    schemaversion::restrict(&mut elem, config)?;
+    Ok(elem)
 }
 
-fn sort_peripherals(refs: &mut [&Peripheral], sorting: Option<Sorting>) {
-    if let Some(sorting) = sorting {
-        match sorting {
-            Sorting::Offset => refs.sort_by_key(|p| p.base_address),
-            Sorting::OffsetReversed => {
-                refs.sort_by_key(|p| -(p.base_address as i32));
-            }
-            Sorting::Name => refs.sort_by_key(|p| &p.name),
-        }
-    }
+/// Peripherals in the order they are encoded
+pub(crate) fn sorted_peripherals<'a>(
+    device: &'a Device,
+    config: &Config,
+) -> Vec<Cow<'a, Peripheral>> {
+    let peripherals = if config.peripheral_sorting == DerivableSorting::Unchanged(None) {
+        device.peripherals.iter().collect()
+    } else {
+        sort_derived_peripherals(&device.peripherals, config.peripheral_sorting, config)
+    };
+    schemaversion::peripherals(peripherals, config)
 }
 
-fn sort_derived_peripherals(
-    peripherals: &[Peripheral],
+fn sort_derived_peripherals<'a>(
+    peripherals: &'a [Peripheral],
     sorting: DerivableSorting,
-) -> Vec<&Peripheral> {
+    config: &Config,
+) -> Vec<&'a Peripheral> {
     match sorting {
         DerivableSorting::Unchanged(sorting) => {
             let mut refs = peripherals.iter().collect::<Vec<_>>();
-            sort_peripherals(&mut refs, sorting);
+            sort(&mut refs, sorting, config);
             refs
         }
         DerivableSorting::DeriveLast(sorting) => {
@@ -125,8 +144,8 @@ fn sort_derived_peripherals(
                 .iter()
                 .filter(|p| p.derived_from.is_some())
                 .collect::<Vec<_>>();
-            sort_peripherals(&mut common_refs, sorting);
-            sort_peripherals(&mut derived_refs, sorting);
+            sort(&mut common_refs, sorting, config);
+            sort(&mut derived_refs, sorting, config);
             common_refs.extend(derived_refs);
             common_refs
         }
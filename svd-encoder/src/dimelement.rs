@@ -1,4 +1,4 @@
-use crate::config::{change_case, format_number};
+use crate::config::{change_case_with_config, format_number};
 
 use super::{new_node, Config, Element, Encode, EncodeError};
 
@@ -45,7 +45,7 @@ impl Encode for crate::svd::DimArrayIndex {
         if let Some(d) = &self.header_enum_name {
             base.children.push(new_node(
                 "headerEnumName",
-                change_case(d, config.dim_array_index_header_enum_name),
+                change_case_with_config(d, config.dim_array_index_header_enum_name, config),
             ));
         }
 
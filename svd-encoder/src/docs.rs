@@ -0,0 +1,819 @@
+//! Register documentation generation.
+//!
+//! Documentation consists of an index page with peripherals and interrupts of the device,
+//! and a page for every peripheral with its address map and registers.
+//! Pages are built as a list of blocks, which are rendered as Markdown or HTML.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use super::EncodeError;
+use crate::export::{base_name, resolve};
+use crate::svd::{
+    self, Cluster, DeriveFrom, Device, DimElement, Field, FieldInfo, Peripheral, Register,
+    RegisterCluster, RegisterProperties,
+};
+
+/// Format of documentation pages
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DocsFormat {
+    /// GitHub flavored Markdown
+    #[default]
+    Markdown,
+    /// HTML pages with embedded style sheet
+    Html,
+}
+
+impl DocsFormat {
+    /// Extension of page files
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Options of documentation generation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DocsConfig {
+    /// Format of pages
+    pub format: DocsFormat,
+    /// Write tables of enumerated values of fields
+    pub enumerated_values: bool,
+}
+
+impl Default for DocsConfig {
+    fn default() -> Self {
+        Self {
+            format: DocsFormat::Markdown,
+            enumerated_values: true,
+        }
+    }
+}
+
+impl DocsConfig {
+    /// Format of pages
+    pub fn format(mut self, val: DocsFormat) -> Self {
+        self.format = val;
+        self
+    }
+
+    /// Write tables of enumerated values of fields
+    pub fn enumerated_values(mut self, val: bool) -> Self {
+        self.enumerated_values = val;
+        self
+    }
+}
+
+/// Documentation page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page {
+    /// File name of the page, like `index.md` or `UART0.html`.
+    /// Pages link to each other by these names
+    pub path: String,
+    /// Title of the page
+    pub title: String,
+    /// Content of the page
+    pub content: String,
+}
+
+pub(crate) fn generate(device: &Device, config: &DocsConfig) -> Result<Vec<Page>, EncodeError> {
+    let docs = Docs { device, config };
+    let mut pages = vec![docs.render("index", &device.name, &docs.index())];
+    for p in &device.peripherals {
+        let blocks = docs.peripheral(p)?;
+        pages.push(docs.render(&base_name(&p.name), &p.name, &blocks));
+    }
+    Ok(pages)
+}
+
+/// Part of text
+enum Inline {
+    Text(String),
+    Code(String),
+    Link(String, String),
+}
+
+type Cell = Vec<Inline>;
+
+/// Bits of a register from `msb` to `lsb`. `None` name for bits without field
+struct Segment {
+    msb: u32,
+    lsb: u32,
+    name: Option<String>,
+}
+
+enum Block {
+    /// Level, text and anchor
+    Heading(u8, String, Option<String>),
+    Paragraph(Vec<Inline>),
+    Table(Vec<&'static str>, Vec<Vec<Cell>>),
+    /// Bit-field diagram
+    Bits(Vec<Segment>),
+}
+
+struct Docs<'a> {
+    device: &'a Device,
+    config: &'a DocsConfig,
+}
+
+impl Docs<'_> {
+    fn link(&self, page: &str, anchor: Option<&str>, text: &str) -> Inline {
+        let mut href = format!("{}.{}", base_name(page), self.config.format.extension());
+        if let Some(anchor) = anchor {
+            href = format!("{href}#{anchor}");
+        }
+        Inline::Link(text.into(), href)
+    }
+
+    fn index(&self) -> Vec<Block> {
+        let d = self.device;
+        let mut blocks = vec![
+            Block::Heading(1, d.name.clone(), None),
+            Block::Paragraph(vec![text(&d.description)]),
+        ];
+        let mut properties = vec![row(["Version", &d.version])];
+        if let Some(vendor) = &d.vendor {
+            properties.push(row(["Vendor", vendor]));
+        }
+        if let Some(series) = &d.series {
+            properties.push(row(["Series", series]));
+        }
+        if let Some(cpu) = &d.cpu {
+            properties.push(row(["CPU", &format!("{} {}", cpu.name, cpu.revision)]));
+        }
+        properties.push(row(["Width", &d.width.to_string()]));
+        properties.push(row(["Address unit bits", &d.address_unit_bits.to_string()]));
+        blocks.push(Block::Table(vec!["Property", "Value"], properties));
+
+        blocks.push(Block::Heading(2, "Peripherals".into(), None));
+        let peripherals = d
+            .peripherals
+            .iter()
+            .map(|p| {
+                let derived = match &p.derived_from {
+                    Some(base) => vec![self.link(base, None, base)],
+                    None => Vec::new(),
+                };
+                vec![
+                    vec![self.link(&p.name, None, &p.name)],
+                    vec![Inline::Code(hex(p.base_address, 8))],
+                    vec![text(p.group_name.as_deref().unwrap_or_default())],
+                    derived,
+                    vec![text(p.description.as_deref().unwrap_or_default())],
+                ]
+            })
+            .collect();
+        blocks.push(Block::Table(
+            vec![
+                "Name",
+                "Base address",
+                "Group",
+                "Derived from",
+                "Description",
+            ],
+            peripherals,
+        ));
+
+        // Interrupts shared by peripherals are listed once
+        let mut interrupts = BTreeMap::<_, (Option<&str>, Vec<&str>)>::new();
+        for p in &d.peripherals {
+            for i in &p.interrupt {
+                let entry = interrupts.entry((i.value, &i.name)).or_default();
+                entry.0 = entry.0.or(i.description.as_deref());
+                entry.1.push(&p.name);
+            }
+        }
+        if !interrupts.is_empty() {
+            blocks.push(Block::Heading(2, "Interrupts".into(), None));
+            let rows = interrupts
+                .into_iter()
+                .map(|((value, name), (description, peripherals))| {
+                    let mut links = Vec::new();
+                    for (n, p) in peripherals.into_iter().enumerate() {
+                        if n > 0 {
+                            links.push(Inline::Text(", ".into()));
+                        }
+                        links.push(self.link(p, None, p));
+                    }
+                    vec![
+                        vec![text(&value.to_string())],
+                        vec![text(name)],
+                        links,
+                        vec![text(description.unwrap_or_default())],
+                    ]
+                })
+                .collect();
+            blocks.push(Block::Table(
+                vec!["Value", "Name", "Peripherals", "Description"],
+                rows,
+            ));
+        }
+        blocks
+    }
+
+    fn peripheral(&self, p: &Peripheral) -> Result<Vec<Block>, EncodeError> {
+        let mut blocks = vec![Block::Heading(1, p.name.clone(), None)];
+        if let Some(description) = &p.description {
+            blocks.push(Block::Paragraph(vec![text(description)]));
+        }
+        let mut properties = vec![vec![
+            vec![text("Base address")],
+            vec![Inline::Code(hex(p.base_address, 8))],
+        ]];
+        if let Some(group) = &p.group_name {
+            properties.push(row(["Group", group]));
+        }
+        if let Some(version) = &p.version {
+            properties.push(row(["Version", version]));
+        }
+        if let Some(base) = &p.derived_from {
+            properties.push(vec![
+                vec![text("Derived from")],
+                vec![self.link(base, None, base)],
+            ]);
+        }
+        if let Some(alternate) = &p.alternate_peripheral {
+            properties.push(vec![
+                vec![text("Alternate peripheral")],
+                vec![self.link(alternate, None, alternate)],
+            ]);
+        }
+        if let Peripheral::Array(_, dim) = p {
+            properties.push(vec![vec![text("Array")], dim_cell(dim)]);
+        }
+        if !p.interrupt.is_empty() {
+            let interrupts = p
+                .interrupt
+                .iter()
+                .map(|i| format!("{} ({})", i.name, i.value))
+                .collect::<Vec<_>>();
+            properties.push(row(["Interrupts", &interrupts.join(", ")]));
+        }
+        blocks.push(Block::Table(vec!["Property", "Value"], properties));
+
+        let base = p
+            .derived_from
+            .as_ref()
+            .map(|base| {
+                self.device
+                    .peripherals
+                    .iter()
+                    .find(|b| &b.name == base)
+                    .ok_or_else(|| EncodeError::UnknownDerivedFrom(p.name.clone(), base.clone()))
+            })
+            .transpose()?;
+        let derived;
+        let p = match base {
+            Some(base) if p.registers.is_none() => {
+                blocks.push(Block::Paragraph(vec![
+                    Inline::Text("Registers are the same as in ".into()),
+                    self.link(&base.name, None, &base.name),
+                    Inline::Text(".".into()),
+                ]));
+                return Ok(blocks);
+            }
+            Some(base) => {
+                derived = p.derive_from(base);
+                &derived
+            }
+            None => p,
+        };
+        let Some(registers) = &p.registers else {
+            return Ok(blocks);
+        };
+        let props = p
+            .default_register_properties
+            .derive_from(&self.device.default_register_properties);
+        let mut map = Vec::new();
+        let mut sections = Vec::new();
+        self.registers(
+            registers,
+            &props,
+            &Scope::default(),
+            &mut map,
+            &mut sections,
+        )?;
+        blocks.push(Block::Heading(2, "Address map".into(), None));
+        blocks.push(Block::Table(
+            vec![
+                "Offset",
+                "Name",
+                "Size",
+                "Access",
+                "Reset value",
+                "Description",
+            ],
+            map,
+        ));
+        blocks.push(Block::Heading(2, "Registers".into(), None));
+        blocks.extend(sections);
+        Ok(blocks)
+    }
+
+    fn registers(
+        &self,
+        children: &[RegisterCluster],
+        inherited: &RegisterProperties,
+        scope: &Scope,
+        map: &mut Vec<Vec<Cell>>,
+        sections: &mut Vec<Block>,
+    ) -> Result<(), EncodeError> {
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    let r = resolve(r, children, Some(self.device), |rc| match rc {
+                        RegisterCluster::Register(r) => Some(r),
+                        _ => None,
+                    })?;
+                    self.register(&r, inherited, scope, map, sections);
+                }
+                RegisterCluster::Cluster(c) => {
+                    let c = resolve(c, children, Some(self.device), |rc| match rc {
+                        RegisterCluster::Cluster(c) => Some(c),
+                        _ => None,
+                    })?;
+                    let props = c.default_register_properties.derive_from(inherited);
+                    let scope = scope.enter(&c.name, c.address_offset);
+                    let mut heading = format!("Cluster {}", scope.path);
+                    if let Cluster::Array(_, dim) = &*c {
+                        heading = format!("{heading} ({})", dim_text(dim));
+                    }
+                    sections.push(Block::Heading(3, heading, Some(base_name(&scope.path))));
+                    if let Some(description) = &c.description {
+                        sections.push(Block::Paragraph(vec![text(description)]));
+                    }
+                    self.registers(&c.children, &props, &scope, map, sections)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn register(
+        &self,
+        r: &Register,
+        inherited: &RegisterProperties,
+        scope: &Scope,
+        map: &mut Vec<Vec<Cell>>,
+        sections: &mut Vec<Block>,
+    ) {
+        let props = r.properties.derive_from(inherited);
+        let size = props.size.unwrap_or(32);
+        let access = props.access.unwrap_or_default();
+        let reset = props.reset_value.map(|v| hex(v, size.div_ceil(4) as usize));
+        let offset = scope.offset + u64::from(r.address_offset);
+        // Registers of alternate group overlap others with the same name
+        let path = match &r.alternate_group {
+            Some(group) => scope.path_of(&format!("{}_{group}", r.name)),
+            None => scope.path_of(&r.name),
+        };
+        let id = base_name(&path);
+        let description = r.description.as_deref().unwrap_or_default();
+
+        map.push(vec![
+            vec![Inline::Code(hex(offset, 3))],
+            vec![Inline::Link(path.clone(), format!("#{id}"))],
+            vec![text(&size.to_string())],
+            vec![text(access.as_str())],
+            reset.iter().map(|r| Inline::Code(r.clone())).collect(),
+            vec![text(description)],
+        ]);
+
+        sections.push(Block::Heading(3, path, Some(id)));
+        if !description.is_empty() {
+            sections.push(Block::Paragraph(vec![text(description)]));
+        }
+        let mut properties = vec![
+            vec![vec![text("Offset")], vec![Inline::Code(hex(offset, 3))]],
+            row(["Size", &size.to_string()]),
+            row(["Access", access.as_str()]),
+        ];
+        if let Some(reset) = &reset {
+            properties.push(vec![
+                vec![text("Reset value")],
+                vec![Inline::Code(reset.clone())],
+            ]);
+        }
+        if let Some(mask) = props.reset_mask {
+            properties.push(vec![
+                vec![text("Reset mask")],
+                vec![Inline::Code(hex(mask, size.div_ceil(4) as usize))],
+            ]);
+        }
+        if let Some(base) = &r.derived_from {
+            properties.push(vec![
+                vec![text("Derived from")],
+                vec![self.path_link(base, scope)],
+            ]);
+        }
+        if let Some(alternate) = &r.alternate_register {
+            properties.push(vec![
+                vec![text("Alternate register")],
+                vec![self.path_link(alternate, scope)],
+            ]);
+        }
+        if let Register::Array(_, dim) = r {
+            properties.push(vec![vec![text("Array")], dim_cell(dim)]);
+        }
+        sections.push(Block::Table(vec!["Property", "Value"], properties));
+
+        let Some(fields) = &r.fields else {
+            return;
+        };
+        let mut fields = fields
+            .iter()
+            .flat_map(|f| match f {
+                Field::Single(info) => vec![info.clone()],
+                Field::Array(info, dim) => svd::field::expand(info, dim).collect(),
+            })
+            .collect::<Vec<_>>();
+        fields.sort_by_key(|f| std::cmp::Reverse(f.bit_range.offset));
+        if fields.is_empty() {
+            return;
+        }
+        sections.push(Block::Bits(segments(&fields, size)));
+
+        let rows = fields
+            .iter()
+            .map(|f| {
+                let field_access = f.access.unwrap_or(access);
+                // Empty if the field lies outside the reset value
+                let field_reset = props
+                    .reset_value
+                    .and_then(|v| (v & f.bitmask()).checked_shr(f.bit_range.offset))
+                    .map(|v| Inline::Code(hex(v, 0)));
+                let mut description = vec![text(f.description.as_deref().unwrap_or_default())];
+                if let Some(base) = &f.derived_from {
+                    description.push(Inline::Text(" Derived from ".into()));
+                    description.push(Inline::Code(base.clone()));
+                    description.push(Inline::Text(".".into()));
+                }
+                vec![
+                    vec![text(&bits(f.bit_range.msb(), f.bit_range.lsb()))],
+                    vec![text(&f.name)],
+                    vec![text(field_access.as_str())],
+                    field_reset.into_iter().collect(),
+                    description,
+                ]
+            })
+            .collect();
+        sections.push(Block::Table(
+            vec!["Bits", "Name", "Access", "Reset", "Description"],
+            rows,
+        ));
+
+        if self.config.enumerated_values {
+            for f in &fields {
+                self.enumerated_values(f, sections);
+            }
+        }
+    }
+
+    fn enumerated_values(&self, f: &FieldInfo, sections: &mut Vec<Block>) {
+        for evs in &f.enumerated_values {
+            let mut heading = format!("{} values", f.name);
+            if let Some(usage) = evs.usage {
+                heading = format!("{heading} ({})", usage.as_str());
+            }
+            sections.push(Block::Heading(4, heading, None));
+            if let Some(base) = &evs.derived_from {
+                sections.push(Block::Paragraph(vec![
+                    Inline::Text("Derived from ".into()),
+                    Inline::Code(base.clone()),
+                    Inline::Text(".".into()),
+                ]));
+            }
+            if evs.values.is_empty() {
+                continue;
+            }
+            let rows = evs
+                .values
+                .iter()
+                .map(|ev| {
+                    let value = match ev.value {
+                        Some(value) => Inline::Code(hex(value, 0)),
+                        None => text("default"),
+                    };
+                    vec![
+                        vec![value],
+                        vec![text(&ev.name)],
+                        vec![text(ev.description.as_deref().unwrap_or_default())],
+                    ]
+                })
+                .collect();
+            sections.push(Block::Table(vec!["Value", "Name", "Description"], rows));
+        }
+    }
+
+    /// Link to element referenced by `derivedFrom` or alternate name.
+    /// Path starting with peripheral name links to its page
+    fn path_link(&self, path: &str, scope: &Scope) -> Inline {
+        if let Some((first, rest)) = path.split_once('.') {
+            if self.device.peripherals.iter().any(|p| p.name == first) {
+                return self.link(first, Some(&base_name(rest)), path);
+            }
+        }
+        Inline::Link(path.into(), format!("#{}", base_name(&scope.path_of(path))))
+    }
+
+    fn render(&self, name: &str, title: &str, blocks: &[Block]) -> Page {
+        let content = match self.config.format {
+            DocsFormat::Markdown => markdown(blocks),
+            DocsFormat::Html => html(title, blocks),
+        };
+        Page {
+            path: format!("{name}.{}", self.config.format.extension()),
+            title: title.into(),
+            content,
+        }
+    }
+}
+
+/// Path of clusters and their offset from the peripheral
+#[derive(Default)]
+struct Scope {
+    path: String,
+    offset: u64,
+}
+
+impl Scope {
+    fn path_of(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.into()
+        } else {
+            format!("{}.{name}", self.path)
+        }
+    }
+
+    fn enter(&self, name: &str, offset: u32) -> Self {
+        Self {
+            path: self.path_of(name),
+            offset: self.offset + u64::from(offset),
+        }
+    }
+}
+
+/// Segments of a register. `fields` are sorted from the most significant
+fn segments(fields: &[FieldInfo], size: u32) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut next = size;
+    for f in fields {
+        let msb = f.bit_range.msb();
+        if msb >= next {
+            // Overlapping fields are shown in the field table only
+            continue;
+        }
+        if msb + 1 < next {
+            segments.push(Segment {
+                msb: next - 1,
+                lsb: msb + 1,
+                name: None,
+            });
+        }
+        segments.push(Segment {
+            msb,
+            lsb: f.bit_range.lsb(),
+            name: Some(f.name.clone()),
+        });
+        next = f.bit_range.lsb();
+    }
+    if next > 0 {
+        segments.push(Segment {
+            msb: next - 1,
+            lsb: 0,
+            name: None,
+        });
+    }
+    segments
+}
+
+fn markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, title, anchor) => {
+                if let Some(anchor) = anchor {
+                    writeln!(out, "<a id=\"{}\"></a>\n", escape_html(anchor)).unwrap();
+                }
+                let level = "#".repeat(*level as usize);
+                writeln!(out, "{level} {}", escape_markdown(title)).unwrap();
+            }
+            Block::Paragraph(inlines) => out.push_str(&markdown_inlines(inlines)),
+            Block::Table(header, rows) => {
+                markdown_row(&mut out, header.iter().map(|h| h.to_string()));
+                markdown_row(&mut out, header.iter().map(|_| "---".into()));
+                for row in rows {
+                    markdown_row(&mut out, row.iter().map(|cell| markdown_inlines(cell)));
+                }
+            }
+            Block::Bits(segments) => {
+                let ranges = segments.iter().map(|s| bits(s.msb, s.lsb));
+                markdown_row(&mut out, ranges);
+                markdown_row(&mut out, segments.iter().map(|_| ":---:".into()));
+                let names = segments
+                    .iter()
+                    .map(|s| s.name.as_deref().map_or("-".into(), escape_markdown));
+                markdown_row(&mut out, names);
+            }
+        }
+        out.push('\n');
+        if !out.ends_with("\n\n") {
+            out.push('\n');
+        }
+    }
+    out.pop();
+    out
+}
+
+fn markdown_row(out: &mut String, cells: impl Iterator<Item = String>) {
+    out.push('|');
+    for cell in cells {
+        write!(out, " {cell} |").unwrap();
+    }
+    out.push('\n');
+}
+
+fn markdown_inlines(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|i| match i {
+            Inline::Text(t) => escape_markdown(t),
+            Inline::Code(c) => format!("`{c}`"),
+            Inline::Link(t, href) => format!("[{}]({href})", escape_markdown(t)),
+        })
+        .collect()
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn html(title: &str, blocks: &[Block]) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(out, "<title>{}</title>", escape_html(title)).unwrap();
+    writeln!(out, "<style>\n{STYLE}</style>\n</head>\n<body>").unwrap();
+    for block in blocks {
+        match block {
+            Block::Heading(level, title, anchor) => {
+                let id = anchor
+                    .as_ref()
+                    .map(|a| format!(" id=\"{}\"", escape_html(a)))
+                    .unwrap_or_default();
+                writeln!(out, "<h{level}{id}>{}</h{level}>", escape_html(title)).unwrap();
+            }
+            Block::Paragraph(inlines) => {
+                writeln!(out, "<p>{}</p>", html_inlines(inlines)).unwrap();
+            }
+            Block::Table(header, rows) => {
+                out.push_str("<table>\n<tr>");
+                for h in header {
+                    write!(out, "<th>{h}</th>").unwrap();
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        write!(out, "<td>{}</td>", html_inlines(cell)).unwrap();
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+            Block::Bits(segments) => {
+                out.push_str("<table class=\"bits\">\n<tr>");
+                let msb = segments.first().map_or(0, |s| s.msb);
+                for bit in (0..=msb).rev() {
+                    write!(out, "<th>{bit}</th>").unwrap();
+                }
+                out.push_str("</tr>\n<tr>");
+                for s in segments {
+                    let span = s.msb - s.lsb + 1;
+                    match &s.name {
+                        Some(name) => {
+                            write!(out, "<td colspan=\"{span}\">{}</td>", escape_html(name))
+                        }
+                        None => write!(out, "<td colspan=\"{span}\" class=\"reserved\"></td>"),
+                    }
+                    .unwrap();
+                }
+                out.push_str("</tr>\n</table>\n");
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn html_inlines(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|i| match i {
+            Inline::Text(t) => escape_html(t),
+            Inline::Code(c) => format!("<code>{}</code>", escape_html(c)),
+            Inline::Link(t, href) => {
+                format!("<a href=\"{}\">{}</a>", escape_html(href), escape_html(t))
+            }
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #999; padding: 0.2em 0.5em; text-align: left; }
+th { background: #eee; }
+table.bits td { text-align: center; }
+table.bits th { font-weight: normal; font-size: small; }
+td.reserved { background: #ddd; }
+";
+
+/// Text on one line
+fn text(s: &str) -> Inline {
+    Inline::Text(s.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn row<const N: usize>(cells: [&str; N]) -> Vec<Cell> {
+    cells.iter().map(|c| vec![text(c)]).collect()
+}
+
+fn dim_text(dim: &DimElement) -> String {
+    format!(
+        "{} elements with increment 0x{:X}",
+        dim.dim, dim.dim_increment
+    )
+}
+
+fn dim_cell(dim: &DimElement) -> Cell {
+    let indexes = dim.indexes().collect::<Vec<_>>();
+    vec![text(&format!(
+        "{}, indexes {}",
+        dim_text(dim),
+        indexes.join(", ")
+    ))]
+}
+
+/// Bit range like `5:4`, or bit number for single bit
+fn bits(msb: u32, lsb: u32) -> String {
+    if msb == lsb {
+        msb.to_string()
+    } else {
+        format!("{msb}:{lsb}")
+    }
+}
+
+fn hex(value: u64, digits: usize) -> String {
+    format!("0x{value:0digits$X}")
+}
+
+/// Name of element without array placeholders
+#[test]
+fn test_segments() {
+    let field = |name: &str, offset, width| {
+        FieldInfo::builder()
+            .name(name.into())
+            .bit_range(svd::BitRange::from_offset_width(offset, width))
+            .build(svd::ValidateLevel::Disabled)
+            .unwrap()
+    };
+    let fields = [field("MODE", 4, 2), field("EN", 0, 1)];
+    let segments = segments(&fields, 8)
+        .into_iter()
+        .map(|s| (s.msb, s.lsb, s.name))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        segments,
+        [
+            (7, 6, None),
+            (5, 4, Some("MODE".into())),
+            (3, 1, None),
+            (0, 0, Some("EN".into())),
+        ]
+    );
+}
@@ -1,7 +1,7 @@
 use super::{new_node, Config, Element, Encode, EncodeError};
 
 use crate::{
-    config::{change_case, format_number},
+    config::{change_case_with_config, format_number},
     svd::EnumeratedValue,
 };
 
@@ -12,7 +12,7 @@ impl Encode for EnumeratedValue {
         let mut base = Element::new("enumeratedValue");
         base.children.push(new_node(
             "name",
-            change_case(&self.name, config.enumerated_value_name),
+            change_case_with_config(&self.name, config.enumerated_value_name, config),
         ));
 
         if let Some(d) = &self.description {
@@ -1,6 +1,6 @@
 use super::{new_node, Config, Element, Encode, EncodeError};
 
-use crate::{config::change_case, svd::EnumeratedValues};
+use crate::{config::change_case_with_config, sorting::sort, svd::EnumeratedValues};
 
 impl Encode for EnumeratedValues {
     type Error = EncodeError;
@@ -11,7 +11,7 @@ impl Encode for EnumeratedValues {
         if let Some(d) = &self.name {
             base.children.push(new_node(
                 "name",
-                change_case(d, config.enumerated_values_name),
+                change_case_with_config(d, config.enumerated_values_name, config),
             ));
         };
 
@@ -22,11 +22,13 @@ impl Encode for EnumeratedValues {
         if let Some(v) = &self.derived_from {
             base.attributes.insert(
                 String::from("derivedFrom"),
-                change_case(v, config.enumerated_values_name),
+                change_case_with_config(v, config.enumerated_values_name, config),
             );
         }
 
-        for v in &self.values {
+        let mut values = self.values.iter().collect::<Vec<_>>();
+        sort(&mut values, config.enumerated_values_sorting, config);
+        for v in values {
             base.children.push(v.encode_node_with_config(config)?);
         }
 
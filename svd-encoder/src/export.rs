@@ -0,0 +1,177 @@
+//! Helpers shared by generators of other formats than SVD.
+
+use std::borrow::Cow;
+
+use super::{EncodeError, Unmapped};
+use crate::svd::{
+    self, BitRange, Cluster, DeriveFrom, Device, DimElement, FieldInfo, Peripheral, Register,
+    RegisterCluster, RegisterInfo, ValidateLevel,
+};
+
+/// Element with properties of the one it is derived from.
+///
+/// A name without dots is looked up among siblings. A dotted path is looked up
+/// from the peripheral of `device` it starts with, as `svd_parser::expand` does,
+/// and can't be resolved without the device
+pub(crate) fn resolve<'a, T>(
+    item: &'a T,
+    siblings: &'a [RegisterCluster],
+    device: Option<&'a Device>,
+    get: impl Fn(&'a RegisterCluster) -> Option<&'a T>,
+) -> Result<Cow<'a, T>, EncodeError>
+where
+    T: DeriveFrom + Clone + svd::Name + DerivedFrom,
+{
+    let Some(path) = item.derived() else {
+        return Ok(Cow::Borrowed(item));
+    };
+    let unknown = || EncodeError::UnknownDerivedFrom(item.name().into(), path.into());
+    let (children, name) = match path.rsplit_once('.') {
+        Some((block, name)) => {
+            let children = device
+                .and_then(|d| block_children(d, block))
+                .ok_or_else(unknown)?;
+            (children, name)
+        }
+        None => (siblings, path),
+    };
+    let base = children
+        .iter()
+        .filter_map(get)
+        .find(|s| s.name() == name)
+        .ok_or_else(unknown)?;
+    Ok(Cow::Owned(item.derive_from(base)))
+}
+
+/// Registers and clusters of the peripheral or cluster at dotted `path`
+fn block_children<'a>(device: &'a Device, path: &str) -> Option<&'a [RegisterCluster]> {
+    let mut names = path.split('.');
+    let peripheral = names.next()?;
+    let p = device.peripherals.iter().find(|p| match p {
+        Peripheral::Array(info, dim) => {
+            p.name == peripheral || svd::array::names(info, dim).any(|n| n == peripheral)
+        }
+        Peripheral::Single(_) => p.name == peripheral,
+    })?;
+    let mut children = p.registers.as_deref()?;
+    for name in names {
+        let c = children.iter().find_map(|rc| match rc {
+            RegisterCluster::Cluster(c) if c.name == name => Some(c),
+            _ => None,
+        })?;
+        children = &c.children;
+    }
+    Some(children)
+}
+
+/// Elements which may have `derivedFrom`
+pub(crate) trait DerivedFrom {
+    fn derived(&self) -> Option<&str>;
+}
+
+impl DerivedFrom for Register {
+    fn derived(&self) -> Option<&str> {
+        self.derived_from.as_deref()
+    }
+}
+
+impl DerivedFrom for Cluster {
+    fn derived(&self) -> Option<&str> {
+        self.derived_from.as_deref()
+    }
+}
+
+/// Elements which the output format can't express
+pub(crate) struct Report {
+    format: &'static str,
+    pub(crate) unmapped: Vec<Unmapped>,
+}
+
+impl Report {
+    pub(crate) fn new(format: &'static str) -> Self {
+        Self {
+            format,
+            unmapped: Vec::new(),
+        }
+    }
+
+    pub(crate) fn unmapped(&mut self, path: &str, element: &str) {
+        self.unmapped.push(Unmapped {
+            format: self.format,
+            path: path.into(),
+            element: element.into(),
+        });
+    }
+
+    /// Reports each of `elements` which is present
+    pub(crate) fn unmapped_if<const N: usize>(&mut self, path: &str, elements: [(&str, bool); N]) {
+        for (element, present) in elements {
+            if present {
+                self.unmapped(path, element);
+            }
+        }
+    }
+
+    /// Reports parts of array which arrays of the format can't express
+    pub(crate) fn dim(&mut self, dim: &DimElement, path: &str) {
+        self.unmapped_if(
+            path,
+            [
+                ("dimName", dim.dim_name.is_some()),
+                ("dimArrayIndex", dim.dim_array_index.is_some()),
+            ],
+        );
+    }
+}
+
+/// Whether the array has `[%s]` placeholder and is indexed from 0
+pub(crate) fn is_indexed_from_zero(name: &str, dim: &DimElement) -> bool {
+    if !name.ends_with("[%s]") {
+        return false;
+    }
+    match &dim.dim_index {
+        None => true,
+        Some(indexes) => indexes
+            .iter()
+            .enumerate()
+            .all(|(i, index)| index == &i.to_string()),
+    }
+}
+
+/// Name without array placeholders
+pub(crate) fn base_name(name: &str) -> String {
+    name.replace("[%s]", "").replace("%s", "")
+}
+
+/// Name of register without array placeholders. Group name is appended to names
+/// of registers of alternate groups as in CMSIS headers, so they differ from others
+pub(crate) fn register_name(r: &RegisterInfo) -> String {
+    let name = base_name(&r.name);
+    match &r.alternate_group {
+        Some(group) => format!("{name}_{group}"),
+        None => name,
+    }
+}
+
+/// Field named as the register covering all its `size` bits.
+/// Stands for a register without fields in formats where registers have at least one field
+pub(crate) fn whole_register_field(r: &RegisterInfo, size: u32) -> FieldInfo {
+    FieldInfo::builder()
+        .name(register_name(r))
+        .bit_range(BitRange::from_offset_width(0, size))
+        .build(ValidateLevel::Disabled)
+        .expect("name and bit range are set")
+}
+
+/// Mask of the lowest `size` bits of register value
+pub(crate) fn value_mask(size: u32) -> u64 {
+    (!0u64).checked_shr(64u32.saturating_sub(size)).unwrap_or(0)
+}
+
+/// Whether the bit range fits in 64-bit register value
+pub(crate) fn fits(bit_range: &BitRange) -> bool {
+    bit_range
+        .offset
+        .checked_add(bit_range.width)
+        .is_some_and(|end| end <= 64)
+}
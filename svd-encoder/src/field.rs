@@ -1,13 +1,14 @@
 use super::{new_node, Config, Element, ElementMerge, Encode, EncodeError, XMLNode};
 use crate::bitrange::encode_bitrange;
 
-use crate::config::change_case;
+use crate::config::change_case_with_config;
 use crate::svd::{Field, FieldInfo};
 
 impl Encode for Field {
     type Error = EncodeError;
 
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
+        let config = &*config.enter(&self.name);
         match self {
             Self::Single(info) => info.encode_with_config(config),
             Self::Array(info, array_info) => {
@@ -25,8 +26,10 @@ impl Encode for FieldInfo {
 
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
         let mut elem = Element::new("field");
-        elem.children
-            .push(new_node("name", change_case(&self.name, config.field_name)));
+        elem.children.push(new_node(
+            "name",
+            change_case_with_config(&self.name, config.field_name, config),
+        ));
 
         if let Some(description) = &self.description {
             elem.children
@@ -63,7 +66,7 @@ impl Encode for FieldInfo {
         if let Some(v) = &self.derived_from {
             elem.attributes.insert(
                 String::from("derivedFrom"),
-                change_case(v, config.field_name),
+                change_case_with_config(v, config.field_name, config),
             );
         }
 
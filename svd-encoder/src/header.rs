@@ -0,0 +1,877 @@
+//! CMSIS-Core device header generation.
+//!
+//! The header has the same sections as the ones generated by SVDConv:
+//! interrupt numbers, processor configuration, peripheral structures, memory map,
+//! peripheral declarations and `_Pos`/`_Msk` macros of fields.
+//!
+//! Registers and clusters which overlap, like alternate registers, are placed in unions.
+//! Gaps between registers are filled with `RESERVED` members.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use super::EncodeError;
+use crate::export::{base_name, fits, resolve};
+use crate::svd::{
+    self, Access, Cluster, Cpu, DeriveFrom, Device, DimElement, Field, Peripheral, PeripheralInfo,
+    Register, RegisterCluster, RegisterInfo, RegisterProperties,
+};
+
+/// Options of C header generation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeaderConfig {
+    /// Write `_Pos` and `_Msk` macros of fields
+    pub fields: bool,
+    /// Write macros of enumerated values of fields
+    pub enumerated_values: bool,
+}
+
+impl Default for HeaderConfig {
+    fn default() -> Self {
+        Self {
+            fields: true,
+            enumerated_values: false,
+        }
+    }
+}
+
+impl HeaderConfig {
+    /// Write `_Pos` and `_Msk` macros of fields
+    pub fn fields(mut self, val: bool) -> Self {
+        self.fields = val;
+        self
+    }
+
+    /// Write macros of enumerated values of fields
+    pub fn enumerated_values(mut self, val: bool) -> Self {
+        self.enumerated_values = val;
+        self
+    }
+}
+
+/// Column of member comments
+const COMMENT_COLUMN: usize = 48;
+
+pub(crate) fn generate(device: &Device, config: &HeaderConfig) -> Result<String, EncodeError> {
+    let mut g = Generator {
+        device,
+        config,
+        prefix: device
+            .header_definitions_prefix
+            .as_deref()
+            .unwrap_or_default(),
+        types: String::new(),
+        type_names: HashSet::new(),
+        enums: String::new(),
+        macros: String::new(),
+        defined: HashSet::new(),
+    };
+    let mut instances = Vec::new();
+    for p in &device.peripherals {
+        instances.extend(g.peripheral(p)?);
+    }
+
+    let guard = format!("{}_H", identifier(&device.name).to_uppercase());
+    let mut out = String::new();
+    out.push_str("/*\n");
+    writeln!(out, " * @file     {}.h", device.name).unwrap();
+    out.push_str(" * @brief    CMSIS HeaderFile\n");
+    writeln!(out, " * @version  {}", device.version).unwrap();
+    writeln!(
+        out,
+        " * @note     Generated from CMSIS-SVD description of {}",
+        device.name
+    )
+    .unwrap();
+    out.push_str(" */\n\n");
+    if let Some(license) = &device.license_text {
+        out.push_str("/*\n");
+        for line in license.trim().lines() {
+            writeln!(out, " * {}", comment(line)).unwrap();
+        }
+        out.push_str(" */\n\n");
+    }
+    writeln!(out, "#ifndef {guard}\n#define {guard}\n").unwrap();
+    out.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+
+    banner(&mut out, "Interrupt Number Definition");
+    out.push_str("typedef enum {\n");
+    if let Some(cpu) = &device.cpu {
+        banner(
+            &mut out,
+            &format!("{} Specific Interrupt Numbers", cpu.name),
+        );
+        for (name, value, description) in core_exceptions(cpu) {
+            enumerator(&mut out, &format!("{name}_IRQn"), value, description);
+        }
+    }
+    banner(
+        &mut out,
+        &format!("{} Specific Interrupt Numbers", device.name),
+    );
+    for (name, value, description) in interrupts(device) {
+        enumerator(&mut out, &format!("{name}_IRQn"), value, &description);
+    }
+    out.push_str("} IRQn_Type;\n\n");
+
+    if let Some(cpu) = &device.cpu {
+        banner(&mut out, "Processor and Core Peripheral Section");
+        processor(&mut out, device, cpu);
+    }
+    out.push_str(COMPATIBILITY);
+
+    banner(&mut out, "Device Specific Peripheral Section");
+    out.push_str(UNIONS_START);
+    out.push_str(&g.enums);
+    out.push_str(&g.types);
+    out.push_str(UNIONS_END);
+
+    banner(&mut out, "Device Specific Peripheral Address Map");
+    for i in &instances {
+        define(&mut out, &format!("{}_BASE", i.name), &hex(i.base_address));
+    }
+    out.push('\n');
+
+    banner(&mut out, "Peripheral declaration");
+    for i in &instances {
+        let value = format!("(({}*) {}_BASE)", i.type_name, i.name);
+        define(&mut out, &i.name, &value);
+    }
+    out.push('\n');
+
+    if !g.macros.is_empty() {
+        banner(&mut out, "Pos/Mask Peripheral Section");
+        out.push_str(&g.macros);
+    }
+
+    out.push_str("#ifdef __cplusplus\n}\n#endif\n\n");
+    writeln!(out, "#endif /* {guard} */").unwrap();
+    Ok(out)
+}
+
+/// Peripheral instance in the memory map
+struct Instance {
+    name: String,
+    type_name: String,
+    base_address: u64,
+}
+
+/// Member of a structure
+struct Member {
+    offset: u64,
+    size: u64,
+    decl: String,
+    description: String,
+}
+
+struct Generator<'a> {
+    device: &'a Device,
+    config: &'a HeaderConfig,
+    prefix: &'a str,
+    /// Structure definitions
+    types: String,
+    type_names: HashSet<String>,
+    /// Enumerations of array indexes
+    enums: String,
+    /// Macros of fields
+    macros: String,
+    defined: HashSet<String>,
+}
+
+impl Generator<'_> {
+    fn peripheral(&mut self, p: &Peripheral) -> Result<Vec<Instance>, EncodeError> {
+        let derived;
+        let p = match &p.derived_from {
+            Some(base_name) => {
+                let base = self.find_peripheral(base_name, &p.name)?;
+                if p.registers.is_none() {
+                    // Shares the structure of the base peripheral
+                    let type_name = self.type_name(base);
+                    return Ok(instances(p, type_name));
+                }
+                derived = p.derive_from(base);
+                &derived
+            }
+            None => p,
+        };
+        let type_name = self.type_name(p);
+        if let Some(dim) = array_dim(p) {
+            self.dim_enum(&p.name, dim);
+        }
+        if let Some(registers) = &p.registers {
+            let props = p
+                .default_register_properties
+                .derive_from(&self.device.default_register_properties);
+            let scope = base_name(&type_name[self.prefix.len()..type_name.len() - 5]);
+            let naming = Naming {
+                prepend: p.prepend_to_name.as_deref().unwrap_or_default(),
+                append: p.append_to_name.as_deref().unwrap_or_default(),
+            };
+            let members = self.members(registers, &props, &scope, &naming)?;
+            let description = format!("{} Structure", p.name);
+            self.structure(&type_name, members, None, &description);
+        }
+        Ok(instances(p, type_name))
+    }
+
+    fn find_peripheral(&self, name: &str, derived: &str) -> Result<&Peripheral, EncodeError> {
+        self.device
+            .peripherals
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| EncodeError::UnknownDerivedFrom(derived.into(), name.into()))
+    }
+
+    /// Structure type of the peripheral
+    fn type_name(&self, p: &PeripheralInfo) -> String {
+        let name = match (&p.header_struct_name, &p.derived_from) {
+            (Some(name), _) => name.clone(),
+            (None, Some(base)) if p.registers.is_none() => base_name(base),
+            _ => base_name(&p.name),
+        };
+        format!("{}{name}_Type", self.prefix)
+    }
+
+    fn members(
+        &mut self,
+        children: &[RegisterCluster],
+        inherited: &RegisterProperties,
+        scope: &str,
+        naming: &Naming,
+    ) -> Result<Vec<Member>, EncodeError> {
+        let mut members = Vec::new();
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    let r = resolve(r, children, Some(self.device), |rc| match rc {
+                        RegisterCluster::Register(r) => Some(r),
+                        _ => None,
+                    })?;
+                    self.register(&r, inherited, scope, naming, &mut members)?;
+                }
+                RegisterCluster::Cluster(c) => {
+                    let c = resolve(c, children, Some(self.device), |rc| match rc {
+                        RegisterCluster::Cluster(c) => Some(c),
+                        _ => None,
+                    })?;
+                    self.cluster(&c, inherited, scope, naming, &mut members)?;
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    fn register(
+        &mut self,
+        r: &Register,
+        inherited: &RegisterProperties,
+        scope: &str,
+        naming: &Naming,
+        members: &mut Vec<Member>,
+    ) -> Result<(), EncodeError> {
+        let props = r.properties.derive_from(inherited);
+        let (ctype, size) = match r.datatype {
+            Some(datatype) => (datatype.as_str(), datatype_size(datatype.as_str())),
+            None => c_type(props.size.unwrap_or(32)),
+        };
+        let qualifier = qualifier(props.access);
+        let member_name = |info: &RegisterInfo| {
+            let mut name = format!("{}{}{}", naming.prepend, info.name, naming.append);
+            if let Some(group) = &info.alternate_group {
+                name = format!("{name}_{group}");
+            }
+            name
+        };
+        match r {
+            Register::Single(info) => members.push(Member {
+                offset: info.address_offset.into(),
+                size,
+                decl: format!("{qualifier} {ctype} {};", member_name(info)),
+                description: description(info.description.as_deref(), &info.name),
+            }),
+            Register::Array(info, dim) => {
+                self.dim_enum(&info.name, dim);
+                if info.name.contains("[%s]") && u64::from(dim.dim_increment) == size {
+                    let name = member_name(info).replace("[%s]", "");
+                    members.push(Member {
+                        offset: info.address_offset.into(),
+                        size: size * u64::from(dim.dim),
+                        decl: format!("{qualifier} {ctype} {name}[{}];", dim.dim),
+                        description: description(info.description.as_deref(), &info.name),
+                    });
+                } else {
+                    for info in svd::register::expand(info, dim) {
+                        members.push(Member {
+                            offset: info.address_offset.into(),
+                            size,
+                            decl: format!("{qualifier} {ctype} {};", member_name(&info)),
+                            description: description(info.description.as_deref(), &info.name),
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(fields) = &r.fields {
+            let scope = format!("{scope}_{}", base_name(&r.name));
+            self.fields(fields, &scope)?;
+        }
+        Ok(())
+    }
+
+    fn cluster(
+        &mut self,
+        c: &Cluster,
+        inherited: &RegisterProperties,
+        scope: &str,
+        naming: &Naming,
+        members: &mut Vec<Member>,
+    ) -> Result<(), EncodeError> {
+        let props = c.default_register_properties.derive_from(inherited);
+        let base = base_name(&c.name);
+        let type_base = match (&c.header_struct_name, c) {
+            (Some(name), _) => name.clone(),
+            (
+                None,
+                Cluster::Array(
+                    _,
+                    DimElement {
+                        dim_name: Some(name),
+                        ..
+                    },
+                ),
+            ) => name.clone(),
+            (None, _) => format!("{scope}_{base}"),
+        };
+        let type_name = format!("{}{type_base}_Type", self.prefix);
+        let children = self.members(&c.children, &props, &format!("{scope}_{base}"), naming)?;
+        let end = children
+            .iter()
+            .map(|m| m.offset + m.size)
+            .max()
+            .unwrap_or(0);
+        let description = description(c.description.as_deref(), &c.name);
+        match c {
+            Cluster::Single(info) => {
+                self.structure(&type_name, children, None, &description);
+                members.push(Member {
+                    offset: info.address_offset.into(),
+                    size: end,
+                    decl: format!("{type_name} {};", info.name),
+                    description,
+                });
+            }
+            Cluster::Array(info, dim) => {
+                self.dim_enum(&info.name, dim);
+                let increment = u64::from(dim.dim_increment);
+                // Structure is padded to the increment, so it can be used in C array
+                let size = if increment >= end {
+                    Some(increment)
+                } else {
+                    None
+                };
+                self.structure(&type_name, children, size, &description);
+                if info.name.contains("[%s]") && size.is_some() {
+                    members.push(Member {
+                        offset: info.address_offset.into(),
+                        size: increment * u64::from(dim.dim),
+                        decl: format!("{type_name} {base}[{}];", dim.dim),
+                        description,
+                    });
+                } else {
+                    for info in svd::cluster::expand(info, dim) {
+                        members.push(Member {
+                            offset: info.address_offset.into(),
+                            size: end,
+                            decl: format!("{type_name} {};", info.name),
+                            description: description.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fields(&mut self, fields: &[Field], scope: &str) -> Result<(), EncodeError> {
+        if !self.config.fields {
+            return Ok(());
+        }
+        let mut out = String::new();
+        for f in fields {
+            let infos = match f {
+                Field::Single(info) => vec![info.clone()],
+                Field::Array(info, dim) => svd::field::expand(info, dim).collect(),
+            };
+            for info in infos {
+                let name = format!("{scope}_{}", info.name);
+                if !self.defined.insert(name.clone()) {
+                    continue;
+                }
+                let offset = info.bit_range.offset;
+                if !fits(&info.bit_range) {
+                    return Err(EncodeError::FieldTooWide(name));
+                }
+                let mask = info.bitmask();
+                define_commented(
+                    &mut out,
+                    &format!("{name}_Pos"),
+                    &format!("({offset}UL)"),
+                    &format!("{} (Bit {offset})", info.name),
+                );
+                define_commented(
+                    &mut out,
+                    &format!("{name}_Msk"),
+                    &format!("(0x{mask:X}UL)"),
+                    &format!("{} (Bitfield-Mask: 0x{:02x})", info.name, mask >> offset),
+                );
+                if self.config.enumerated_values {
+                    for ev in info.enumerated_values.iter().flat_map(|evs| &evs.values) {
+                        if let Some(value) = ev.value {
+                            define_commented(
+                                &mut out,
+                                &format!("{name}_{}", ev.name),
+                                &format!("({value}UL)"),
+                                &description(ev.description.as_deref(), &ev.name),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        if !out.is_empty() {
+            writeln!(self.macros, "/* {:=^60} */", format!("  {scope}  ")).unwrap();
+            self.macros.push_str(&out);
+            self.macros.push('\n');
+        }
+        Ok(())
+    }
+
+    /// Enumeration of `dimArrayIndex` values
+    fn dim_enum(&mut self, name: &str, dim: &DimElement) {
+        let Some(dai) = &dim.dim_array_index else {
+            return;
+        };
+        let enum_name = dai
+            .header_enum_name
+            .clone()
+            .unwrap_or_else(|| base_name(name));
+        let type_name = format!("{}{enum_name}_Enum", self.prefix);
+        if !self.type_names.insert(type_name.clone()) {
+            return;
+        }
+        self.enums.push_str("typedef enum {\n");
+        for ev in &dai.values {
+            if let Some(value) = ev.value {
+                let description = description(ev.description.as_deref(), &ev.name);
+                enumerator(
+                    &mut self.enums,
+                    &format!("{enum_name}_{}", ev.name),
+                    value as i64,
+                    &description,
+                );
+            }
+        }
+        writeln!(self.enums, "}} {type_name};\n").unwrap();
+    }
+
+    /// Writes structure definition with gaps filled by reserved members
+    fn structure(
+        &mut self,
+        type_name: &str,
+        mut members: Vec<Member>,
+        size: Option<u64>,
+        description: &str,
+    ) {
+        if !self.type_names.insert(type_name.into()) {
+            return;
+        }
+        members.sort_by_key(|m| m.offset);
+        let mut reserved = 0;
+        let mut body = Vec::new();
+        let mut end = 0;
+        let mut members = members.into_iter().peekable();
+        while let Some(first) = members.next() {
+            if first.offset > end {
+                body.push(reserve(end, first.offset - end, &mut reserved));
+            }
+            // Members which overlap with the first one are placed in union
+            let mut group_end = first.offset + first.size;
+            let mut group = vec![first];
+            while let Some(m) = members.next_if(|m| m.offset < group_end) {
+                group_end = group_end.max(m.offset + m.size);
+                group.push(m);
+            }
+            if group.len() == 1 {
+                body.push(line(1, &group[0].decl, &member_comment(&group[0])));
+            } else {
+                let start = group[0].offset;
+                body.push("  union {".into());
+                for m in &group {
+                    if m.offset == start {
+                        body.push(line(2, &m.decl, &member_comment(m)));
+                    } else {
+                        body.push("    struct {".into());
+                        body.push(format!(
+                            "      {}",
+                            reserve(start, m.offset - start, &mut reserved).trim_start()
+                        ));
+                        body.push(line(3, &m.decl, &member_comment(m)));
+                        body.push("    };".into());
+                    }
+                }
+                body.push("  };".into());
+            }
+            end = group_end;
+        }
+        if let Some(size) = size.filter(|size| *size > end) {
+            body.push(reserve(end, size - end, &mut reserved));
+        }
+        let open = format!("typedef struct {{{:1$}/*!< {description} */", "", 33);
+        self.types.push_str(&open);
+        self.types.push('\n');
+        for l in body {
+            self.types.push_str(&l);
+            self.types.push('\n');
+        }
+        writeln!(self.types, "}} {type_name};\n").unwrap();
+    }
+}
+
+/// Names of registers in the peripheral
+struct Naming<'a> {
+    prepend: &'a str,
+    append: &'a str,
+}
+
+fn array_dim(p: &Peripheral) -> Option<&DimElement> {
+    match p {
+        Peripheral::Single(_) => None,
+        Peripheral::Array(_, dim) => Some(dim),
+    }
+}
+
+fn instances(p: &Peripheral, type_name: String) -> Vec<Instance> {
+    match p {
+        Peripheral::Single(info) => vec![Instance {
+            name: info.name.clone(),
+            type_name,
+            base_address: info.base_address,
+        }],
+        Peripheral::Array(info, dim) => svd::peripheral::expand(info, dim)
+            .map(|info| Instance {
+                name: info.name,
+                type_name: type_name.clone(),
+                base_address: info.base_address,
+            })
+            .collect(),
+    }
+}
+
+/// Interrupts of all peripherals sorted by number
+fn interrupts(device: &Device) -> Vec<(String, i64, String)> {
+    let mut seen = HashSet::new();
+    let mut interrupts = device
+        .peripherals
+        .iter()
+        .flat_map(|p| &p.interrupt)
+        .filter(|i| seen.insert(i.name.clone()))
+        .map(|i| {
+            let description = description(i.description.as_deref(), &i.name);
+            (i.name.clone(), i64::from(i.value), description)
+        })
+        .collect::<Vec<_>>();
+    interrupts.sort_by_key(|(_, value, _)| *value);
+    interrupts
+}
+
+/// Exceptions of the processor core
+fn core_exceptions(cpu: &Cpu) -> Vec<(&'static str, i64, &'static str)> {
+    let core = core_name(&cpu.name);
+    let baseline = matches!(core.as_str(), "cm0" | "cm0plus" | "cm1" | "cm23");
+    let armv8m = matches!(core.as_str(), "cm33" | "cm35p" | "cm52" | "cm55" | "cm85");
+    let mut exceptions = vec![
+        (
+            "Reset",
+            -15,
+            "Reset Vector, invoked on Power up and warm reset",
+        ),
+        (
+            "NonMaskableInt",
+            -14,
+            "Non maskable Interrupt, cannot be stopped or preempted",
+        ),
+        ("HardFault", -13, "Hard Fault, all classes of Fault"),
+    ];
+    if !baseline {
+        exceptions.extend([
+            (
+                "MemoryManagement",
+                -12,
+                "Memory Management, MPU mismatch, including Access Violation and No Match",
+            ),
+            (
+                "BusFault",
+                -11,
+                "Bus Fault, Pre-Fetch-, Memory Access Fault, other address/memory related Fault",
+            ),
+            (
+                "UsageFault",
+                -10,
+                "Usage Fault, i.e. Undef Instruction, Illegal State Transition",
+            ),
+        ]);
+        if armv8m {
+            exceptions.push(("SecureFault", -9, "Secure Fault Handler"));
+        }
+    }
+    exceptions.push(("SVCall", -5, "System Service Call via SVC instruction"));
+    if !baseline {
+        exceptions.push(("DebugMonitor", -4, "Debug Monitor"));
+    }
+    exceptions.extend([
+        ("PendSV", -2, "Pendable request for system service"),
+        ("SysTick", -1, "System Tick Timer"),
+    ]);
+    exceptions
+}
+
+fn processor(out: &mut String, device: &Device, cpu: &Cpu) {
+    let core = core_name(&cpu.name);
+    let flag = |b: bool| u8::from(b).to_string();
+    define(
+        out,
+        &format!("__{}_REV", core.to_uppercase()),
+        &revision(&cpu.revision),
+    );
+    define(out, "__MPU_PRESENT", &flag(cpu.mpu_present));
+    define(out, "__NVIC_PRIO_BITS", &cpu.nvic_priority_bits.to_string());
+    define(out, "__Vendor_SysTickConfig", &flag(cpu.has_vendor_systick));
+    define(out, "__FPU_PRESENT", &flag(cpu.fpu_present));
+    let optional = [
+        ("__FPU_DP", cpu.fpu_double_precision),
+        ("__DSP_PRESENT", cpu.dsp_present),
+        ("__ICACHE_PRESENT", cpu.icache_present),
+        ("__DCACHE_PRESENT", cpu.dcache_present),
+        ("__ITCM_PRESENT", cpu.itcm_present),
+        ("__DTCM_PRESENT", cpu.dtcm_present),
+        ("__VTOR_PRESENT", cpu.vtor_present),
+    ];
+    for (name, value) in optional {
+        if let Some(value) = value {
+            define(out, name, &flag(value));
+        }
+    }
+    if let Some(regions) = cpu.sau_num_regions {
+        define(out, "__SAUREGION_PRESENT", &flag(regions > 0));
+    }
+    out.push('\n');
+    writeln!(out, "#include \"core_{core}.h\"").unwrap();
+    let system = match &device.header_system_filename {
+        Some(name) => name.clone(),
+        None => format!("system_{}", device.name),
+    };
+    writeln!(out, "#include \"{system}.h\"\n").unwrap();
+}
+
+/// Name of the core in CMSIS header file names, like `cm0plus`
+fn core_name(cpu: &str) -> String {
+    match cpu {
+        "CM0+" | "CM0PLUS" => "cm0plus".into(),
+        "CM35P" | "CM35P+" => "cm35p".into(),
+        "CA5" | "CA7" | "CA9" => cpu.to_lowercase(),
+        _ => identifier(cpu).to_lowercase(),
+    }
+}
+
+/// Core revision `rNpM` as `0xNNMMU`
+fn revision(revision: &str) -> String {
+    let parsed = revision
+        .strip_prefix('r')
+        .and_then(|r| r.split_once('p'))
+        .and_then(|(r, p)| Some((r.parse::<u8>().ok()?, p.parse::<u8>().ok()?)));
+    let (r, p) = parsed.unwrap_or_default();
+    format!("0x{r:02X}{p:02X}U")
+}
+
+fn qualifier(access: Option<Access>) -> &'static str {
+    match access {
+        Some(Access::ReadOnly) => "__IM ",
+        Some(Access::WriteOnly | Access::WriteOnce) => "__OM ",
+        _ => "__IOM",
+    }
+}
+
+/// C type and its size in bytes for register of `bits` size
+fn c_type(bits: u32) -> (&'static str, u64) {
+    match bits {
+        0..=8 => ("uint8_t", 1),
+        9..=16 => ("uint16_t", 2),
+        17..=32 => ("uint32_t", 4),
+        _ => ("uint64_t", 8),
+    }
+}
+
+fn datatype_size(ctype: &str) -> u64 {
+    if ctype.ends_with('*') {
+        return 4;
+    }
+    match ctype.trim_start_matches('u') {
+        "int8_t" => 1,
+        "int16_t" => 2,
+        "int64_t" => 8,
+        _ => 4,
+    }
+}
+
+/// Reserved member filling `size` bytes at `offset`
+fn reserve(offset: u64, size: u64, count: &mut usize) -> String {
+    let (ctype, bytes) = [("uint32_t", 4), ("uint16_t", 2), ("uint8_t", 1)]
+        .into_iter()
+        .find(|(_, bytes)| offset % bytes == 0 && size % bytes == 0)
+        .unwrap();
+    let name = match *count {
+        0 => String::from("RESERVED"),
+        n => format!("RESERVED{n}"),
+    };
+    *count += 1;
+    let decl = match size / bytes {
+        1 => format!("__IM  {ctype} {name};"),
+        n => format!("__IM  {ctype} {name}[{n}];"),
+    };
+    format!("  {decl}")
+}
+
+fn member_comment(m: &Member) -> String {
+    format!("(@ 0x{:08X}) {}", m.offset, m.description)
+}
+
+/// Declaration with comment aligned to [`COMMENT_COLUMN`]
+fn line(level: usize, decl: &str, comment: &str) -> String {
+    let decl = format!("{}{decl}", "  ".repeat(level));
+    let width = COMMENT_COLUMN.max(decl.len() + 1);
+    format!("{decl:width$}/*!< {comment} */")
+}
+
+fn define(out: &mut String, name: &str, value: &str) {
+    let decl = format!("#define {name}");
+    let width = COMMENT_COLUMN.max(decl.len() + 1);
+    writeln!(out, "{decl:width$}{value}").unwrap();
+}
+
+fn define_commented(out: &mut String, name: &str, value: &str, comment: &str) {
+    let decl = format!("#define {name}");
+    let width = COMMENT_COLUMN.max(decl.len() + 1);
+    let value = format!("{value:18}");
+    writeln!(out, "{decl:width$}{value}/*!< {comment} */").unwrap();
+}
+
+fn enumerator(out: &mut String, name: &str, value: i64, comment: &str) {
+    let decl = format!("  {name}");
+    let width = (COMMENT_COLUMN - 10).max(decl.len() + 1);
+    writeln!(
+        out,
+        "{decl:width$}= {value:>4},   /*!< {value:>3}  {comment} */"
+    )
+    .unwrap();
+}
+
+fn banner(out: &mut String, title: &str) {
+    let line = "=".repeat(123);
+    writeln!(out, "/* {line} */").unwrap();
+    writeln!(out, "/* {:=^123} */", format!("  {title}  ")).unwrap();
+    writeln!(out, "/* {line} */\n").unwrap();
+}
+
+fn hex(address: u64) -> String {
+    if address > u64::from(u32::MAX) {
+        format!("0x{address:016X}ULL")
+    } else {
+        format!("0x{address:08X}UL")
+    }
+}
+
+/// Description on one line, or `name` if there is no description
+fn description(description: Option<&str>, name: &str) -> String {
+    let text = description.unwrap_or(name);
+    comment(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Text which can't close C comment
+fn comment(text: &str) -> String {
+    text.replace("*/", "* /")
+}
+
+/// Replaces characters which are not allowed in C identifiers
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+const COMPATIBILITY: &str = "\
+#ifndef __IM                                    /*!< Fallback for older CMSIS versions */
+  #define __IM   __I
+#endif
+#ifndef __OM                                    /*!< Fallback for older CMSIS versions */
+  #define __OM   __O
+#endif
+#ifndef __IOM                                   /*!< Fallback for older CMSIS versions */
+  #define __IOM  __IO
+#endif
+
+";
+
+const UNIONS_START: &str = "\
+/* -------------------  Start of section using anonymous unions  ------------------- */
+#if defined (__CC_ARM)
+  #pragma push
+  #pragma anon_unions
+#elif defined (__ICCARM__)
+  #pragma language=extended
+#elif defined(__ARMCC_VERSION) && (__ARMCC_VERSION >= 6010050)
+  #pragma clang diagnostic push
+  #pragma clang diagnostic ignored \"-Wc11-extensions\"
+  #pragma clang diagnostic ignored \"-Wreserved-id-macro\"
+  #pragma clang diagnostic ignored \"-Wgnu-anonymous-struct\"
+  #pragma clang diagnostic ignored \"-Wnested-anon-types\"
+#elif defined (__GNUC__)
+  /* anonymous unions are enabled by default */
+#elif defined (__TMS470__)
+  /* anonymous unions are enabled by default */
+#elif defined (__TASKING__)
+  #pragma warning 586
+#elif defined (__CSMC__)
+  /* anonymous unions are enabled by default */
+#else
+  #warning Not supported compiler type
+#endif
+
+";
+
+const UNIONS_END: &str = "\
+/* --------------------  End of section using anonymous unions  ------------------- */
+#if defined (__CC_ARM)
+  #pragma pop
+#elif defined (__ICCARM__)
+  /* leave anonymous unions enabled */
+#elif (defined(__ARMCC_VERSION) && (__ARMCC_VERSION >= 6010050))
+  #pragma clang diagnostic pop
+#elif defined (__GNUC__)
+  /* anonymous unions are enabled by default */
+#elif defined (__TMS470__)
+  /* anonymous unions are enabled by default */
+#elif defined (__TASKING__)
+  #pragma warning restore
+#elif defined (__CSMC__)
+  /* anonymous unions are enabled by default */
+#endif
+
+";
+
+#[test]
+fn test_revision() {
+    assert_eq!(revision("r0p1"), "0x0001U");
+    assert_eq!(revision("r2p10"), "0x020AU");
+    assert_eq!(revision("unknown"), "0x0000U");
+}
@@ -1,6 +1,6 @@
 use super::{new_node, Config, Element, Encode, EncodeError};
 
-use crate::{config::change_case, svd::Interrupt};
+use crate::{config::change_case_with_config, svd::Interrupt};
 
 impl Encode for Interrupt {
     type Error = EncodeError;
@@ -8,7 +8,7 @@ impl Encode for Interrupt {
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
         let mut children = vec![new_node(
             "name",
-            change_case(&self.name, config.interrupt_name),
+            change_case_with_config(&self.name, config.interrupt_name, config),
         )];
         if let Some(d) = self.description.clone() {
             children.push(new_node("description", d));
@@ -0,0 +1,549 @@
+//! Export of devices as IP-XACT (IEEE 1685-2014) components.
+//!
+//! Every peripheral becomes an `addressBlock` of a single `memoryMap`, clusters become
+//! `registerFile`s. Register properties are written explicitly on every register,
+//! and the reset value of a register is split between its fields.
+//! A register without fields gets a single field with the register name covering all its bits.
+//!
+//! Elements which IP-XACT can't express, like interrupts, are skipped and reported.
+//! Vendor, library and version are required by IP-XACT. When the device has no vendor,
+//! series or version, the element is written empty and reported.
+//! IP-XACT arrays are indexed from 0 and have no stride: registers follow each other
+//! and register files are placed at their `range`. Other arrays are written as separate elements.
+
+use xmltree::{Element, XMLNode};
+
+use super::{new_node, writer, Config, EncodeError, Unmapped};
+use crate::export::{
+    base_name, is_indexed_from_zero, register_name, resolve, value_mask, whole_register_field,
+    Report,
+};
+use crate::svd::{
+    self, AddressBlockUsage, Cluster, ClusterInfo, DeriveFrom, Device, DimElement, Field,
+    FieldInfo, Peripheral, PeripheralInfo, Register, RegisterCluster, RegisterInfo,
+    RegisterProperties, WriteConstraint,
+};
+
+/// Namespace of IP-XACT 1685-2014 documents
+pub const IPXACT_NAMESPACE: &str = "http://www.accellera.org/XMLSchema/IPXACT/1685-2014";
+
+pub(crate) fn encode(d: &Device, config: &Config) -> Result<(String, Vec<Unmapped>), EncodeError> {
+    let mut e = Export {
+        report: Report::new("IP-XACT"),
+        device: d,
+        address_unit_bits: d.address_unit_bits,
+    };
+    let root = e.component(d)?;
+    let mut writer = writer::XmlWriter::new(Vec::new(), writer::Format::new(config), true);
+    writer.start_document()?;
+    writer.element(&root)?;
+    let xml = super::into_string(writer.into_inner())?;
+    Ok((xml, e.report.unmapped))
+}
+
+struct Export<'a> {
+    report: Report,
+    device: &'a Device,
+    address_unit_bits: u32,
+}
+
+impl Export<'_> {
+    fn component(&mut self, d: &Device) -> Result<Element, EncodeError> {
+        let mut root = element("component");
+        root.attributes
+            .insert("xmlns:ipxact".into(), IPXACT_NAMESPACE.into());
+        root.attributes.insert(
+            "xmlns:xsi".into(),
+            "http://www.w3.org/2001/XMLSchema-instance".into(),
+        );
+        root.attributes.insert(
+            "xsi:schemaLocation".into(),
+            format!("{IPXACT_NAMESPACE} {IPXACT_NAMESPACE}/index.xsd"),
+        );
+        let children = &mut root.children;
+        children.push(node("vendor", d.vendor.as_deref().unwrap_or_default()));
+        children.push(node("library", d.series.as_deref().unwrap_or_default()));
+        children.push(node("name", &d.name));
+        children.push(node("version", &d.version));
+        self.report.unmapped_if(
+            &d.name,
+            [
+                ("vendor", d.vendor.is_none()),
+                ("series", d.series.is_none()),
+                ("version", d.version.is_empty()),
+            ],
+        );
+        if !d.description.is_empty() {
+            children.push(node("description", &d.description));
+        }
+        self.report.unmapped_if(
+            &d.name,
+            [
+                ("vendorID", d.vendor_id.is_some()),
+                ("licenseText", d.license_text.is_some()),
+                ("cpu", d.cpu.is_some()),
+                ("headerSystemFilename", d.header_system_filename.is_some()),
+                (
+                    "headerDefinitionsPrefix",
+                    d.header_definitions_prefix.is_some(),
+                ),
+                (
+                    "protection",
+                    d.default_register_properties.protection.is_some(),
+                ),
+            ],
+        );
+
+        let mut map = element("memoryMap");
+        map.children.push(node("name", &d.name));
+        for p in &d.peripherals {
+            let base = p
+                .derived_from
+                .as_ref()
+                .map(|base| {
+                    d.peripherals
+                        .iter()
+                        .find(|b| &b.name == base)
+                        .ok_or_else(|| {
+                            EncodeError::UnknownDerivedFrom(p.name.clone(), base.clone())
+                        })
+                })
+                .transpose()?;
+            if p.derived_from.is_some() {
+                self.report.unmapped(&p.name, "derivedFrom");
+            }
+            let derived;
+            let info: &PeripheralInfo = match base {
+                Some(base) => {
+                    derived = p.derive_from(base);
+                    &derived
+                }
+                None => p,
+            };
+            let props = info
+                .default_register_properties
+                .derive_from(&d.default_register_properties);
+            match p {
+                Peripheral::Single(_) => {
+                    let block = self.address_block(info, &props, d.width)?;
+                    map.children.push(XMLNode::Element(block));
+                }
+                Peripheral::Array(_, dim) => {
+                    self.report.unmapped(&p.name, "dim");
+                    for info in svd::peripheral::expand(info, dim) {
+                        let block = self.address_block(&info, &props, d.width)?;
+                        map.children.push(XMLNode::Element(block));
+                    }
+                }
+            }
+        }
+        map.children
+            .push(node("addressUnitBits", &d.address_unit_bits.to_string()));
+        let mut maps = element("memoryMaps");
+        maps.children.push(XMLNode::Element(map));
+        root.children.push(XMLNode::Element(maps));
+        Ok(root)
+    }
+
+    fn address_block(
+        &mut self,
+        p: &PeripheralInfo,
+        props: &RegisterProperties,
+        width: u32,
+    ) -> Result<Element, EncodeError> {
+        let path = &p.name;
+        self.report.unmapped_if(
+            path,
+            [
+                ("version", p.version.is_some()),
+                ("alternatePeripheral", p.alternate_peripheral.is_some()),
+                ("groupName", p.group_name.is_some()),
+                ("prependToName", p.prepend_to_name.is_some()),
+                ("appendToName", p.append_to_name.is_some()),
+                ("headerStructName", p.header_struct_name.is_some()),
+                ("interrupt", !p.interrupt.is_empty()),
+                ("protection", props.protection.is_some()),
+            ],
+        );
+        let mut block = element("addressBlock");
+        name_group(
+            &mut block,
+            &p.name,
+            p.display_name.as_deref(),
+            p.description.as_deref(),
+        );
+        block
+            .children
+            .push(node("baseAddress", &value(p.base_address)));
+        let registers = p.registers.as_deref().unwrap_or_default();
+        let (range, usage) = match p.address_block.as_deref() {
+            Some([ab]) if ab.offset == 0 => (u64::from(ab.size), ab.usage),
+            blocks => {
+                if blocks.is_some() {
+                    self.report.unmapped(path, "addressBlock");
+                }
+                (extent(registers, props), AddressBlockUsage::Registers)
+            }
+        };
+        block.children.push(node("range", &value(range)));
+        block.children.push(node("width", &width.to_string()));
+        let usage = match usage {
+            AddressBlockUsage::Registers => "register",
+            AddressBlockUsage::Buffer => "memory",
+            AddressBlockUsage::Reserved => "reserved",
+        };
+        block.children.push(node("usage", usage));
+        if let Some(access) = props.access {
+            block.children.push(node("access", access.as_str()));
+        }
+        block
+            .children
+            .extend(self.registers(registers, props, path)?);
+        Ok(block)
+    }
+
+    fn registers(
+        &mut self,
+        children: &[RegisterCluster],
+        inherited: &RegisterProperties,
+        scope: &str,
+    ) -> Result<Vec<XMLNode>, EncodeError> {
+        let mut nodes = Vec::new();
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    let path = format!("{scope}.{}", r.name);
+                    if r.derived_from.is_some() {
+                        self.report.unmapped(&path, "derivedFrom");
+                    }
+                    let r = resolve(r, children, Some(self.device), |rc| match rc {
+                        RegisterCluster::Register(r) => Some(r),
+                        _ => None,
+                    })?;
+                    let props = r.properties.derive_from(inherited);
+                    match &*r {
+                        Register::Single(info) => {
+                            nodes.push(self.register(info, None, &props, &path));
+                        }
+                        Register::Array(info, dim)
+                            if is_indexed_from_zero(&info.name, dim)
+                                && u64::from(dim.dim_increment)
+                                    * u64::from(self.address_unit_bits)
+                                    == u64::from(props.size.unwrap_or(32)) =>
+                        {
+                            nodes.push(self.register(info, Some(dim), &props, &path));
+                        }
+                        Register::Array(info, dim) => {
+                            self.report.unmapped(&path, "dim");
+                            for info in svd::register::expand(info, dim) {
+                                nodes.push(self.register(&info, None, &props, &path));
+                            }
+                        }
+                    }
+                }
+                RegisterCluster::Cluster(c) => {
+                    let path = format!("{scope}.{}", c.name);
+                    if c.derived_from.is_some() {
+                        self.report.unmapped(&path, "derivedFrom");
+                    }
+                    let c = resolve(c, children, Some(self.device), |rc| match rc {
+                        RegisterCluster::Cluster(c) => Some(c),
+                        _ => None,
+                    })?;
+                    let props = c.default_register_properties.derive_from(inherited);
+                    match &*c {
+                        Cluster::Single(info) => {
+                            nodes.push(self.register_file(info, None, &props, &path)?);
+                        }
+                        Cluster::Array(info, dim)
+                            if is_indexed_from_zero(&info.name, dim)
+                                && extent(&info.children, &props)
+                                    <= u64::from(dim.dim_increment) =>
+                        {
+                            nodes.push(self.register_file(info, Some(dim), &props, &path)?);
+                        }
+                        Cluster::Array(info, dim) => {
+                            self.report.unmapped(&path, "dim");
+                            for info in svd::cluster::expand(info, dim) {
+                                nodes.push(self.register_file(&info, None, &props, &path)?);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn register_file(
+        &mut self,
+        c: &ClusterInfo,
+        dim: Option<&DimElement>,
+        props: &RegisterProperties,
+        path: &str,
+    ) -> Result<XMLNode, EncodeError> {
+        self.report.unmapped_if(
+            path,
+            [
+                ("alternateCluster", c.alternate_cluster.is_some()),
+                ("headerStructName", c.header_struct_name.is_some()),
+                ("protection", props.protection.is_some()),
+            ],
+        );
+        if let Some(dim) = dim {
+            self.report.dim(dim, path);
+        }
+        let mut file = element("registerFile");
+        name_group(
+            &mut file,
+            &base_name(&c.name),
+            None,
+            c.description.as_deref(),
+        );
+        if let Some(dim) = dim {
+            file.children.push(node("dim", &dim.dim.to_string()));
+        }
+        file.children
+            .push(node("addressOffset", &value(c.address_offset.into())));
+        // Elements of array are placed at the range
+        let range = match dim {
+            Some(dim) => dim.dim_increment.into(),
+            None => extent(&c.children, props),
+        };
+        file.children.push(node("range", &value(range)));
+        file.children
+            .extend(self.registers(&c.children, props, path)?);
+        Ok(XMLNode::Element(file))
+    }
+
+    fn register(
+        &mut self,
+        r: &RegisterInfo,
+        dim: Option<&DimElement>,
+        props: &RegisterProperties,
+        path: &str,
+    ) -> XMLNode {
+        self.report.unmapped_if(
+            path,
+            [
+                ("alternateGroup", r.alternate_group.is_some()),
+                ("alternateRegister", r.alternate_register.is_some()),
+                ("dataType", r.datatype.is_some()),
+                ("protection", props.protection.is_some()),
+            ],
+        );
+        if let Some(dim) = dim {
+            self.report.dim(dim, path);
+        }
+        let name = register_name(r);
+        let size = props.size.unwrap_or(32);
+        let mut reg = element("register");
+        name_group(
+            &mut reg,
+            &name,
+            r.display_name.as_deref(),
+            r.description.as_deref(),
+        );
+        if let Some(dim) = dim {
+            reg.children.push(node("dim", &dim.dim.to_string()));
+        }
+        reg.children
+            .push(node("addressOffset", &value(r.address_offset.into())));
+        reg.children.push(node("size", &size.to_string()));
+        if let Some(access) = props.access {
+            reg.children.push(node("access", access.as_str()));
+        }
+
+        let mut fields = match r.fields.as_deref() {
+            Some(fields) if !fields.is_empty() => {
+                let mut infos = Vec::new();
+                for f in fields {
+                    match f {
+                        Field::Single(info) => infos.push(info.clone()),
+                        Field::Array(info, dim) => {
+                            self.report.unmapped(&format!("{path}.{}", f.name), "dim");
+                            infos.extend(svd::field::expand(info, dim));
+                        }
+                    }
+                }
+                if r.write_constraint.is_some() {
+                    self.report.unmapped(path, "writeConstraint");
+                }
+                infos
+            }
+            _ => {
+                let mut field = whole_register_field(r, size);
+                field.write_constraint = r.write_constraint;
+                vec![field]
+            }
+        };
+        // Field resets cover only bits of fields
+        let mut covered = 0;
+        for f in &mut fields {
+            f.modified_write_values = f.modified_write_values.or(r.modified_write_values);
+            f.read_action = f.read_action.or(r.read_action);
+            covered |= f.bitmask();
+        }
+        if props
+            .reset_value
+            .is_some_and(|v| v & !covered & value_mask(size) != 0)
+        {
+            self.report.unmapped(path, "resetValue");
+        }
+        for f in &fields {
+            let path = format!("{path}.{}", f.name);
+            let field = self.field(f, props, &path);
+            reg.children.push(field);
+        }
+        XMLNode::Element(reg)
+    }
+
+    fn field(&mut self, f: &FieldInfo, props: &RegisterProperties, path: &str) -> XMLNode {
+        if f.derived_from.is_some() {
+            self.report.unmapped(path, "derivedFrom");
+        }
+        let mut field = element("field");
+        name_group(&mut field, &f.name, None, f.description.as_deref());
+        field
+            .children
+            .push(node("bitOffset", &f.bit_range.offset.to_string()));
+        // Fields outside the reset value have no reset
+        let offset = f.bit_range.offset;
+        let field_mask = f.bitmask();
+        if let Some(reset) = props
+            .reset_value
+            .and_then(|v| (v & field_mask).checked_shr(offset))
+        {
+            let mut r = element("reset");
+            r.children.push(node("value", &value(reset)));
+            if let Some(m) = props.reset_mask.filter(|m| m & field_mask != field_mask) {
+                r.children
+                    .push(node("mask", &value((m & field_mask) >> offset)));
+            }
+            let mut resets = element("resets");
+            resets.children.push(XMLNode::Element(r));
+            field.children.push(XMLNode::Element(resets));
+        }
+        field
+            .children
+            .push(node("bitWidth", &f.bit_range.width.to_string()));
+        if let Some(access) = f.access {
+            field.children.push(node("access", access.as_str()));
+        }
+        if !f.enumerated_values.is_empty() {
+            let mut evs = element("enumeratedValues");
+            for set in &f.enumerated_values {
+                self.report.unmapped_if(
+                    path,
+                    [
+                        ("enumeratedValues/name", set.name.is_some()),
+                        ("enumeratedValues/derivedFrom", set.derived_from.is_some()),
+                    ],
+                );
+                for ev in &set.values {
+                    let Some(v) = ev.value else {
+                        self.report
+                            .unmapped(&format!("{path}.{}", ev.name), "isDefault");
+                        continue;
+                    };
+                    let mut e = element("enumeratedValue");
+                    if let Some(usage) = set.usage {
+                        e.attributes.insert("usage".into(), usage.as_str().into());
+                    }
+                    name_group(&mut e, &ev.name, None, ev.description.as_deref());
+                    e.children.push(node("value", &value(v)));
+                    evs.children.push(XMLNode::Element(e));
+                }
+            }
+            if !evs.children.is_empty() {
+                field.children.push(XMLNode::Element(evs));
+            }
+        }
+        if let Some(mwv) = f.modified_write_values {
+            field
+                .children
+                .push(node("modifiedWriteValue", mwv.as_str()));
+        }
+        if let Some(wc) = f.write_constraint {
+            let mut c = element("writeValueConstraint");
+            match wc {
+                WriteConstraint::WriteAsRead(b) => {
+                    c.children.push(node("writeAsRead", &b.to_string()));
+                }
+                WriteConstraint::UseEnumeratedValues(b) => {
+                    c.children.push(node("useEnumeratedValues", &b.to_string()));
+                }
+                WriteConstraint::Range(range) => {
+                    c.children.push(node("minimum", &value(range.min)));
+                    c.children.push(node("maximum", &value(range.max)));
+                }
+            }
+            field.children.push(XMLNode::Element(c));
+        }
+        if let Some(ra) = f.read_action {
+            let ra = match ra {
+                svd::ReadAction::ModifyExternal => {
+                    self.report.unmapped(path, "readAction");
+                    svd::ReadAction::Modify
+                }
+                ra => ra,
+            };
+            field.children.push(node("readAction", ra.as_str()));
+        }
+        XMLNode::Element(field)
+    }
+}
+
+/// Bytes occupied by registers and clusters
+fn extent(children: &[RegisterCluster], props: &RegisterProperties) -> u64 {
+    let mut end = 0;
+    for rc in children {
+        let (offset, size, dim) = match rc {
+            RegisterCluster::Register(r) => {
+                let props = r.properties.derive_from(props);
+                let size = u64::from(props.size.unwrap_or(32).div_ceil(8));
+                let dim = match r {
+                    Register::Single(_) => None,
+                    Register::Array(_, dim) => Some(dim),
+                };
+                (r.address_offset, size, dim)
+            }
+            RegisterCluster::Cluster(c) => {
+                let props = c.default_register_properties.derive_from(props);
+                let dim = match c {
+                    Cluster::Single(_) => None,
+                    Cluster::Array(_, dim) => Some(dim),
+                };
+                (c.address_offset, extent(&c.children, &props), dim)
+            }
+        };
+        let last = dim.map_or(0, |dim| {
+            u64::from(dim.dim_increment) * u64::from(dim.dim.saturating_sub(1))
+        });
+        end = end.max(u64::from(offset) + last + size);
+    }
+    end
+}
+
+fn name_group(e: &mut Element, name: &str, display_name: Option<&str>, description: Option<&str>) {
+    e.children.push(node("name", name));
+    if let Some(display_name) = display_name {
+        e.children.push(node("displayName", display_name));
+    }
+    if let Some(description) = description {
+        e.children.push(node("description", description));
+    }
+}
+
+fn element(name: &str) -> Element {
+    Element::new(&format!("ipxact:{name}"))
+}
+
+fn node(name: &str, text: &str) -> XMLNode {
+    new_node(&format!("ipxact:{name}"), text.into())
+}
+
+/// Number in SystemVerilog notation used by IP-XACT expressions
+fn value(v: u64) -> String {
+    format!("'h{v:X}")
+}
@@ -3,15 +3,71 @@
 
 use svd_rs as svd;
 
-use crate::svd::Device;
-use xmltree::{Element, EmitterConfig, XMLNode};
+use crate::svd::{Cluster, Device, Field, Peripheral, Register, SourceFormat};
+use std::io::Write;
+use xmltree::{Element, XMLNode};
 
 pub use crate::config::{
-    Config, DerivableSorting, IdentifierFormat, NumberFormat, RcSorting, Sorting,
+    change_case, change_case_with_config, Config, ConfigError, DerivableSorting, DescriptionFormat,
+    Encoding, FieldBitRangeFormat, IdentifierCallback, IdentifierFormat, Indent, LineEnding,
+    NumberFormat, PropertiesMode, RcSorting, SchemaVersion, SelfClosing, Sorting, Unsupported,
+    WordBoundary, CONFIG_KEYS,
 };
+pub use crate::docs::{DocsConfig, DocsFormat, Page};
+pub use crate::header::HeaderConfig;
+pub use crate::ipxact::IPXACT_NAMESPACE;
+pub use crate::overrides::Override;
+pub use crate::sorting::{natural_cmp, Comparator, Sortable};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
-pub enum EncodeError {}
+/// SVD encode Errors.
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("XML writer error: {0}")]
+    Xml(String),
+    #[error("Value of <{0}> contains character {1:?} which is not allowed in XML")]
+    InvalidCharacter(String, char),
+    #[error("Encoded output is not valid UTF-8")]
+    InvalidUtf8,
+    #[error("<{0}> in `{1}` is not supported by CMSIS-SVD {2}")]
+    Unsupported(String, String, SchemaVersion),
+    #[error("`{0}` is derived from unknown `{1}`")]
+    UnknownDerivedFrom(String, String),
+    #[error("Field `{0}` doesn't fit in 64 bits")]
+    FieldTooWide(String),
+}
+
+/// Element of the device which has no equivalent in the output format
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Unmapped {
+    /// Name of the output format
+    pub format: &'static str,
+    /// Names of the peripheral, clusters, register and field joined with `.`
+    pub path: String,
+    /// Tag name of the element in SVD
+    pub element: String,
+}
+
+impl std::fmt::Display for Unmapped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<{}> of `{}` has no {} equivalent",
+            self.element, self.path, self.format
+        )
+    }
+}
+
+impl From<xmltree::Error> for EncodeError {
+    fn from(e: xmltree::Error) -> Self {
+        match e {
+            xmltree::Error::Io(e) => Self::Io(e),
+            e => Self::Xml(e.to_string()),
+        }
+    }
+}
 
 /// Encode trait allows SVD objects to be encoded into XML elements.
 pub trait Encode {
@@ -51,13 +107,132 @@ pub fn encode(d: &Device) -> Result<String, EncodeError> {
 
 /// Encodes a device object to an SVD (XML) string
 pub fn encode_with_config(d: &Device, config: &Config) -> Result<String, EncodeError> {
-    let root = d.encode_with_config(config)?;
     let mut wr = Vec::new();
-    let mut cfg = EmitterConfig::new();
-    cfg.perform_indent = true;
-    cfg.pad_self_closing = false;
-    root.write_with_config(&mut wr, cfg).unwrap();
-    Ok(String::from_utf8(wr).unwrap())
+    encode_to_writer(d, config, &mut wr)?;
+    into_string(wr)
+}
+
+/// Encodes a device object as SVD (XML) into `w`.
+///
+/// Peripherals are encoded and written one at a time, so the XML tree of the whole device
+/// is never kept in memory. Wrap `w` in [`std::io::BufWriter`] if it is unbuffered
+pub fn encode_to_writer(d: &Device, config: &Config, w: impl Write) -> Result<(), EncodeError> {
+    let mut writer = writer::XmlWriter::new(w, writer::Format::new(config), true);
+    writer.start_document()?;
+    let defaults = properties::Defaults::new(d, config);
+    let mut header = device::encode_header(d, &defaults, config)?;
+    let children = std::mem::take(&mut header.children);
+    writer.start_element(&header)?;
+    for child in &children {
+        writer.node(&header.name, child)?;
+    }
+    writer.start_element(&Element::new("peripherals"))?;
+    for p in device::sorted_peripherals(d, config) {
+        writer.element(&device::encode_peripheral(p, &defaults, config)?)?;
+    }
+    writer.end_element()?;
+    writer.end_element()?;
+    writer.into_inner().flush()?;
+    Ok(())
+}
+
+/// Encodes a standalone element (peripheral, register, etc.) to an XML string
+/// without XML declaration
+pub fn encode_fragment<T>(item: &T, config: &Config) -> Result<String, EncodeError>
+where
+    T: Encode<Error = EncodeError>,
+{
+    let mut root = item.encode_with_config(config)?;
+    schemaversion::restrict(&mut root, config)?;
+    write_element(&root, writer::Format::new(config))
+}
+
+/// Encodes a peripheral object to an XML string
+pub fn encode_peripheral(p: &Peripheral) -> Result<String, EncodeError> {
+    encode_fragment(p, &Config::default())
+}
+
+/// Encodes a cluster object to an XML string
+pub fn encode_cluster(c: &Cluster) -> Result<String, EncodeError> {
+    encode_fragment(c, &Config::default())
+}
+
+/// Encodes a register object to an XML string
+pub fn encode_register(r: &Register) -> Result<String, EncodeError> {
+    encode_fragment(r, &Config::default())
+}
+
+/// Encodes a field object to an XML string
+pub fn encode_field(f: &Field) -> Result<String, EncodeError> {
+    encode_fragment(f, &Config::default())
+}
+
+/// Generates register documentation pages for a device
+pub fn encode_docs(d: &Device, config: &DocsConfig) -> Result<Vec<Page>, EncodeError> {
+    docs::generate(d, config)
+}
+
+/// Generates CMSIS-Core device header (`.h` file) for a device
+pub fn encode_c_header(d: &Device, config: &HeaderConfig) -> Result<String, EncodeError> {
+    header::generate(d, config)
+}
+
+/// Encodes a device object as IP-XACT (IEEE 1685-2014) component.
+///
+/// Also returns the list of elements which IP-XACT can't express and which were skipped
+pub fn encode_ipxact(d: &Device, config: &Config) -> Result<(String, Vec<Unmapped>), EncodeError> {
+    ipxact::encode(d, config)
+}
+
+/// Encodes a device object as SystemRDL `addrmap`.
+///
+/// Also returns the list of elements which SystemRDL can't express and which were skipped
+pub fn encode_systemrdl(
+    d: &Device,
+    config: &Config,
+) -> Result<(String, Vec<Unmapped>), EncodeError> {
+    systemrdl::encode_device(d, config)
+}
+
+/// Encodes a peripheral object as SystemRDL `addrmap` definition.
+///
+/// Also returns the list of elements which SystemRDL can't express and which were skipped
+pub fn encode_systemrdl_peripheral(
+    p: &Peripheral,
+    config: &Config,
+) -> Result<(String, Vec<Unmapped>), EncodeError> {
+    systemrdl::encode_peripheral(p, config)
+}
+
+/// Encodes a device object to an SVD (XML) string.
+///
+/// Elements which are equal to ones recorded in `format` are copied from the source file
+/// with their whitespace and comments. Changed values keep the style of the original ones,
+/// and the original order of elements and attributes is kept.
+/// So only changed elements differ from the source
+pub fn encode_with_source_format(
+    d: &Device,
+    config: &Config,
+    format: &SourceFormat,
+) -> Result<String, EncodeError> {
+    let mut root = d.encode_with_config(config)?;
+    let path = sourceformat::root_segment(&root);
+    sourceformat::restore(&mut root, &path, format);
+    let mut output = writer::Format::new(config);
+    if let Some(indent) = format.indent.as_ref() {
+        output.indent.clone_from(indent);
+    }
+    sourceformat::write(&root, &path, format, output)
+}
+
+fn write_element(root: &Element, format: writer::Format) -> Result<String, EncodeError> {
+    let mut writer = writer::XmlWriter::new(Vec::new(), format, false);
+    writer.element(root)?;
+    into_string(writer.into_inner())
+}
+
+fn into_string(wr: Vec<u8>) -> Result<String, EncodeError> {
+    String::from_utf8(wr).map_err(|_| EncodeError::InvalidUtf8)
 }
 
 /// Defines extensions for implementation over xmltree::Element
@@ -87,21 +262,34 @@ mod addressblock;
 mod bitrange;
 mod cluster;
 mod config;
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json"))]
+mod configfile;
 mod cpu;
 mod datatype;
 mod device;
 mod dimelement;
+mod docs;
 mod endian;
 mod enumeratedvalue;
 mod enumeratedvalues;
+mod export;
 mod field;
+mod header;
 mod interrupt;
+mod ipxact;
 mod modifiedwritevalues;
+mod overrides;
 mod peripheral;
+mod properties;
 mod protection;
 mod readaction;
 mod register;
 mod registercluster;
 mod registerproperties;
+mod schemaversion;
+mod sorting;
+mod sourceformat;
+mod systemrdl;
 mod usage;
 mod writeconstraint;
+mod writer;
@@ -0,0 +1,129 @@
+//! Options for peripherals, clusters, registers and fields selected by path.
+//!
+//! Path of element consists of names of the peripheral and its clusters, register and field
+//! as written in the device (`UART%s` for arrays) joined by `.`, like `TIM1.CCR1.CC1E`.
+//! Options of the override apply to the matched element and all its children.
+//! When several overrides match, they are applied in order.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ops::Deref;
+
+use super::{Config, ConfigError, CONFIG_KEYS};
+use crate::svd::glob;
+
+thread_local! {
+    /// Path of the element being encoded
+    static SCOPE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Options applied to elements with path matching pattern
+#[derive(Clone, Debug)]
+pub struct Override {
+    /// Pattern of element path. May contain `*` and `?` wildcards
+    pub path: String,
+    /// Names of options set by the override
+    keys: Vec<&'static str>,
+    /// Values of options in `keys`
+    values: Config,
+}
+
+impl Override {
+    /// Create override for elements with path matching `path`
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            keys: Vec::new(),
+            values: Config::default(),
+        }
+    }
+
+    /// Set option with given name and value as accepted by [`Config::try_update`]
+    pub fn set(mut self, name: &str, value: &str) -> Result<Self, ConfigError> {
+        self.values.try_update(name, value)?;
+        let key = CONFIG_KEYS
+            .iter()
+            .find(|k| **k == name)
+            .ok_or_else(|| ConfigError::UnknownKey(name.into()))?;
+        if !self.keys.contains(key) {
+            self.keys.push(key);
+        }
+        Ok(self)
+    }
+
+    /// Names and values of options set by the override
+    pub fn options(&self) -> impl Iterator<Item = (&'static str, String)> + '_ {
+        self.keys
+            .iter()
+            .map(|key| (*key, self.values.get(key).unwrap_or_default()))
+    }
+
+    /// Check if override applies to element with `path`
+    pub fn matches(&self, path: &str) -> bool {
+        glob(&self.path, path)
+    }
+
+    fn apply(&self, config: &mut Config) {
+        for key in &self.keys {
+            config.copy_option(key, &self.values);
+        }
+    }
+}
+
+impl PartialEq for Override {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.options().eq(other.options())
+    }
+}
+
+/// Config of element with matching overrides applied.
+/// Leaves the element's path when dropped
+pub(crate) struct Scoped<'a> {
+    config: Cow<'a, Config>,
+    /// Length of the parent path
+    parent: Option<usize>,
+}
+
+impl Deref for Scoped<'_> {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.config
+    }
+}
+
+impl Drop for Scoped<'_> {
+    fn drop(&mut self) {
+        if let Some(len) = self.parent {
+            SCOPE.with_borrow_mut(|scope| scope.truncate(len));
+        }
+    }
+}
+
+impl Config {
+    /// Config for child element with `name` with matching overrides applied
+    pub(crate) fn enter(&self, name: &str) -> Scoped<'_> {
+        if self.overrides.is_empty() {
+            return Scoped {
+                config: Cow::Borrowed(self),
+                parent: None,
+            };
+        }
+        let mut config = self.clone();
+        let parent = SCOPE.with_borrow_mut(|scope| {
+            let len = scope.len();
+            if len > 0 {
+                scope.push('.');
+            }
+            scope.push_str(name);
+            for o in self.overrides.iter().filter(|o| o.matches(scope)) {
+                o.apply(&mut config);
+            }
+            len
+        });
+        Scoped {
+            config: Cow::Owned(config),
+            parent: Some(parent),
+        }
+    }
+}
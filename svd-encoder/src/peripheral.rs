@@ -5,7 +5,8 @@ use super::{
 };
 
 use crate::{
-    config::{change_case, format_number, DerivableSorting, RcSorting, Sorting},
+    config::{change_case_with_config, format_number, DerivableSorting, RcSorting},
+    sorting::sort,
     svd::{Peripheral, PeripheralInfo},
 };
 
@@ -13,6 +14,7 @@ impl Encode for Peripheral {
     type Error = EncodeError;
 
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
+        let config = &*config.enter(&self.name);
         match self {
             Self::Single(info) => info.encode_with_config(config),
             Self::Array(info, array_info) => {
@@ -32,7 +34,7 @@ impl Encode for PeripheralInfo {
         let mut elem = Element::new("peripheral");
         elem.children.push(new_node(
             "name",
-            change_case(&self.name, config.peripheral_name),
+            change_case_with_config(&self.name, config.peripheral_name, config),
         ));
 
         if let Some(v) = &self.display_name {
@@ -50,7 +52,7 @@ impl Encode for PeripheralInfo {
         if let Some(v) = &self.alternate_peripheral {
             elem.children.push(new_node(
                 "alternatePeripheral",
-                change_case(v, config.peripheral_name),
+                change_case_with_config(v, config.peripheral_name, config),
             ));
         }
 
@@ -61,21 +63,21 @@ impl Encode for PeripheralInfo {
         if let Some(v) = &self.prepend_to_name {
             elem.children.push(new_node(
                 "prependToName",
-                change_case(v, config.peripheral_name),
+                change_case_with_config(v, config.peripheral_name, config),
             ));
         }
 
         if let Some(v) = &self.append_to_name {
             elem.children.push(new_node(
                 "appendToName",
-                change_case(v, config.peripheral_name),
+                change_case_with_config(v, config.peripheral_name, config),
             ));
         }
 
         if let Some(v) = &self.header_struct_name {
             elem.children.push(new_node(
                 "headerStructName",
-                change_case(v, config.peripheral_name),
+                change_case_with_config(v, config.peripheral_name, config),
             ));
         }
 
@@ -95,9 +97,10 @@ impl Encode for PeripheralInfo {
             }
         }
 
-        let interrupts: Result<Vec<_>, _> = self
-            .interrupt
-            .iter()
+        let mut interrupts = self.interrupt.iter().collect::<Vec<_>>();
+        sort(&mut interrupts, config.interrupt_sorting, config);
+        let interrupts: Result<Vec<_>, _> = interrupts
+            .into_iter()
             .map(|interrupt| interrupt.encode_node_with_config(config))
             .collect();
 
@@ -109,14 +112,14 @@ impl Encode for PeripheralInfo {
                     .iter()
                     .map(|e| e.encode_node_with_config(config))
                     .collect(),
-                RcSorting::Unchanged(sorting) => sort_derived_register_cluster(v, sorting)
+                RcSorting::Unchanged(sorting) => sort_derived_register_cluster(v, sorting, config)
                     .into_iter()
                     .map(|e| e.encode_node_with_config(config))
                     .collect(),
-                RcSorting::RegistersFirst(sorting) => rc_sort(v, sorting, true)
+                RcSorting::RegistersFirst(sorting) => rc_sort(v, sorting, true, config)
                     .map(|e| e.encode_node_with_config(config))
                     .collect(),
-                RcSorting::ClustersFirst(sorting) => rc_sort(v, sorting, false)
+                RcSorting::ClustersFirst(sorting) => rc_sort(v, sorting, false, config)
                     .map(|e| e.encode_node_with_config(config))
                     .collect(),
             };
@@ -131,7 +134,7 @@ impl Encode for PeripheralInfo {
         if let Some(v) = &self.derived_from {
             elem.attributes.insert(
                 String::from("derivedFrom"),
-                change_case(v, config.peripheral_name),
+                change_case_with_config(v, config.peripheral_name, config),
             );
         }
 
@@ -139,26 +142,15 @@ impl Encode for PeripheralInfo {
     }
 }
 
-fn sort_register_cluster(refs: &mut [&RegisterCluster], sorting: Option<Sorting>) {
-    if let Some(sorting) = sorting {
-        match sorting {
-            Sorting::Offset => refs.sort_by_key(|r| r.address_offset()),
-            Sorting::OffsetReversed => {
-                refs.sort_by_key(|r| -(r.address_offset() as i32));
-            }
-            Sorting::Name => refs.sort_by_key(|r| r.name()),
-        }
-    }
-}
-
 fn sort_derived_register_cluster<'a>(
     rcs: impl IntoIterator<Item = &'a RegisterCluster>,
     sorting: DerivableSorting,
+    config: &Config,
 ) -> Vec<&'a RegisterCluster> {
     match sorting {
         DerivableSorting::Unchanged(sorting) => {
             let mut refs = rcs.into_iter().collect::<Vec<_>>();
-            sort_register_cluster(&mut refs, sorting);
+            sort(&mut refs, sorting, config);
             refs
         }
         DerivableSorting::DeriveLast(sorting) => {
@@ -171,30 +163,31 @@ fn sort_derived_register_cluster<'a>(
                     common_refs.push(rc);
                 }
             }
-            sort_register_cluster(&mut common_refs, sorting);
-            sort_register_cluster(&mut derived_refs, sorting);
+            sort(&mut common_refs, sorting, config);
+            sort(&mut derived_refs, sorting, config);
             common_refs.extend(derived_refs);
             common_refs
         }
     }
 }
 
-fn rc_sort(
-    v: &[RegisterCluster],
+fn rc_sort<'a>(
+    v: &'a [RegisterCluster],
     sorting: DerivableSorting,
     register_first: bool,
-) -> impl Iterator<Item = &RegisterCluster> {
+    config: &Config,
+) -> impl Iterator<Item = &'a RegisterCluster> {
     let reg_refs = v
         .iter()
         .filter(|rc| matches!(rc, RegisterCluster::Register(_)))
         .collect::<Vec<_>>();
-    let reg_refs = sort_derived_register_cluster(reg_refs, sorting);
+    let reg_refs = sort_derived_register_cluster(reg_refs, sorting, config);
 
     let c_refs = v
         .iter()
         .filter(|rc| matches!(rc, RegisterCluster::Cluster(_)))
         .collect::<Vec<_>>();
-    let c_refs = sort_derived_register_cluster(c_refs, sorting);
+    let c_refs = sort_derived_register_cluster(c_refs, sorting, config);
     if register_first {
         reg_refs.into_iter().chain(c_refs)
     } else {
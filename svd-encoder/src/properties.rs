@@ -0,0 +1,187 @@
+//! Omitting and hoisting of register properties.
+//!
+//! Properties of registers, clusters and peripherals equal to the ones inherited
+//! from the parent element are redundant. Elements with `derivedFrom` inherit
+//! properties from the derivation source, so they are kept as is.
+
+use std::borrow::Cow;
+
+use super::{Config, PropertiesMode};
+use crate::svd::{DeriveFrom, Device, Peripheral, RegisterCluster, RegisterProperties};
+
+/// Register properties of the device
+pub(crate) struct Defaults {
+    /// Properties as written in the device
+    original: RegisterProperties,
+    /// Properties to encode
+    pub(crate) device: RegisterProperties,
+    mode: PropertiesMode,
+}
+
+impl Defaults {
+    pub(crate) fn new(device: &Device, config: &Config) -> Self {
+        let original = device.default_register_properties;
+        let hoisted = if config.register_properties == PropertiesMode::Hoist {
+            let peripherals = device
+                .peripherals
+                .iter()
+                .filter(|p| p.derived_from.is_none())
+                .map(|p| hoisted(p, &original))
+                .collect::<Vec<_>>();
+            common(&peripherals).derive_from(&original)
+        } else {
+            original
+        };
+        Self {
+            original,
+            device: hoisted,
+            mode: config.register_properties,
+        }
+    }
+
+    /// Peripheral with properties to encode
+    pub(crate) fn peripheral<'a>(&self, p: Cow<'a, Peripheral>) -> Cow<'a, Peripheral> {
+        if self.mode == PropertiesMode::Unchanged || p.derived_from.is_some() {
+            return p;
+        }
+        let mut p = p.into_owned();
+        if self.mode == PropertiesMode::Hoist {
+            let props = hoisted(&p, &self.original);
+            let effective = p.default_register_properties.derive_from(&self.original);
+            if let Some(regs) = p.registers.as_mut().filter(|regs| !has_derived(regs)) {
+                materialize(regs, &effective);
+            }
+            p.default_register_properties = props;
+        }
+        let effective = p.default_register_properties.derive_from(&self.device);
+        omit_equal(&mut p.default_register_properties, &self.device);
+        if let Some(regs) = p.registers.as_mut() {
+            omit(regs, &effective);
+        }
+        Cow::Owned(p)
+    }
+}
+
+/// Properties of the peripheral with the most common properties of its registers.
+/// Properties are hoisted only if all registers have them
+fn hoisted(p: &Peripheral, inherited: &RegisterProperties) -> RegisterProperties {
+    let effective = p.default_register_properties.derive_from(inherited);
+    match p.registers.as_deref() {
+        Some(regs) if !has_derived(regs) => {
+            let mut registers = Vec::new();
+            collect(regs, &effective, &mut registers);
+            common(&registers).derive_from(&effective)
+        }
+        _ => effective,
+    }
+}
+
+/// Effective properties of all registers
+fn collect(
+    regs: &[RegisterCluster],
+    inherited: &RegisterProperties,
+    out: &mut Vec<RegisterProperties>,
+) {
+    for rc in regs {
+        match rc {
+            RegisterCluster::Cluster(c) => {
+                let effective = c.default_register_properties.derive_from(inherited);
+                collect(&c.children, &effective, out);
+            }
+            RegisterCluster::Register(r) => out.push(r.properties.derive_from(inherited)),
+        }
+    }
+}
+
+fn has_derived(regs: &[RegisterCluster]) -> bool {
+    regs.iter().any(|rc| match rc {
+        RegisterCluster::Cluster(c) => c.derived_from.is_some() || has_derived(&c.children),
+        RegisterCluster::Register(r) => r.derived_from.is_some(),
+    })
+}
+
+/// Writes inherited properties explicitly on every cluster and register
+fn materialize(regs: &mut [RegisterCluster], inherited: &RegisterProperties) {
+    for rc in regs {
+        match rc {
+            RegisterCluster::Cluster(c) => {
+                let effective = c.default_register_properties.derive_from(inherited);
+                c.default_register_properties = effective;
+                materialize(&mut c.children, &effective);
+            }
+            RegisterCluster::Register(r) => r.properties = r.properties.derive_from(inherited),
+        }
+    }
+}
+
+fn omit(regs: &mut [RegisterCluster], inherited: &RegisterProperties) {
+    for rc in regs {
+        match rc {
+            RegisterCluster::Cluster(c) => {
+                if c.derived_from.is_some() {
+                    continue;
+                }
+                let effective = c.default_register_properties.derive_from(inherited);
+                omit_equal(&mut c.default_register_properties, inherited);
+                omit(&mut c.children, &effective);
+            }
+            RegisterCluster::Register(r) => {
+                if r.derived_from.is_none() {
+                    omit_equal(&mut r.properties, inherited);
+                }
+            }
+        }
+    }
+}
+
+fn omit_equal(props: &mut RegisterProperties, inherited: &RegisterProperties) {
+    if props.size == inherited.size {
+        props.size = None;
+    }
+    if props.access == inherited.access {
+        props.access = None;
+    }
+    if props.protection == inherited.protection {
+        props.protection = None;
+    }
+    if props.reset_value == inherited.reset_value {
+        props.reset_value = None;
+    }
+    if props.reset_mask == inherited.reset_mask {
+        props.reset_mask = None;
+    }
+}
+
+/// The most common value of each property if all elements have it
+fn common(props: &[RegisterProperties]) -> RegisterProperties {
+    RegisterProperties::new()
+        .size(most_common(props.iter().map(|p| p.size)))
+        .access(most_common(props.iter().map(|p| p.access)))
+        .protection(most_common(props.iter().map(|p| p.protection)))
+        .reset_value(most_common(props.iter().map(|p| p.reset_value)))
+        .reset_mask(most_common(props.iter().map(|p| p.reset_mask)))
+}
+
+/// Returns the first of the most common values or `None` if some value is absent
+fn most_common<T: Copy + PartialEq>(values: impl Iterator<Item = Option<T>>) -> Option<T> {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    for v in values {
+        let v = v?;
+        match counts.iter_mut().find(|(c, _)| *c == v) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((v, 1)),
+        }
+    }
+    counts.iter().rev().max_by_key(|(_, n)| *n).map(|(v, _)| *v)
+}
+
+#[test]
+fn test_most_common() {
+    assert_eq!(
+        most_common([Some(1), Some(2), Some(2)].into_iter()),
+        Some(2)
+    );
+    assert_eq!(most_common([Some(1), Some(2)].into_iter()), Some(1));
+    assert_eq!(most_common([Some(1), None].into_iter()), None);
+    assert_eq!(most_common(std::iter::empty::<Option<u32>>()), None);
+}
@@ -5,7 +5,8 @@ use super::{
 };
 
 use crate::{
-    config::{change_case, format_number, DerivableSorting, Sorting},
+    config::{change_case_with_config, format_number, DerivableSorting},
+    sorting::sort,
     svd::{Register, RegisterInfo},
 };
 
@@ -13,6 +14,7 @@ impl Encode for Register {
     type Error = EncodeError;
 
     fn encode_with_config(&self, config: &Config) -> Result<Element, EncodeError> {
+        let config = &*config.enter(&self.name);
         match self {
             Self::Single(info) => info.encode_with_config(config),
             Self::Array(info, array_info) => {
@@ -32,7 +34,7 @@ impl Encode for RegisterInfo {
         let mut elem = Element::new("register");
         elem.children.push(new_node(
             "name",
-            change_case(&self.name, config.register_name),
+            change_case_with_config(&self.name, config.register_name, config),
         ));
 
         if let Some(v) = &self.display_name {
@@ -51,7 +53,7 @@ impl Encode for RegisterInfo {
         if let Some(v) = &self.alternate_register {
             elem.children.push(new_node(
                 "alternateRegister",
-                change_case(v, config.register_name),
+                change_case_with_config(v, config.register_name, config),
             ));
         }
 
@@ -86,7 +88,7 @@ impl Encode for RegisterInfo {
                         .map(|field| field.encode_node_with_config(config))
                         .collect()
                 } else {
-                    sort_derived_fields(v, config.field_sorting)
+                    sort_derived_fields(v, config.field_sorting, config)
                         .into_iter()
                         .map(|field| field.encode_node_with_config(config))
                         .collect()
@@ -103,7 +105,7 @@ impl Encode for RegisterInfo {
         if let Some(v) = &self.derived_from {
             elem.attributes.insert(
                 String::from("derivedFrom"),
-                change_case(v, config.register_name),
+                change_case_with_config(v, config.register_name, config),
             );
         }
 
@@ -111,23 +113,15 @@ impl Encode for RegisterInfo {
     }
 }
 
-fn sort_fields(refs: &mut [&Field], sorting: Option<Sorting>) {
-    if let Some(sorting) = sorting {
-        match sorting {
-            Sorting::Offset => refs.sort_by_key(|f| f.bit_offset()),
-            Sorting::OffsetReversed => {
-                refs.sort_by_key(|f| -(f.bit_offset() as i32));
-            }
-            Sorting::Name => refs.sort_by_key(|f| &f.name),
-        }
-    }
-}
-
-fn sort_derived_fields(v: &[Field], sorting: DerivableSorting) -> Vec<&Field> {
+fn sort_derived_fields<'a>(
+    v: &'a [Field],
+    sorting: DerivableSorting,
+    config: &Config,
+) -> Vec<&'a Field> {
     match sorting {
         DerivableSorting::Unchanged(sorting) => {
             let mut refs = v.iter().collect::<Vec<_>>();
-            sort_fields(&mut refs, sorting);
+            sort(&mut refs, sorting, config);
             refs
         }
         DerivableSorting::DeriveLast(sorting) => {
@@ -150,8 +144,8 @@ fn sort_derived_fields(v: &[Field], sorting: DerivableSorting) -> Vec<&Field> {
                     }
                 }
             }
-            sort_fields(&mut common_refs, sorting);
-            sort_fields(&mut derived_refs, sorting);
+            sort(&mut common_refs, sorting, config);
+            sort(&mut derived_refs, sorting, config);
             common_refs.extend(derived_refs);
 
             common_refs
@@ -0,0 +1,117 @@
+//! Restriction of encoded elements to the target schema version.
+
+use std::borrow::Cow;
+use xmltree::{Element, XMLNode};
+
+use super::{Config, EncodeError, SchemaVersion, Unsupported};
+use crate::svd::{peripheral, Peripheral};
+
+/// Elements added in CMSIS-SVD 1.3 which may appear in any element
+const ANY_1_3: &[&str] = &["dimName", "dimArrayIndex", "protection"];
+/// `cpu` elements added in CMSIS-SVD 1.3
+const CPU_1_3: &[&str] = &["dspPresent", "sauNumRegions", "sauRegionsConfig"];
+/// Elements of peripheral arrays
+const PERIPHERAL_DIM: &[&str] = &["dim", "dimIncrement", "dimIndex"];
+
+/// Rewrites `schemaVersion` and `xs:noNamespaceSchemaLocation` of the `device` element
+pub(crate) fn device_attributes(elem: &mut Element, config: &Config) {
+    if let Some(version) = config.schema_version {
+        elem.attributes
+            .insert("schemaVersion".into(), version.as_str().into());
+        elem.attributes.insert(
+            "xs:noNamespaceSchemaLocation".into(),
+            version.schema_location().into(),
+        );
+    }
+}
+
+/// Replaces peripheral arrays with separate peripherals if they are dropped
+pub(crate) fn peripherals<'a>(
+    peripherals: Vec<&'a Peripheral>,
+    config: &Config,
+) -> Vec<Cow<'a, Peripheral>> {
+    let expand = matches!(config.schema_version, Some(v) if v < SchemaVersion::V1_3)
+        && config.unsupported == Unsupported::Drop;
+    let mut list = Vec::with_capacity(peripherals.len());
+    for p in peripherals {
+        match p {
+            Peripheral::Array(info, dim) if expand => list.extend(
+                peripheral::expand(info, dim).map(|info| Cow::Owned(Peripheral::Single(info))),
+            ),
+            p => list.push(Cow::Borrowed(p)),
+        }
+    }
+    list
+}
+
+/// Removes or reports children of `elem` which the target schema can't express
+pub(crate) fn restrict(elem: &mut Element, config: &Config) -> Result<(), EncodeError> {
+    let Some(version) = config.schema_version else {
+        return Ok(());
+    };
+    if version >= SchemaVersion::V1_3 {
+        return Ok(());
+    }
+    restrict_element(elem, version, config.unsupported)
+}
+
+fn restrict_element(
+    elem: &mut Element,
+    version: SchemaVersion,
+    unsupported: Unsupported,
+) -> Result<(), EncodeError> {
+    let parent = elem.name.clone();
+    let is_supported = |child: &Element| {
+        let tag = child.name.as_str();
+        !(ANY_1_3.contains(&tag)
+            || (parent == "cpu" && CPU_1_3.contains(&tag))
+            || (parent == "peripheral" && PERIPHERAL_DIM.contains(&tag))
+            || (version < SchemaVersion::V1_1 && (tag == "cpu" || tag == "cluster")))
+    };
+    if let Some(child) = elem
+        .children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .find(|child| !is_supported(child))
+    {
+        if unsupported == Unsupported::Error {
+            return Err(EncodeError::Unsupported(
+                child.name.clone(),
+                owner(elem),
+                version,
+            ));
+        }
+    }
+    elem.children
+        .retain(|node| node.as_element().is_none_or(is_supported));
+
+    if parent == "cluster" {
+        // Registers can't follow nested clusters before 1.3
+        let is_cluster = |node: &XMLNode| node.as_element().is_some_and(|e| e.name == "cluster");
+        let (clusters, mut children): (Vec<_>, Vec<_>) = std::mem::take(&mut elem.children)
+            .into_iter()
+            .partition(is_cluster);
+        children.extend(clusters);
+        elem.children = children;
+    }
+
+    for child in elem.children.iter_mut() {
+        if let XMLNode::Element(child) = child {
+            restrict_element(child, version, unsupported)?;
+        }
+    }
+    // `registers` can't be empty
+    elem.children.retain(|node| {
+        node.as_element().is_none_or(|e| {
+            e.name != "registers" || e.children.iter().any(|c| c.as_element().is_some())
+        })
+    });
+    Ok(())
+}
+
+/// Name of the element for error messages
+fn owner(elem: &Element) -> String {
+    elem.get_child("name")
+        .and_then(|n| n.get_text())
+        .map_or_else(|| elem.name.clone(), |n| n.into_owned())
+}
@@ -0,0 +1,176 @@
+//! Sorting of peripherals, registers, clusters, fields, enumerated values and interrupts.
+
+use std::cmp::{Ordering, Reverse};
+
+use super::{Config, Sorting};
+use crate::svd::{EnumeratedValue, Field, Interrupt, Peripheral, RegisterCluster};
+
+/// Element passed to [`Comparator`]
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Sortable<'a> {
+    Peripheral(&'a Peripheral),
+    RegisterCluster(&'a RegisterCluster),
+    Field(&'a Field),
+    EnumeratedValue(&'a EnumeratedValue),
+    Interrupt(&'a Interrupt),
+}
+
+impl Sortable<'_> {
+    /// Name of the element. Empty for enumerated values without name
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Peripheral(p) => &p.name,
+            Self::RegisterCluster(rc) => rc.name(),
+            Self::Field(f) => &f.name,
+            Self::EnumeratedValue(ev) => &ev.name,
+            Self::Interrupt(i) => &i.name,
+        }
+    }
+}
+
+/// User-supplied comparison used by [`Sorting::Custom`].
+///
+/// Sorting is stable, so elements which compare equal keep their order
+pub type Comparator = fn(Sortable<'_>, Sortable<'_>) -> Ordering;
+
+/// Compares strings with runs of digits compared by their numeric values,
+/// so `USART2` goes before `USART10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut x, mut y) = (a, b);
+    loop {
+        let ord = match (chunk(x), chunk(y)) {
+            // Tie-break for equal numbers written differently, like `01` and `1`
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some((cx, rx)), Some((cy, ry))) => {
+                x = rx;
+                y = ry;
+                if is_number(cx) && is_number(cy) {
+                    let cx = cx.trim_start_matches('0');
+                    let cy = cy.trim_start_matches('0');
+                    cx.len().cmp(&cy.len()).then_with(|| cx.cmp(cy))
+                } else {
+                    cx.cmp(cy)
+                }
+            }
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+/// Splits off the leading run of digits or non-digits
+fn chunk(s: &str) -> Option<(&str, &str)> {
+    let digit = s.chars().next()?.is_ascii_digit();
+    let end = s
+        .find(|c: char| c.is_ascii_digit() != digit)
+        .unwrap_or(s.len());
+    Some(s.split_at(end))
+}
+
+fn is_number(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Elements which can be sorted
+pub(crate) trait Sort {
+    /// Address, offset or value. `None` goes last
+    fn offset(&self) -> Option<u64>;
+    fn name(&self) -> &str;
+    fn sortable(&self) -> Sortable<'_>;
+}
+
+impl Sort for Peripheral {
+    fn offset(&self) -> Option<u64> {
+        Some(self.base_address)
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sortable(&self) -> Sortable<'_> {
+        Sortable::Peripheral(self)
+    }
+}
+
+impl Sort for RegisterCluster {
+    fn offset(&self) -> Option<u64> {
+        Some(self.address_offset().into())
+    }
+    fn name(&self) -> &str {
+        RegisterCluster::name(self)
+    }
+    fn sortable(&self) -> Sortable<'_> {
+        Sortable::RegisterCluster(self)
+    }
+}
+
+impl Sort for Field {
+    fn offset(&self) -> Option<u64> {
+        Some(self.bit_offset().into())
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sortable(&self) -> Sortable<'_> {
+        Sortable::Field(self)
+    }
+}
+
+impl Sort for EnumeratedValue {
+    fn offset(&self) -> Option<u64> {
+        self.value
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sortable(&self) -> Sortable<'_> {
+        Sortable::EnumeratedValue(self)
+    }
+}
+
+impl Sort for Interrupt {
+    fn offset(&self) -> Option<u64> {
+        Some(self.value.into())
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn sortable(&self) -> Sortable<'_> {
+        Sortable::Interrupt(self)
+    }
+}
+
+/// Sorts elements. [`Sorting::Custom`] without [`Config::comparator`] keeps the order
+pub(crate) fn sort<T: Sort>(refs: &mut [&T], sorting: Option<Sorting>, config: &Config) {
+    let Some(sorting) = sorting else {
+        return;
+    };
+    match sorting {
+        Sorting::Offset => refs.sort_by_key(|e| (e.offset().is_none(), e.offset())),
+        Sorting::OffsetReversed => {
+            refs.sort_by_key(|e| (e.offset().is_none(), Reverse(e.offset())))
+        }
+        Sorting::Name => refs.sort_by(|a, b| a.name().cmp(b.name())),
+        Sorting::NaturalName => refs.sort_by(|a, b| natural_cmp(a.name(), b.name())),
+        Sorting::OffsetName => refs.sort_by(|a, b| {
+            (a.offset().is_none(), a.offset())
+                .cmp(&(b.offset().is_none(), b.offset()))
+                .then_with(|| natural_cmp(a.name(), b.name()))
+        }),
+        Sorting::Custom => {
+            if let Some(c) = config.comparator {
+                refs.sort_by(|a, b| c(a.sortable(), b.sortable()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_natural_cmp() {
+    let mut names = ["USART10", "USART2", "CH10", "CH2", "CH02", "CH"];
+    names.sort_by(|a, b| natural_cmp(a, b));
+    assert_eq!(names, ["CH", "CH02", "CH2", "CH10", "USART2", "USART10"]);
+}
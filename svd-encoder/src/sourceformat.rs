@@ -0,0 +1,420 @@
+//! Restoring the original form of elements recorded by the parser.
+//!
+//! Values, attributes and order of children of the encoded tree are restored first.
+//! Then elements equal to the original ones are copied from the source text,
+//! and only changed and new elements are written.
+
+use xmltree::{Element, XMLNode};
+
+use super::{into_string, writer, EncodeError};
+use crate::svd::sourceformat::{child_path, segments, ElementFormat, SourceFormat};
+use crate::svd::DimElement;
+
+const BIT_RANGE_TAGS: [&str; 5] = ["bitRange", "lsb", "msb", "bitOffset", "bitWidth"];
+
+/// Replaces values of `elem` and its children with the original text if they are not changed,
+/// restores the original order of children and attributes
+pub(crate) fn restore(elem: &mut Element, path: &str, format: &SourceFormat) {
+    let Some(orig) = format.get(path) else {
+        return;
+    };
+    restore_bit_range(elem, orig);
+    let segs = child_segments(elem);
+
+    // Values
+    for (child, seg) in child_elements_mut(elem).zip(&segs) {
+        if child
+            .children
+            .iter()
+            .any(|c| matches!(c, XMLNode::Element(_)))
+        {
+            continue;
+        }
+        if let Some(text) = orig.values.get(seg) {
+            let new = element_text(child);
+            if same_value(text, &new) || (seg == "dimIndex" && same_indexes(text, &new)) {
+                child.children = vec![XMLNode::Text(text.clone())];
+            } else if let Some(new) = restyle_number(text, &new) {
+                child.children = vec![XMLNode::Text(new)];
+            }
+        }
+    }
+
+    // Attributes
+    if !orig.attributes.is_empty() {
+        let mut attributes = std::mem::take(&mut elem.attributes);
+        for (key, value) in &orig.attributes {
+            if let Some(new) = attributes.shift_remove(key) {
+                let value = if *value == new { value.clone() } else { new };
+                elem.attributes.insert(key.clone(), value);
+            }
+        }
+        elem.attributes.extend(attributes);
+    }
+
+    // Order of children. New children stay after their encoded predecessor
+    let mut key = 0;
+    let keys = segs
+        .iter()
+        .map(|seg| {
+            if let Some(i) = orig.children.iter().position(|s| s == seg) {
+                key = i;
+            }
+            key
+        })
+        .collect::<Vec<_>>();
+    let mut children = take_elements(elem)
+        .into_iter()
+        .zip(segs)
+        .zip(keys)
+        .collect::<Vec<_>>();
+    children.sort_by_key(|(_, key)| *key);
+
+    for ((mut child, seg), _) in children {
+        restore(&mut child, &child_path(path, &seg), format);
+        elem.children.push(XMLNode::Element(child));
+    }
+}
+
+/// Writes restored `root` element copying unchanged elements and the text between them
+/// from the source
+pub(crate) fn write(
+    root: &Element,
+    path: &str,
+    format: &SourceFormat,
+    output: writer::Format,
+) -> Result<String, EncodeError> {
+    let mut out = Splice {
+        out: String::new(),
+        format,
+        output,
+    };
+    match format.get(path) {
+        Some(orig) => {
+            out.out.push_str(&format.text[..orig.range.start]);
+            out.element(root, path, 0)?;
+            out.out.push_str(&format.text[orig.range.end..]);
+        }
+        None => {
+            let mut writer = writer::XmlWriter::new(Vec::new(), out.output.clone(), true);
+            writer.start_document()?;
+            writer.element(root)?;
+            out.out.push_str(&into_string(writer.into_inner())?);
+            out.out.push_str(out.output.line_ending());
+        }
+    }
+    Ok(out.out)
+}
+
+struct Splice<'a> {
+    out: String,
+    format: &'a SourceFormat,
+    output: writer::Format,
+}
+
+impl Splice<'_> {
+    /// Writes element recorded with `path` at nesting `depth`
+    fn element(&mut self, elem: &Element, path: &str, depth: usize) -> Result<(), EncodeError> {
+        let Some(orig) = self.format.get(path) else {
+            return self.new_element(elem, depth);
+        };
+        let text = &self.format.text;
+        if unchanged(elem, orig, path, self.format) {
+            self.out.push_str(&text[orig.range.clone()]);
+            return Ok(());
+        }
+        let segs = child_segments(elem);
+        if segs.is_empty() || orig.children.is_empty() || orig.gaps.is_empty() {
+            return self.new_element(elem, depth);
+        }
+        if same_attributes(elem, orig) {
+            self.out
+                .push_str(&text[orig.range.start..orig.start_tag_end]);
+        } else {
+            let mut start = elem.clone();
+            start.children.clear();
+            self.out.push_str(&self.fragment(&start, depth, true)?);
+        }
+        let children = elem.children.iter().filter_map(|c| match c {
+            XMLNode::Element(e) => Some(e),
+            _ => None,
+        });
+        for (child, seg) in children.zip(&segs) {
+            match orig.children.iter().position(|s| s == seg) {
+                Some(i) => {
+                    self.out.push_str(&text[orig.gaps[i].clone()]);
+                    let child_path = child_path(path, seg);
+                    if same_text(child, orig, seg, self.format.get(&child_path)) {
+                        self.element(child, &child_path, depth + 1)?;
+                    } else {
+                        self.new_element(child, depth + 1)?;
+                    }
+                }
+                None => {
+                    self.out.push_str(self.output.line_ending());
+                    self.out.push_str(&self.output.indent.repeat(depth + 1));
+                    self.new_element(child, depth + 1)?;
+                }
+            }
+        }
+        let last = orig.gaps[orig.gaps.len() - 1].clone();
+        self.out.push_str(&text[last.start..orig.range.end]);
+        Ok(())
+    }
+
+    /// Writes element which is not in the source, without indentation of the first line
+    fn new_element(&mut self, elem: &Element, depth: usize) -> Result<(), EncodeError> {
+        let text = self.fragment(elem, depth, false)?;
+        self.out.push_str(&text);
+        Ok(())
+    }
+
+    /// Text of `elem` written at nesting `depth`, or only its start tag
+    fn fragment(
+        &self,
+        elem: &Element,
+        depth: usize,
+        start_tag: bool,
+    ) -> Result<String, EncodeError> {
+        let mut writer = writer::XmlWriter::new(Vec::new(), self.output.clone(), false);
+        // Parents are written to indent the element and wrap its description as in the tree
+        let parent = Element::new("_");
+        for _ in 0..depth {
+            writer.start_element(&parent)?;
+        }
+        if start_tag {
+            writer.start_element(elem)?;
+            // Ends the start tag
+            writer.node(&elem.name, &XMLNode::Text(String::new()))?;
+        } else {
+            writer.element(elem)?;
+        }
+        let text = into_string(writer.into_inner())?;
+        let newline = self.output.line_ending();
+        let lines = text.split(newline).skip(depth).collect::<Vec<_>>();
+        Ok(lines.join(newline).trim_start().to_string())
+    }
+}
+
+/// Whether `elem` and its children are the same as recorded with `path`
+fn unchanged(elem: &Element, orig: &ElementFormat, path: &str, format: &SourceFormat) -> bool {
+    if !same_attributes(elem, orig) {
+        return false;
+    }
+    let segs = child_segments(elem);
+    if segs != orig.children {
+        return false;
+    }
+    if segs.is_empty() {
+        // Text of element is compared by its parent
+        return true;
+    }
+    let children = elem.children.iter().filter_map(|c| match c {
+        XMLNode::Element(e) => Some(e),
+        _ => None,
+    });
+    children.zip(&segs).all(|(child, seg)| {
+        let path = child_path(path, seg);
+        let Some(child_orig) = format.get(&path) else {
+            return false;
+        };
+        same_text(child, orig, seg, Some(child_orig)) && unchanged(child, child_orig, &path, format)
+    })
+}
+
+/// Whether text of `child` with `seg` is the same as the original one recorded in its parent
+fn same_text(
+    child: &Element,
+    parent: &ElementFormat,
+    seg: &str,
+    orig: Option<&ElementFormat>,
+) -> bool {
+    match parent.values.get(seg) {
+        Some(value) => orig.is_some_and(|o| o.children.is_empty()) && element_text(child) == *value,
+        None => true,
+    }
+}
+
+fn same_attributes(elem: &Element, orig: &ElementFormat) -> bool {
+    elem.attributes.len() == orig.attributes.len()
+        && orig
+            .attributes
+            .iter()
+            .all(|(k, v)| elem.attributes.get(k) == Some(v))
+}
+
+/// Writes bit range in the original style if it is not changed
+fn restore_bit_range(elem: &mut Element, orig: &ElementFormat) {
+    let orig_tags = BIT_RANGE_TAGS
+        .into_iter()
+        .filter(|t| orig.values.contains_key(*t))
+        .collect::<Vec<_>>();
+    let new_tags = BIT_RANGE_TAGS
+        .into_iter()
+        .filter(|t| elem.get_child(*t).is_some())
+        .collect::<Vec<_>>();
+    if orig_tags.is_empty() || new_tags.is_empty() || orig_tags == new_tags {
+        return;
+    }
+    let orig_range = bit_range(|tag| orig.values.get(tag).cloned());
+    let new_range = bit_range(|tag| elem.get_child(tag).map(element_text));
+    if orig_range.is_none() || orig_range != new_range {
+        return;
+    }
+    let Some(pos) = elem
+        .children
+        .iter()
+        .position(|c| matches!(c, XMLNode::Element(e) if new_tags.contains(&e.name.as_str())))
+    else {
+        return;
+    };
+    elem.children
+        .retain(|c| !matches!(c, XMLNode::Element(e) if new_tags.contains(&e.name.as_str())));
+    let nodes = orig_tags.iter().map(|tag| {
+        let mut e = Element::new(tag);
+        e.children.push(XMLNode::Text(orig.values[*tag].clone()));
+        XMLNode::Element(e)
+    });
+    elem.children.splice(pos..pos, nodes);
+}
+
+/// Offset and width of bit range given by any style
+fn bit_range(get: impl Fn(&str) -> Option<String>) -> Option<(u64, u64)> {
+    let num = |tag| get(tag).and_then(|t| parse_number(&t));
+    if let Some(range) = get("bitRange") {
+        let (msb, lsb) = range
+            .trim()
+            .strip_prefix('[')?
+            .strip_suffix(']')?
+            .split_once(':')?;
+        let (msb, lsb) = (
+            msb.trim().parse::<u64>().ok()?,
+            lsb.trim().parse::<u64>().ok()?,
+        );
+        Some((lsb, (msb + 1).checked_sub(lsb)?))
+    } else if let (Some(lsb), Some(msb)) = (num("lsb"), num("msb")) {
+        Some((lsb, (msb + 1).checked_sub(lsb)?))
+    } else {
+        Some((num("bitOffset")?, num("bitWidth")?))
+    }
+}
+
+fn child_elements_mut(elem: &mut Element) -> impl Iterator<Item = &mut Element> {
+    elem.children.iter_mut().filter_map(|c| match c {
+        XMLNode::Element(e) => Some(e),
+        _ => None,
+    })
+}
+
+fn take_elements(elem: &mut Element) -> Vec<Element> {
+    let (elements, other): (Vec<_>, Vec<_>) = std::mem::take(&mut elem.children)
+        .into_iter()
+        .partition(|c| matches!(c, XMLNode::Element(_)));
+    elem.children = other;
+    elements
+        .into_iter()
+        .filter_map(|c| match c {
+            XMLNode::Element(e) => Some(e),
+            _ => None,
+        })
+        .collect()
+}
+
+fn child_segments(elem: &Element) -> Vec<String> {
+    let children = elem
+        .children
+        .iter()
+        .filter_map(|c| match c {
+            XMLNode::Element(e) => Some(e),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let names = children
+        .iter()
+        .map(|e| e.get_child("name").map(element_text))
+        .collect::<Vec<_>>();
+    segments(
+        children
+            .iter()
+            .zip(&names)
+            .map(|(e, name)| (e.name.as_str(), name.as_deref().map(str::trim))),
+    )
+}
+
+/// Segment of the root element
+pub(crate) fn root_segment(elem: &Element) -> String {
+    let name = elem.get_child("name").map(element_text);
+    segments([(elem.name.as_str(), name.as_deref().map(str::trim))]).remove(0)
+}
+
+fn element_text(elem: &Element) -> String {
+    elem.children
+        .iter()
+        .filter_map(|c| match c {
+            XMLNode::Text(t) | XMLNode::CData(t) => Some(t.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compares the original and the new text of simple element by value
+fn same_value(orig: &str, new: &str) -> bool {
+    let (orig, new) = (orig.trim(), new.trim());
+    if orig == new {
+        return true;
+    }
+    match (parse_number(orig), parse_number(new)) {
+        (Some(a), Some(b)) => return a == b,
+        (None, None) => {}
+        _ => return false,
+    }
+    matches!((parse_bool(orig), parse_bool(new)), (Some(a), Some(b)) if a == b)
+}
+
+/// Compares the original and the new dim indexes, which can be written as a list or a range
+fn same_indexes(orig: &str, new: &str) -> bool {
+    let orig = DimElement::parse_indexes(orig.trim());
+    orig.is_some() && orig == DimElement::parse_indexes(new.trim())
+}
+
+/// Writes changed number `new` with the radix, letter case and width of `orig`
+fn restyle_number(orig: &str, new: &str) -> Option<String> {
+    let value = parse_number(new)?;
+    let orig = orig.trim();
+    parse_number(orig)?;
+    let (prefix, digits) = ["0x", "0X", "#", "0b"]
+        .into_iter()
+        .find_map(|p| orig.strip_prefix(p).map(|d| (p, d)))
+        .unwrap_or(("", orig));
+    let width = if digits.len() > 1 && digits.starts_with('0') {
+        digits.len()
+    } else {
+        0
+    };
+    let upper = !digits.chars().any(|c| c.is_ascii_lowercase());
+    Some(match prefix {
+        "0x" | "0X" if upper => format!("{prefix}{value:0width$X}"),
+        "0x" | "0X" => format!("{prefix}{value:0width$x}"),
+        "#" | "0b" => format!("{prefix}{value:0width$b}"),
+        _ => format!("{value:0width$}"),
+    })
+}
+
+fn parse_number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = text.strip_prefix('#').or_else(|| text.strip_prefix("0b")) {
+        u64::from_str_radix(bin, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,609 @@
+//! Export of devices and peripherals as SystemRDL 2.0.
+//!
+//! The device is written as an `addrmap` with an `addrmap` instance for every peripheral,
+//! clusters become `regfile`s. Components are defined anonymously where they are instantiated.
+//! A register without fields gets a single field with the register name covering all its bits.
+//! Alternate registers are written as `alias`es of their primary register when both have
+//! the same width, other alternate registers are skipped and reported.
+//!
+//! Enumerated values are written as `enum` definitions used by `encode`.
+//! SystemRDL field has only one encoding, so only the first set of values is written.
+//!
+//! Elements which SystemRDL can't express, like interrupts, are skipped and reported.
+
+use std::fmt::Write as _;
+
+use super::{Config, EncodeError, Unmapped};
+use crate::export::{
+    base_name, is_indexed_from_zero, register_name, resolve, whole_register_field, Report,
+};
+use crate::svd::{
+    self, Access, Cluster, ClusterInfo, DeriveFrom, Device, DimElement, Field, ModifiedWriteValues,
+    Peripheral, PeripheralInfo, ReadAction, Register, RegisterCluster, RegisterInfo,
+    RegisterProperties,
+};
+
+pub(crate) fn encode_device(
+    d: &Device,
+    config: &Config,
+) -> Result<(String, Vec<Unmapped>), EncodeError> {
+    let mut w = Writer::new(config, Some(d));
+    w.report.unmapped_if(
+        &d.name,
+        [
+            ("vendor", d.vendor.is_some()),
+            ("vendorID", d.vendor_id.is_some()),
+            ("series", d.series.is_some()),
+            ("version", !d.version.is_empty()),
+            ("licenseText", d.license_text.is_some()),
+            ("cpu", d.cpu.is_some()),
+            ("headerSystemFilename", d.header_system_filename.is_some()),
+            (
+                "headerDefinitionsPrefix",
+                d.header_definitions_prefix.is_some(),
+            ),
+        ],
+    );
+    w.open(&format!("addrmap {}", identifier(&d.name)));
+    w.strings(None, Some(&d.description));
+    for p in &d.peripherals {
+        let base = p
+            .derived_from
+            .as_ref()
+            .map(|base| {
+                d.peripherals
+                    .iter()
+                    .find(|b| &b.name == base)
+                    .ok_or_else(|| EncodeError::UnknownDerivedFrom(p.name.clone(), base.clone()))
+            })
+            .transpose()?;
+        if p.derived_from.is_some() {
+            w.report.unmapped(&p.name, "derivedFrom");
+        }
+        let derived;
+        let info: &PeripheralInfo = match base {
+            Some(base) => {
+                derived = p.derive_from(base);
+                &derived
+            }
+            None => p,
+        };
+        let props = info
+            .default_register_properties
+            .derive_from(&d.default_register_properties);
+        match p {
+            Peripheral::Array(_, dim) if is_indexed_from_zero(&info.name, dim) => {
+                w.report.dim(dim, &p.name);
+                w.open("addrmap");
+                w.peripheral_body(info, &props)?;
+                w.close(&instance(&info.name, Some(dim), info.base_address));
+            }
+            Peripheral::Array(_, dim) => {
+                w.report.unmapped(&p.name, "dim");
+                for info in svd::peripheral::expand(info, dim) {
+                    w.open("addrmap");
+                    w.peripheral_body(&info, &props)?;
+                    w.close(&instance(&info.name, None, info.base_address));
+                }
+            }
+            Peripheral::Single(_) => {
+                w.open("addrmap");
+                w.peripheral_body(info, &props)?;
+                w.close(&instance(&info.name, None, info.base_address));
+            }
+        }
+    }
+    w.close("");
+    Ok((w.out, w.report.unmapped))
+}
+
+pub(crate) fn encode_peripheral(
+    p: &Peripheral,
+    config: &Config,
+) -> Result<(String, Vec<Unmapped>), EncodeError> {
+    let mut w = Writer::new(config, None);
+    w.report.unmapped_if(
+        &p.name,
+        [
+            ("derivedFrom", p.derived_from.is_some()),
+            ("dim", matches!(p, Peripheral::Array(..))),
+        ],
+    );
+    w.open(&format!("addrmap {}", identifier(&base_name(&p.name))));
+    w.peripheral_body(p, &p.default_register_properties)?;
+    w.close("");
+    Ok((w.out, w.report.unmapped))
+}
+
+struct Writer<'a> {
+    out: String,
+    depth: usize,
+    indent: String,
+    line_ending: &'static str,
+    report: Report,
+    /// Device for resolving `derivedFrom` paths through other peripherals
+    device: Option<&'a Device>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(config: &Config, device: Option<&'a Device>) -> Self {
+        Self {
+            out: String::new(),
+            depth: 0,
+            indent: config.indent.as_string(),
+            line_ending: config.line_ending.as_str(),
+            report: Report::new("SystemRDL"),
+            device,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.out.push_str(&self.indent);
+        }
+        self.out.push_str(line);
+        self.out.push_str(self.line_ending);
+    }
+
+    /// Starts component definition
+    fn open(&mut self, head: &str) {
+        self.line(&format!("{head} {{"));
+        self.depth += 1;
+    }
+
+    /// Ends component definition with instance
+    fn close(&mut self, instance: &str) {
+        self.depth -= 1;
+        if instance.is_empty() {
+            self.line("};");
+        } else {
+            self.line(&format!("}} {instance};"));
+        }
+    }
+
+    fn strings(&mut self, name: Option<&str>, desc: Option<&str>) {
+        if let Some(name) = name {
+            self.line(&format!("name = {};", string(name)));
+        }
+        if let Some(desc) = desc.filter(|desc| !desc.is_empty()) {
+            self.line(&format!("desc = {};", string(desc)));
+        }
+    }
+
+    fn peripheral_body(
+        &mut self,
+        p: &PeripheralInfo,
+        props: &RegisterProperties,
+    ) -> Result<(), EncodeError> {
+        let path = &p.name;
+        self.report.unmapped_if(
+            path,
+            [
+                ("version", p.version.is_some()),
+                ("alternatePeripheral", p.alternate_peripheral.is_some()),
+                ("groupName", p.group_name.is_some()),
+                ("prependToName", p.prepend_to_name.is_some()),
+                ("appendToName", p.append_to_name.is_some()),
+                ("headerStructName", p.header_struct_name.is_some()),
+                ("addressBlock", p.address_block.is_some()),
+                ("interrupt", !p.interrupt.is_empty()),
+                ("protection", props.protection.is_some()),
+            ],
+        );
+        self.strings(p.display_name.as_deref(), p.description.as_deref());
+        if let Some(registers) = &p.registers {
+            self.registers(registers, props, path)?;
+        }
+        Ok(())
+    }
+
+    fn registers(
+        &mut self,
+        children: &[RegisterCluster],
+        inherited: &RegisterProperties,
+        scope: &str,
+    ) -> Result<(), EncodeError> {
+        // Aliases are written after their primary registers
+        let mut alternates = Vec::new();
+        for rc in children {
+            match rc {
+                RegisterCluster::Register(r) => {
+                    let path = format!("{scope}.{}", r.name);
+                    if r.derived_from.is_some() {
+                        self.report.unmapped(&path, "derivedFrom");
+                    }
+                    let r = resolve(r, children, self.device, |rc| match rc {
+                        RegisterCluster::Register(r) => Some(r),
+                        _ => None,
+                    })?;
+                    let props = r.properties.derive_from(inherited);
+                    if r.alternate_group.is_some() || r.alternate_register.is_some() {
+                        alternates.push((path, r, props));
+                        continue;
+                    }
+                    match &*r {
+                        Register::Array(info, dim) if is_indexed_from_zero(&info.name, dim) => {
+                            self.report.dim(dim, &path);
+                            self.register(info, Some(dim), &props, &path, None);
+                        }
+                        Register::Array(info, dim) => {
+                            self.report.unmapped(&path, "dim");
+                            for info in svd::register::expand(info, dim) {
+                                self.register(&info, None, &props, &path, None);
+                            }
+                        }
+                        Register::Single(info) => self.register(info, None, &props, &path, None),
+                    }
+                }
+                RegisterCluster::Cluster(c) => {
+                    let path = format!("{scope}.{}", c.name);
+                    if c.derived_from.is_some() {
+                        self.report.unmapped(&path, "derivedFrom");
+                    }
+                    let c = resolve(c, children, self.device, |rc| match rc {
+                        RegisterCluster::Cluster(c) => Some(c),
+                        _ => None,
+                    })?;
+                    let props = c.default_register_properties.derive_from(inherited);
+                    match &*c {
+                        Cluster::Array(info, dim) if is_indexed_from_zero(&info.name, dim) => {
+                            self.report.dim(dim, &path);
+                            self.register_file(info, Some(dim), &props, &path)?;
+                        }
+                        Cluster::Array(info, dim) => {
+                            self.report.unmapped(&path, "dim");
+                            for info in svd::cluster::expand(info, dim) {
+                                self.register_file(&info, None, &props, &path)?;
+                            }
+                        }
+                        Cluster::Single(info) => self.register_file(info, None, &props, &path)?,
+                    }
+                }
+            }
+        }
+        for (path, r, props) in alternates {
+            self.alias(&r, &props, &path, children, inherited)?;
+        }
+        Ok(())
+    }
+
+    /// Alternate register as `alias` of its primary register, which has the same width
+    fn alias(
+        &mut self,
+        r: &Register,
+        props: &RegisterProperties,
+        path: &str,
+        children: &[RegisterCluster],
+        inherited: &RegisterProperties,
+    ) -> Result<(), EncodeError> {
+        let primary = r.alternate_register.as_deref().unwrap_or(&r.name);
+        let primary = children.iter().find_map(|rc| match rc {
+            RegisterCluster::Register(p)
+                if p.name == primary
+                    && p.alternate_group.is_none()
+                    && p.alternate_register.is_none() =>
+            {
+                Some(p)
+            }
+            _ => None,
+        });
+        let primary = primary
+            .map(|p| {
+                resolve(p, children, self.device, |rc| match rc {
+                    RegisterCluster::Register(r) => Some(r),
+                    _ => None,
+                })
+            })
+            .transpose()?;
+        let width = |props: &RegisterProperties| props.size.unwrap_or(32);
+        match (r, primary.as_deref()) {
+            (Register::Single(info), Some(Register::Single(p)))
+                if width(&p.properties.derive_from(inherited)) == width(props) =>
+            {
+                self.register(info, None, props, path, Some(&register_name(p)));
+            }
+            _ if r.alternate_register.is_some() => {
+                self.report.unmapped(path, "alternateRegister");
+            }
+            _ => self.report.unmapped(path, "alternateGroup"),
+        }
+        Ok(())
+    }
+
+    fn register_file(
+        &mut self,
+        c: &ClusterInfo,
+        dim: Option<&DimElement>,
+        props: &RegisterProperties,
+        path: &str,
+    ) -> Result<(), EncodeError> {
+        self.report.unmapped_if(
+            path,
+            [
+                ("alternateCluster", c.alternate_cluster.is_some()),
+                ("headerStructName", c.header_struct_name.is_some()),
+                ("protection", props.protection.is_some()),
+            ],
+        );
+        self.open("regfile");
+        self.strings(None, c.description.as_deref());
+        self.registers(&c.children, props, path)?;
+        self.close(&instance(&c.name, dim, c.address_offset.into()));
+        Ok(())
+    }
+
+    fn register(
+        &mut self,
+        r: &RegisterInfo,
+        dim: Option<&DimElement>,
+        props: &RegisterProperties,
+        path: &str,
+        alias: Option<&str>,
+    ) {
+        self.report.unmapped_if(
+            path,
+            [
+                ("dataType", r.datatype.is_some()),
+                ("writeConstraint", r.write_constraint.is_some()),
+                ("resetMask", props.reset_mask.is_some()),
+                ("protection", props.protection.is_some()),
+            ],
+        );
+        let name = register_name(r);
+        let size = props.size.unwrap_or(32);
+        let access = props.access.unwrap_or_default();
+        match alias {
+            Some(primary) => self.open(&format!("alias {} reg", identifier(primary))),
+            None => self.open("reg"),
+        }
+        self.strings(r.display_name.as_deref(), r.description.as_deref());
+        if size != 32 {
+            self.line(&format!("regwidth = {size};"));
+        }
+        let fields = match r.fields.as_deref() {
+            Some(fields) if !fields.is_empty() => {
+                let mut infos = Vec::new();
+                for f in fields {
+                    match f {
+                        Field::Single(info) => infos.push(info.clone()),
+                        Field::Array(info, dim) => {
+                            self.report.unmapped(&format!("{path}.{}", f.name), "dim");
+                            infos.extend(svd::field::expand(info, dim));
+                        }
+                    }
+                }
+                infos
+            }
+            _ => vec![whole_register_field(r, size)],
+        };
+        for f in &fields {
+            let path = format!("{path}.{}", f.name);
+            self.report.unmapped_if(
+                &path,
+                [
+                    ("derivedFrom", f.derived_from.is_some()),
+                    ("writeConstraint", f.write_constraint.is_some()),
+                    // SystemRDL field has only one encoding
+                    ("enumeratedValues", f.enumerated_values.len() > 1),
+                ],
+            );
+            let encoding = f
+                .enumerated_values
+                .first()
+                .filter(|evs| evs.values.iter().any(|ev| ev.value.is_some()));
+            let enum_name = format!("{}_e", identifier(&f.name));
+            if let Some(evs) = encoding {
+                self.report.unmapped_if(
+                    &path,
+                    [
+                        ("enumeratedValues/name", evs.name.is_some()),
+                        ("enumeratedValues/derivedFrom", evs.derived_from.is_some()),
+                        ("enumeratedValues/usage", evs.usage.is_some()),
+                    ],
+                );
+                self.open(&format!("enum {enum_name}"));
+                for ev in &evs.values {
+                    let Some(value) = ev.value else {
+                        self.report
+                            .unmapped(&format!("{path}.{}", ev.name), "isDefault");
+                        continue;
+                    };
+                    let value = format!("{}'h{value:X}", f.bit_range.width);
+                    match &ev.description {
+                        Some(desc) => self.line(&format!(
+                            "{} = {value} {{ desc = {}; }};",
+                            identifier(&ev.name),
+                            string(desc)
+                        )),
+                        None => self.line(&format!("{} = {value};", identifier(&ev.name))),
+                    }
+                }
+                self.close("");
+            }
+
+            self.open("field");
+            self.strings(None, f.description.as_deref());
+            let access = f.access.unwrap_or(access);
+            self.line(&format!("sw = {};", sw(access)));
+            self.line(&format!(
+                "hw = {};",
+                if access == Access::ReadOnly { "w" } else { "r" }
+            ));
+            if let Some(ra) = f.read_action.or(r.read_action) {
+                if ra == ReadAction::ModifyExternal {
+                    self.report.unmapped(&path, "readAction");
+                }
+                self.line(&format!("onread = {};", onread(ra)));
+            }
+            if let Some(mwv) = f
+                .modified_write_values
+                .or(r.modified_write_values)
+                .and_then(onwrite)
+            {
+                self.line(&format!("onwrite = {mwv};"));
+            }
+            if encoding.is_some() {
+                self.line(&format!("encode = {enum_name};"));
+            }
+            let mut inst = format!(
+                "{}[{}:{}]",
+                identifier(&f.name),
+                f.bit_range.msb(),
+                f.bit_range.lsb()
+            );
+            // Fields outside the reset value have no reset
+            if let Some(reset) = props
+                .reset_value
+                .and_then(|v| (v & f.bitmask()).checked_shr(f.bit_range.offset))
+            {
+                write!(inst, " = 0x{reset:X}").unwrap();
+            }
+            self.close(&inst);
+        }
+        match alias {
+            // Alias has the address of its primary register
+            Some(_) => self.close(&identifier(&name)),
+            None => self.close(&instance(&name, dim, r.address_offset.into())),
+        }
+    }
+}
+
+/// Instance with address, and stride for arrays
+fn instance(name: &str, dim: Option<&DimElement>, address: u64) -> String {
+    let name = identifier(&base_name(name));
+    match dim {
+        Some(dim) => format!(
+            "{name}[{}] @ 0x{address:X} += 0x{:X}",
+            dim.dim, dim.dim_increment
+        ),
+        None => format!("{name} @ 0x{address:X}"),
+    }
+}
+
+fn sw(access: Access) -> &'static str {
+    match access {
+        Access::ReadOnly => "r",
+        Access::WriteOnly => "w",
+        Access::ReadWrite => "rw",
+        Access::WriteOnce => "w1",
+        Access::ReadWriteOnce => "rw1",
+    }
+}
+
+fn onread(ra: ReadAction) -> &'static str {
+    match ra {
+        ReadAction::Clear => "rclr",
+        ReadAction::Set => "rset",
+        ReadAction::Modify | ReadAction::ModifyExternal => "ruser",
+    }
+}
+
+/// `onwrite` value. Plain writes have none
+fn onwrite(mwv: ModifiedWriteValues) -> Option<&'static str> {
+    Some(match mwv {
+        ModifiedWriteValues::OneToClear => "woclr",
+        ModifiedWriteValues::OneToSet => "woset",
+        ModifiedWriteValues::OneToToggle => "wot",
+        ModifiedWriteValues::ZeroToClear => "wzc",
+        ModifiedWriteValues::ZeroToSet => "wzs",
+        ModifiedWriteValues::ZeroToToggle => "wzt",
+        ModifiedWriteValues::Clear => "wclr",
+        ModifiedWriteValues::Set => "wset",
+        ModifiedWriteValues::Modify => return None,
+    })
+}
+
+/// SystemRDL keywords which are escaped when used as names
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "accesstype",
+    "addressingtype",
+    "addrmap",
+    "alias",
+    "all",
+    "bit",
+    "boolean",
+    "bothedge",
+    "compact",
+    "component",
+    "componentwidth",
+    "constraint",
+    "default",
+    "encode",
+    "enum",
+    "external",
+    "false",
+    "field",
+    "fullalign",
+    "hw",
+    "inside",
+    "internal",
+    "level",
+    "longint",
+    "mem",
+    "na",
+    "negedge",
+    "nonsticky",
+    "number",
+    "onreadtype",
+    "onwritetype",
+    "posedge",
+    "property",
+    "r",
+    "rclr",
+    "ref",
+    "reg",
+    "regalign",
+    "regfile",
+    "rset",
+    "ruser",
+    "rw",
+    "rw1",
+    "signal",
+    "string",
+    "struct",
+    "sw",
+    "this",
+    "true",
+    "type",
+    "unsigned",
+    "w",
+    "w1",
+    "wclr",
+    "woclr",
+    "woset",
+    "wot",
+    "wr",
+    "wset",
+    "wuser",
+    "wzc",
+    "wzs",
+    "wzt",
+];
+
+/// Name as SystemRDL identifier
+fn identifier(name: &str) -> String {
+    let mut id = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if id.starts_with(|c: char| c.is_ascii_digit()) {
+        id.insert(0, '_');
+    }
+    if KEYWORDS.contains(&id.as_str()) {
+        id.insert(0, '\\');
+    }
+    id
+}
+
+fn string(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+#[test]
+fn test_identifier() {
+    assert_eq!(identifier("CR"), "CR");
+    assert_eq!(identifier("reg"), "\\reg");
+    assert_eq!(identifier("1WIRE"), "_1WIRE");
+    assert_eq!(identifier("A-B"), "A_B");
+}
@@ -0,0 +1,236 @@
+//! Writing of encoded elements as XML events.
+//!
+//! Elements are written to [`EventWriter`] one by one, so the device can be
+//! streamed peripheral by peripheral without building the whole tree.
+
+use std::borrow::Cow;
+use std::io::Write;
+use std::sync::Arc;
+use xml::attribute::Attribute;
+use xml::common::XmlVersion;
+use xml::name::Name;
+use xml::namespace::Namespace;
+use xml::writer::{EmitterConfig, EventWriter, XmlEvent};
+use xmltree::{Element, XMLNode};
+
+use super::{Config, DescriptionFormat, EncodeError, Encoding, LineEnding, SelfClosing};
+
+/// Layout of written XML
+#[derive(Clone)]
+pub(crate) struct Format {
+    pub(crate) indent: String,
+    line_ending: LineEnding,
+    encoding: Encoding,
+    self_closing: SelfClosing,
+    description: DescriptionFormat,
+    attribute_order: Arc<[String]>,
+}
+
+impl Format {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            indent: config.indent.as_string(),
+            line_ending: config.line_ending,
+            encoding: config.declaration_encoding,
+            self_closing: config.self_closing,
+            description: config.description_format,
+            attribute_order: config.attribute_order.clone(),
+        }
+    }
+
+    pub(crate) fn line_ending(&self) -> &'static str {
+        self.line_ending.as_str()
+    }
+
+    /// Characters other than ASCII are written as character references
+    /// if the declared encoding is not UTF-8
+    fn ascii_only(&self) -> bool {
+        self.encoding != Encoding::Utf8
+    }
+}
+
+pub(crate) struct XmlWriter<W: Write> {
+    emitter: EventWriter<W>,
+    format: Format,
+    /// Number of open elements
+    depth: usize,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub(crate) fn new(w: W, format: Format, declaration: bool) -> Self {
+        let mut config = EmitterConfig::new();
+        config.perform_indent = true;
+        config.indent_string = format.indent.clone().into();
+        config.line_separator = format.line_ending().into();
+        config.pad_self_closing = format.self_closing == SelfClosing::Padded;
+        config.normalize_empty_elements = format.self_closing != SelfClosing::Expanded;
+        config.write_document_declaration = declaration;
+        // Text is escaped by `escape` to write character references for other encodings
+        config.perform_escaping = false;
+        Self {
+            emitter: EventWriter::new_with_config(w, config),
+            format,
+            depth: 0,
+        }
+    }
+
+    pub(crate) fn start_document(&mut self) -> Result<(), EncodeError> {
+        self.emitter.write(XmlEvent::StartDocument {
+            version: XmlVersion::Version10,
+            encoding: Some(&self.format.encoding.to_string()),
+            standalone: None,
+        })?;
+        Ok(())
+    }
+
+    /// Writes start tag of `elem` without its children
+    pub(crate) fn start_element(&mut self, elem: &Element) -> Result<(), EncodeError> {
+        let mut attributes = Vec::with_capacity(elem.attributes.len());
+        for (k, v) in &elem.attributes {
+            check_text(k, v)?;
+            attributes.push((k, self.escape(v, true)));
+        }
+        let order = &self.format.attribute_order;
+        attributes.sort_by_key(|(k, _)| order.iter().position(|a| a == *k).unwrap_or(order.len()));
+        let attributes = attributes
+            .iter()
+            .map(|(k, v)| Attribute {
+                name: Name::local(k),
+                value: v,
+            })
+            .collect::<Vec<_>>();
+        self.emitter.write(XmlEvent::StartElement {
+            name: Name::local(&elem.name),
+            attributes: Cow::Owned(attributes),
+            namespace: Cow::Owned(Namespace::empty()),
+        })?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn end_element(&mut self) -> Result<(), EncodeError> {
+        self.emitter.write(XmlEvent::EndElement { name: None })?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Writes `elem` with all its children
+    pub(crate) fn element(&mut self, elem: &Element) -> Result<(), EncodeError> {
+        self.start_element(elem)?;
+        if elem.children.is_empty() && self.format.self_closing == SelfClosing::Expanded {
+            // Keeps end tag on the same line
+            self.emitter.write(XmlEvent::Characters(""))?;
+        }
+        for node in &elem.children {
+            self.node(&elem.name, node)?;
+        }
+        self.end_element()
+    }
+
+    pub(crate) fn node(&mut self, parent: &str, node: &XMLNode) -> Result<(), EncodeError> {
+        match node {
+            XMLNode::Element(e) => return self.element(e),
+            XMLNode::Text(text) => {
+                check_text(parent, text)?;
+                let text = if parent == "description" {
+                    self.description(text)
+                } else {
+                    Cow::Borrowed(text.as_str())
+                };
+                let text = self.escape(&text, false);
+                self.emitter.write(XmlEvent::Characters(&text))?
+            }
+            XMLNode::Comment(comment) => self.emitter.write(XmlEvent::Comment(comment))?,
+            XMLNode::CData(data) => self.emitter.write(XmlEvent::CData(data))?,
+            XMLNode::ProcessingInstruction(name, data) => {
+                self.emitter.write(XmlEvent::ProcessingInstruction {
+                    name,
+                    data: data.as_deref(),
+                })?
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.emitter.into_inner()
+    }
+
+    /// Text of `description` element in configured format
+    fn description<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut words = text.split_whitespace();
+        match self.format.description {
+            DescriptionFormat::Unchanged => Cow::Borrowed(text),
+            DescriptionFormat::SingleLine => Cow::Owned(words.collect::<Vec<_>>().join(" ")),
+            DescriptionFormat::Wrap(width) => {
+                let Some(first) = words.next() else {
+                    return Cow::Borrowed("");
+                };
+                let indent = self.format.indent.repeat(self.depth);
+                let mut out = String::from(first);
+                let mut line = first.chars().count();
+                for word in words {
+                    let len = word.chars().count();
+                    if line + 1 + len > width {
+                        out.push('\n');
+                        out.push_str(&indent);
+                        line = len;
+                    } else {
+                        out.push(' ');
+                        line += 1 + len;
+                    }
+                    out.push_str(word);
+                }
+                Cow::Owned(out)
+            }
+        }
+    }
+
+    /// Escapes markup characters, line endings and, for encodings other than UTF-8,
+    /// all characters which are not ASCII
+    fn escape<'a>(&self, text: &'a str, attribute: bool) -> Cow<'a, str> {
+        let ascii_only = self.format.ascii_only();
+        let crlf = self.format.line_ending == LineEnding::CrLf;
+        let needs_escape = |c: char| {
+            matches!(c, '<' | '>' | '&')
+                || (attribute && matches!(c, '"' | '\'' | '\n' | '\r'))
+                || (!attribute && crlf && c == '\n')
+                || (ascii_only && !c.is_ascii())
+        };
+        if !text.contains(needs_escape) {
+            return Cow::Borrowed(text);
+        }
+        let mut out = String::with_capacity(text.len() + 16);
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '&' => out.push_str("&amp;"),
+                '"' if attribute => out.push_str("&quot;"),
+                '\'' if attribute => out.push_str("&apos;"),
+                '\n' if attribute => out.push_str("&#xA;"),
+                '\r' if attribute => out.push_str("&#xD;"),
+                '\r' if crlf && chars.peek() == Some(&'\n') => {}
+                '\n' if crlf => out.push_str("\r\n"),
+                c if ascii_only && !c.is_ascii() => {
+                    out.push_str(&format!("&#x{:X};", u32::from(c)));
+                }
+                c => out.push(c),
+            }
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// Checks that `text` of element or attribute `name` can be represented in XML 1.0
+fn check_text(name: &str, text: &str) -> Result<(), EncodeError> {
+    match text.chars().find(|c| !is_xml_char(*c)) {
+        Some(c) => Err(EncodeError::InvalidCharacter(name.into(), c)),
+        None => Ok(()),
+    }
+}
+
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
@@ -7,6 +7,7 @@ impl Parse for AddressBlock {
     type Config = Config;
 
     fn parse(tree: &Node, config: &Self::Config) -> Result<Self, Self::Error> {
+        check_children(tree, config)?;
         Self::builder()
             .offset(tree.get_child_u32("offset")?)
             .size(tree.get_child_u32("size")?)
@@ -0,0 +1,193 @@
+//! Loading SVD files from compressed files and CMSIS-Packs.
+//!
+//! Supported inputs are plain SVD files, gzip-compressed SVD files (`.svd.gz`),
+//! zip archives with a single SVD file and CMSIS-Packs (`.pack`, zip archives
+//! with a `.pdsc` package description that names the SVD file of each device).
+
+use anyhow::{anyhow, Context};
+use roxmltree::{Document, Node};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::{parse_bytes_with_config, trim_utf8_bom, Config, Limits, SVDError};
+use crate::svd::Device;
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Device described in CMSIS-Pack
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PackDevice {
+    /// Name of the device or the device variant (`Dname` or `Dvariant`)
+    pub name: String,
+    /// Path of the SVD file inside the pack
+    pub svd: String,
+}
+
+/// Reads the list of devices and their SVD files from `.pdsc` package description
+pub fn pdsc_devices(pdsc: &str) -> anyhow::Result<Vec<PackDevice>> {
+    let tree = Document::parse(trim_utf8_bom(pdsc))?;
+    let mut devices = Vec::new();
+    for node in tree.descendants() {
+        let name = match node.tag_name().name() {
+            "device" => node.attribute("Dname"),
+            "variant" => node.attribute("Dvariant"),
+            _ => None,
+        };
+        let Some(name) = name else {
+            continue;
+        };
+        // `debug` can be specified on `family`, `subFamily`, `device` or `variant` level
+        if let Some(svd) = node.ancestors().find_map(|n| debug_svd(&n)) {
+            devices.push(PackDevice {
+                name: name.to_string(),
+                svd: svd.replace('\\', "/"),
+            });
+        }
+    }
+    Ok(devices)
+}
+
+fn debug_svd<'a>(node: &Node<'a, '_>) -> Option<&'a str> {
+    node.children()
+        .filter(|c| c.has_tag_name("debug"))
+        .find_map(|c| c.attribute("svd"))
+}
+
+/// Opened CMSIS-Pack
+pub struct Pack<R> {
+    archive: ZipArchive<R>,
+    devices: Vec<PackDevice>,
+}
+
+impl Pack<BufReader<File>> {
+    /// Opens CMSIS-Pack file
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> Pack<R> {
+    /// Reads CMSIS-Pack from zip archive
+    pub fn new(reader: R) -> anyhow::Result<Self> {
+        Self::new_with_limits(reader, &Limits::default())
+    }
+
+    /// Reads CMSIS-Pack from zip archive. Package description is read within `limits`
+    pub fn new_with_limits(reader: R, limits: &Limits) -> anyhow::Result<Self> {
+        let mut archive = ZipArchive::new(reader)?;
+        let pdsc = archive
+            .file_names()
+            .find(|n| n.ends_with(".pdsc") && !n.contains('/'))
+            .or_else(|| archive.file_names().find(|n| n.ends_with(".pdsc")))
+            .ok_or_else(|| anyhow!("Package description (.pdsc) is not found in the pack"))?
+            .to_string();
+        let pdsc = read_entry(&mut archive, &pdsc, limits)?;
+        let devices =
+            pdsc_devices(&String::from_utf8_lossy(&pdsc)).context("Parsing package description")?;
+        Ok(Self { archive, devices })
+    }
+
+    /// Devices described in the pack
+    pub fn devices(&self) -> &[PackDevice] {
+        &self.devices
+    }
+
+    /// Parses the SVD file of the device or device variant with `name`
+    pub fn device(&mut self, name: &str) -> anyhow::Result<Device> {
+        self.device_with_config(name, &Config::default())
+    }
+
+    /// Parses the SVD file of the device or device variant with `name`
+    pub fn device_with_config(&mut self, name: &str, config: &Config) -> anyhow::Result<Device> {
+        let device = self
+            .devices
+            .iter()
+            .find(|d| d.name == name)
+            .or_else(|| {
+                self.devices
+                    .iter()
+                    .find(|d| d.name.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| anyhow!("Device `{name}` is not found in the pack"))?;
+        let svd = device.svd.clone();
+        let bytes = read_entry(&mut self.archive, &svd, &config.limits)?;
+        parse_bytes_with_config(&bytes, config).with_context(|| format!("Parsing {svd}"))
+    }
+}
+
+/// Reads the file from archive. Path is compared case-insensitively if there is no exact match
+fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &str,
+    limits: &Limits,
+) -> anyhow::Result<Vec<u8>> {
+    let path = path.trim_start_matches("./");
+    let name = archive
+        .file_names()
+        .find(|n| *n == path)
+        .or_else(|| archive.file_names().find(|n| n.eq_ignore_ascii_case(path)))
+        .ok_or_else(|| anyhow!("File {path} is not found in the archive"))?
+        .to_string();
+    read_decompressed(archive.by_name(&name)?, limits)
+}
+
+/// Reads decompressed data up to [`Limits::max_decompressed_size`]
+fn read_decompressed(reader: impl Read, limits: &Limits) -> anyhow::Result<Vec<u8>> {
+    let max = limits.max_decompressed_size.unwrap_or(u64::MAX);
+    let mut bytes = Vec::new();
+    // One more byte tells that the data is larger
+    reader.take(max.saturating_add(1)).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > max {
+        return Err(SVDError::DecompressedTooLarge(max).into());
+    }
+    Ok(bytes)
+}
+
+/// Loads the SVD file, gzip-compressed SVD file or zip archive with the single SVD file
+pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Device> {
+    load_with_config(path, &Config::default())
+}
+
+/// Loads the SVD file, gzip-compressed SVD file or zip archive with the single SVD file
+pub fn load_with_config(path: impl AsRef<Path>, config: &Config) -> anyhow::Result<Device> {
+    let path = path.as_ref();
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut bytes))
+        .with_context(|| format!("Reading {}", path.display()))?;
+    load_bytes(&bytes, config).with_context(|| format!("Loading {}", path.display()))
+}
+
+/// Parses the SVD file, gzip-compressed SVD file or zip archive with the single SVD file
+pub fn load_bytes(bytes: &[u8], config: &Config) -> anyhow::Result<Device> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        let decoded = read_decompressed(flate2::read::GzDecoder::new(bytes), &config.limits)?;
+        parse_bytes_with_config(&decoded, config)
+    } else if bytes.starts_with(ZIP_MAGIC) {
+        let mut archive = ZipArchive::new(std::io::Cursor::new(bytes))?;
+        let svds = archive
+            .file_names()
+            .filter(|n| n.to_ascii_lowercase().ends_with(".svd"))
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        match svds.as_slice() {
+            [svd] => {
+                let bytes = read_entry(&mut archive, svd, &config.limits)?;
+                parse_bytes_with_config(&bytes, config).with_context(|| format!("Parsing {svd}"))
+            }
+            [] => Err(anyhow!("No SVD files found in the archive")),
+            _ => Err(anyhow!(
+                "Several SVD files found in the archive: {}. Use `Pack` to select the device",
+                svds.join(", ")
+            )),
+        }
+    } else {
+        parse_bytes_with_config(bytes, config)
+    }
+}
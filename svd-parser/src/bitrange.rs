@@ -84,12 +84,13 @@ impl Parse for BitRange {
                 return Err(SVDError::InvalidBitRange(InvalidBitRange::Syntax).at(tree.id()));
             };
 
-        if start > end {
-            return Err(SVDError::InvalidBitRange(InvalidBitRange::Size).at(tree.id()));
-        }
+        let width = end
+            .checked_sub(start)
+            .and_then(|w| w.checked_add(1))
+            .ok_or_else(|| SVDError::InvalidBitRange(InvalidBitRange::Size).at(tree.id()))?;
         Ok(Self {
             offset: start,
-            width: end - start + 1,
+            width,
             range_type,
         })
     }
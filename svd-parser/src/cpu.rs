@@ -11,6 +11,7 @@ impl Parse for Cpu {
         if !tree.has_tag_name("cpu") {
             return Err(SVDError::NotExpectedTag("cpu".to_string()).at(tree.id()));
         }
+        check_children(tree, config)?;
 
         // Vendor systick is required by ARM targets, but not others
         // So for others we just default to false if not provided
@@ -20,15 +21,9 @@ impl Parse for Cpu {
             _ => false,
         };
 
-        let has_mpu_present = match tree.get_child_bool("mpuPresent") {
-            Ok(v) => v,
-            _ => false,
-        };
+        let has_mpu_present = tree.get_child_bool("mpuPresent").unwrap_or_default();
 
-        let has_fpu_present = match tree.get_child_bool("fpuPresent") {
-            Ok(v) => v,
-            _ => false,
-        };
+        let has_fpu_present = tree.get_child_bool("fpuPresent").unwrap_or_default();
 
         Cpu::builder()
             .name(tree.get_child_text("name")?)
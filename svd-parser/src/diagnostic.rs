@@ -0,0 +1,167 @@
+//! Diagnostics collected during parsing.
+//!
+//! With [`Config::lenient`](crate::Config::lenient) enabled, elements that fail to parse
+//! are dropped (or, for optional values, replaced by `None`) instead of aborting the whole parse.
+//! Each such recovery is reported as a [`ParseDiagnostic`].
+//! Unknown elements are also reported if [`UnknownElements::Warn`](crate::UnknownElements::Warn) is set.
+
+use roxmltree::{Document, Node, NodeId, TextPos};
+use std::cell::RefCell;
+use std::fmt;
+
+use super::{SVDError, SVDErrorAt};
+
+/// What the parser did with a malformed element
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// Element was skipped
+    Dropped,
+    /// Optional value was not parsed and left empty
+    Defaulted,
+    /// Unknown element was ignored
+    Ignored,
+}
+
+impl DiagnosticKind {
+    /// Get lowercase name of the recovery
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Dropped => "dropped",
+            Self::Defaulted => "defaulted",
+            Self::Ignored => "ignored",
+        }
+    }
+}
+
+/// An element dropped, defaulted or ignored by the parser
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ParseDiagnostic {
+    /// What was done with the element
+    pub kind: DiagnosticKind,
+    /// Tag name of the element
+    pub tag: String,
+    /// Content of the `name` child of the element, if any
+    pub name: Option<String>,
+    /// Names of parent elements joined with `.`
+    pub path: String,
+    /// Position of the element
+    pub pos: TextPos,
+    /// Error that caused the recovery
+    pub error: SVDError,
+    /// Position where the error was detected
+    pub error_pos: TextPos,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind.as_str(), self.tag)?;
+        if let Some(name) = &self.name {
+            write!(f, " `{}`", name)?;
+        }
+        write!(f, " at {}", self.pos)?;
+        if !self.path.is_empty() {
+            write!(f, " in `{}`", self.path)?;
+        }
+        write!(f, ": {} at {}", self.error, self.error_pos)
+    }
+}
+
+pub(crate) struct Report {
+    kind: DiagnosticKind,
+    node: NodeId,
+    error: SVDErrorAt,
+}
+
+thread_local! {
+    static REPORTS: RefCell<Option<Vec<Report>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` collecting all recoveries reported inside
+pub(crate) fn collect<R>(f: impl FnOnce() -> R) -> (R, Vec<Report>) {
+    let prev = REPORTS.with(|r| r.borrow_mut().replace(Vec::new()));
+    let res = f();
+    let reports = REPORTS.with(|r| std::mem::replace(&mut *r.borrow_mut(), prev));
+    (res, reports.unwrap_or_default())
+}
+
+/// Number of recoveries reported so far
+pub(crate) fn mark() -> usize {
+    REPORTS.with(|r| r.borrow().as_ref().map(Vec::len).unwrap_or_default())
+}
+
+/// Records a recovery of element `node`, forgetting everything reported after `mark`
+pub(crate) fn report(mark: usize, kind: DiagnosticKind, node: NodeId, error: SVDErrorAt) {
+    REPORTS.with(|r| {
+        if let Some(reports) = r.borrow_mut().as_mut() {
+            reports.truncate(mark);
+            reports.push(Report { kind, node, error });
+        }
+    })
+}
+
+/// Adds recoveries collected on other thread
+#[cfg(feature = "parallel")]
+pub(crate) fn extend(collected: Vec<Report>) {
+    REPORTS.with(|r| {
+        if let Some(reports) = r.borrow_mut().as_mut() {
+            reports.extend(collected);
+        }
+    })
+}
+
+/// Content of the `name` child element
+pub(crate) fn get_name<'a>(node: &Node<'a, '_>) -> Option<&'a str> {
+    node.children()
+        .find(|t| t.has_tag_name("name"))
+        .and_then(|t| t.text())
+}
+
+pub(crate) fn node_path(node: &Node) -> String {
+    let mut names = node
+        .ancestors()
+        .skip(1)
+        .filter(|p| {
+            matches!(
+                p.tag_name().name(),
+                "peripheral"
+                    | "cluster"
+                    | "register"
+                    | "field"
+                    | "enumeratedValues"
+                    | "addressBlock"
+                    | "registerFile"
+            )
+        })
+        .filter_map(|p| get_name(&p))
+        .collect::<Vec<_>>();
+    names.reverse();
+    names.join(".")
+}
+
+/// Elements with recoveries
+pub(crate) fn nodes(reports: &[Report]) -> impl Iterator<Item = NodeId> + '_ {
+    reports.iter().map(|r| r.node)
+}
+
+pub(crate) fn resolve(tree: &Document, reports: Vec<Report>) -> Vec<ParseDiagnostic> {
+    reports
+        .into_iter()
+        .filter_map(|r| {
+            let node = tree.get_node(r.node)?;
+            let error_pos = tree
+                .get_node(r.error.id)
+                .map(|n| tree.text_pos_at(n.range().start))?;
+            Some(ParseDiagnostic {
+                kind: r.kind,
+                tag: node.tag_name().name().to_string(),
+                name: get_name(&node).map(ToString::to_string),
+                path: node_path(&node),
+                pos: tree.text_pos_at(node.range().start),
+                error: r.error.error,
+                error_pos,
+            })
+        })
+        .collect()
+}
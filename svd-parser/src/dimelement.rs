@@ -7,8 +7,13 @@ impl Parse for DimElement {
     type Config = Config;
 
     fn parse(tree: &Node, config: &Self::Config) -> Result<Self, Self::Error> {
+        let dim = tree.get_child_u32("dim")?;
+        config
+            .limits
+            .check_dim(dim.into())
+            .map_err(|e| e.at(tree.id()))?;
         DimElement::builder()
-            .dim(tree.get_child_u32("dim")?)
+            .dim(dim)
             .dim_increment(tree.get_child_u32("dimIncrement")?)
             .dim_index(optional::<DimIndex>("dimIndex", tree, config)?)
             .dim_name(tree.get_child_text_opt("dimName")?)
@@ -24,6 +29,7 @@ impl Parse for DimArrayIndex {
     type Config = Config;
 
     fn parse(tree: &Node, config: &Self::Config) -> Result<Self, Self::Error> {
+        check_children(tree, config)?;
         Ok(Self {
             header_enum_name: tree.get_child_text_opt("headerEnumName")?,
             values: {
@@ -51,8 +57,17 @@ impl Parse for DimIndex {
     type Error = SVDErrorAt;
     type Config = Config;
 
-    fn parse(tree: &Node, _config: &Self::Config) -> Result<Vec<String>, Self::Error> {
-        DimElement::parse_indexes(tree.get_text()?)
-            .ok_or_else(|| SVDError::DimIndexParse.at(tree.id()))
+    fn parse(tree: &Node, config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        let text = tree.get_text()?;
+        // Check the length of range before creating indexes
+        if let Some((start, end)) = text.split_once('-') {
+            if let (Ok(start), Ok(end)) = (start.parse::<u64>(), end.parse::<u64>()) {
+                config
+                    .limits
+                    .check_dim(end.saturating_sub(start) + 1)
+                    .map_err(|e| e.at(tree.id()))?;
+            }
+        }
+        DimElement::parse_indexes(text).ok_or_else(|| SVDError::DimIndexParse.at(tree.id()))
     }
 }
@@ -0,0 +1,234 @@
+//! Known child elements of SVD elements.
+//!
+//! Used to find misspelled or unsupported tags which are otherwise silently ignored by the parser.
+
+use roxmltree::Node;
+
+use super::diagnostic::{self, DiagnosticKind};
+use super::{Config, SVDError, SVDErrorAt};
+
+/// What to do with elements not described by the CMSIS-SVD schema
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownElements {
+    /// Skip them silently
+    #[default]
+    Ignore,
+    /// Report them as [`ParseDiagnostic`](crate::ParseDiagnostic)s and continue
+    Warn,
+    /// Fail parsing
+    Error,
+}
+
+const DIM: [&str; 5] = [
+    "dim",
+    "dimIncrement",
+    "dimIndex",
+    "dimName",
+    "dimArrayIndex",
+];
+const PROPERTIES: [&str; 5] = ["size", "access", "protection", "resetValue", "resetMask"];
+
+const DEVICE: &[&str] = &[
+    "vendor",
+    "vendorID",
+    "name",
+    "series",
+    "version",
+    "description",
+    "licenseText",
+    "cpu",
+    "headerSystemFilename",
+    "headerDefinitionsPrefix",
+    "addressUnitBits",
+    "width",
+    "size",
+    "access",
+    "protection",
+    "resetValue",
+    "resetMask",
+    "peripherals",
+    "vendorExtensions",
+];
+
+const CPU: &[&str] = &[
+    "name",
+    "revision",
+    "endian",
+    "mpuPresent",
+    "fpuPresent",
+    "fpuDP",
+    "dspPresent",
+    "icachePresent",
+    "dcachePresent",
+    "itcmPresent",
+    "dtcmPresent",
+    "vtorPresent",
+    "nvicPrioBits",
+    "vendorSystickConfig",
+    "deviceNumInterrupts",
+    "sauNumRegions",
+    "sauRegionsConfig",
+];
+
+const PERIPHERAL: &[&str] = &[
+    "name",
+    "version",
+    "description",
+    "displayName",
+    "alternatePeripheral",
+    "groupName",
+    "prependToName",
+    "appendToName",
+    "headerStructName",
+    "disableCondition",
+    "baseAddress",
+    "addressBlock",
+    "interrupt",
+    "registers",
+];
+
+const CLUSTER: &[&str] = &[
+    "name",
+    "description",
+    "alternateCluster",
+    "headerStructName",
+    "addressOffset",
+    "register",
+    "cluster",
+];
+
+const REGISTER: &[&str] = &[
+    "name",
+    "displayName",
+    "description",
+    "alternateGroup",
+    "alternateRegister",
+    "addressOffset",
+    "dataType",
+    "modifiedWriteValues",
+    "writeConstraint",
+    "readAction",
+    "fields",
+];
+
+const FIELD: &[&str] = &[
+    "name",
+    "description",
+    "bitOffset",
+    "bitWidth",
+    "lsb",
+    "msb",
+    "bitRange",
+    "access",
+    "modifiedWriteValues",
+    "writeConstraint",
+    "readAction",
+    "enumeratedValues",
+];
+
+/// Returns list of child elements allowed in element with `tag` name
+///
+/// Returns `None` for elements which contain only text or are not known
+pub fn allowed_children(tag: &str) -> Option<Vec<&'static str>> {
+    let dimable = |list: &[&'static str]| DIM.iter().chain(list).copied().collect();
+    let with_properties = |list: &[&'static str]| {
+        DIM.iter()
+            .chain(list)
+            .chain(PROPERTIES.iter())
+            .copied()
+            .collect()
+    };
+    Some(match tag {
+        "device" => DEVICE.to_vec(),
+        "cpu" => CPU.to_vec(),
+        "sauRegionsConfig" => vec!["region"],
+        "region" => vec!["base", "limit", "access"],
+        "peripherals" => vec!["peripheral"],
+        "peripheral" => with_properties(PERIPHERAL),
+        "addressBlock" => vec!["offset", "size", "usage", "protection"],
+        "interrupt" => vec!["name", "description", "value"],
+        "registers" => vec!["register", "cluster"],
+        "cluster" => with_properties(CLUSTER),
+        "register" => with_properties(REGISTER),
+        "fields" => vec!["field"],
+        "field" => dimable(FIELD),
+        "writeConstraint" => vec!["writeAsRead", "useEnumeratedValues", "range"],
+        "range" => vec!["minimum", "maximum"],
+        "enumeratedValues" => vec!["name", "headerEnumName", "usage", "enumeratedValue"],
+        "enumeratedValue" => vec!["name", "description", "value", "isDefault"],
+        "dimArrayIndex" => vec!["headerEnumName", "enumeratedValue"],
+        _ => return None,
+    })
+}
+
+/// Checks that all children of the element are known
+pub(crate) fn check_children(tree: &Node, config: &Config) -> Result<(), SVDErrorAt> {
+    if config.unknown_elements == UnknownElements::Ignore {
+        return Ok(());
+    }
+    let parent = tree.tag_name().name();
+    let Some(allowed) = allowed_children(parent) else {
+        return Ok(());
+    };
+    for child in tree.children().filter(Node::is_element) {
+        let tag = child.tag_name().name();
+        if allowed.contains(&tag) {
+            continue;
+        }
+        let err = SVDError::UnknownElement(
+            tag.to_string(),
+            parent.to_string(),
+            suggest(tag, &allowed).map(ToString::to_string),
+        )
+        .at(child.id());
+        match config.unknown_elements {
+            UnknownElements::Ignore => {}
+            UnknownElements::Warn => {
+                diagnostic::report(diagnostic::mark(), DiagnosticKind::Ignored, child.id(), err)
+            }
+            UnknownElements::Error => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Find the most similar of allowed tags
+fn suggest<'a>(tag: &str, allowed: &[&'a str]) -> Option<&'a str> {
+    let tag = tag.to_lowercase();
+    let max = (tag.chars().count() / 3).max(1);
+    allowed
+        .iter()
+        .map(|a| (distance(&tag, &a.to_lowercase()), *a))
+        .filter(|(d, _)| *d <= max)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, a)| a)
+}
+
+/// Levenshtein distance
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+#[test]
+fn test_suggest() {
+    let allowed = allowed_children("register").unwrap();
+    assert_eq!(suggest("resetvalue", &allowed), Some("resetValue"));
+    assert_eq!(suggest("acess", &allowed), Some("access"));
+    assert_eq!(suggest("adressOffset", &allowed), Some("addressOffset"));
+    assert_eq!(suggest("vendorSpecific", &allowed), None);
+}
@@ -0,0 +1,132 @@
+//! Decoding of SVD files which are not in UTF-8.
+//!
+//! The encoding is detected from the byte order mark or from the `encoding`
+//! attribute of the XML declaration. Supported encodings are UTF-8, UTF-16,
+//! ISO-8859-1 (and its ASCII subset) and windows-1252.
+
+use std::borrow::Cow;
+
+use super::SVDError;
+
+/// Text encoding of the SVD file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8
+    Utf8,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// ISO-8859-1 (Latin-1)
+    Latin1,
+    /// windows-1252
+    Windows1252,
+}
+
+impl Encoding {
+    /// Get encoding by its name as used in the XML declaration
+    pub fn from_label(label: &str) -> Option<Self> {
+        Some(match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Self::Utf8,
+            "utf-16le" => Self::Utf16Le,
+            "utf-16" | "utf-16be" => Self::Utf16Be,
+            "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "l1" | "us-ascii" | "ascii" => {
+                Self::Latin1
+            }
+            "windows-1252" | "cp1252" | "x-cp1252" => Self::Windows1252,
+            _ => return None,
+        })
+    }
+}
+
+/// Detects the encoding of `bytes` and returns it with the length of the byte order mark
+pub fn detect(bytes: &[u8]) -> Result<(Encoding, usize), SVDError> {
+    Ok(match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        // `<?` without byte order mark
+        [b'<', 0, b'?', 0, ..] => (Encoding::Utf16Le, 0),
+        [0, b'<', 0, b'?', ..] => (Encoding::Utf16Be, 0),
+        _ => match declared_encoding(bytes) {
+            Some(label) => match Encoding::from_label(label) {
+                // Declaration is readable as ASCII, so the data is not UTF-16
+                Some(Encoding::Utf16Le | Encoding::Utf16Be) => (Encoding::Utf8, 0),
+                Some(encoding) => (encoding, 0),
+                None => return Err(SVDError::UnsupportedEncoding(label.into())),
+            },
+            None => (Encoding::Utf8, 0),
+        },
+    })
+}
+
+/// Value of the `encoding` attribute of the XML declaration
+fn declared_encoding(bytes: &[u8]) -> Option<&str> {
+    let bytes = bytes.strip_prefix(b"<?xml")?;
+    let end = bytes.windows(2).position(|w| w == b"?>")?;
+    let decl = std::str::from_utf8(&bytes[..end]).ok()?;
+    let rest = &decl[decl.find("encoding")? + "encoding".len()..];
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let rest = &rest[1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+/// Decodes the content of an SVD file into a string
+pub fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, SVDError> {
+    let (encoding, bom) = detect(bytes)?;
+    let bytes = &bytes[bom..];
+    Ok(match encoding {
+        Encoding::Utf8 => Cow::Borrowed(
+            std::str::from_utf8(bytes).map_err(|_| SVDError::InvalidEncoding("UTF-8".into()))?,
+        ),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            if bytes.len() % 2 != 0 {
+                return Err(SVDError::InvalidEncoding("UTF-16".into()));
+            }
+            let units = bytes.chunks_exact(2).map(|c| {
+                if encoding == Encoding::Utf16Le {
+                    u16::from_le_bytes([c[0], c[1]])
+                } else {
+                    u16::from_be_bytes([c[0], c[1]])
+                }
+            });
+            Cow::Owned(
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .map_err(|_| SVDError::InvalidEncoding("UTF-16".into()))?,
+            )
+        }
+        Encoding::Latin1 => Cow::Owned(bytes.iter().map(|b| char::from(*b)).collect()),
+        Encoding::Windows1252 => Cow::Owned(bytes.iter().map(|b| windows_1252(*b)).collect()),
+    })
+}
+
+/// Characters of windows-1252 which differ from ISO-8859-1
+const WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+fn windows_1252(b: u8) -> char {
+    match b {
+        0x80..=0x9F => WINDOWS_1252[usize::from(b - 0x80)],
+        _ => char::from(b),
+    }
+}
+
+#[test]
+fn test_declared_encoding() {
+    assert_eq!(
+        declared_encoding(b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><device/>"),
+        Some("ISO-8859-1")
+    );
+    assert_eq!(
+        declared_encoding(b"<?xml version='1.0' encoding = 'windows-1252' ?>"),
+        Some("windows-1252")
+    );
+    assert_eq!(declared_encoding(b"<?xml version=\"1.0\"?>"), None);
+    assert_eq!(declared_encoding(b"<device/>"), None);
+}
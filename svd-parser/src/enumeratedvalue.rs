@@ -10,6 +10,7 @@ impl Parse for EnumeratedValue {
         if !tree.has_tag_name("enumeratedValue") {
             return Err(SVDError::NotExpectedTag("enumeratedValue".to_string()).at(tree.id()));
         }
+        check_children(tree, config)?;
 
         EnumeratedValue::builder()
             .name(tree.get_child_text("name")?)
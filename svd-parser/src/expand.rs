@@ -1,12 +1,14 @@
 //! Provides [expand] method to convert arrays, clusters and derived items in regular instances
 
+use crate::{Config, PeripheralFilter};
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 use std::fmt;
 use std::mem::take;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use svd_rs::{
     array::names, cluster, field, peripheral, register, Cluster, ClusterInfo, DeriveFrom, Device,
-    EnumeratedValues, Field, Peripheral, Register, RegisterCluster, RegisterProperties,
+    DimElement, EnumeratedValues, Field, Peripheral, Register, RegisterCluster, RegisterProperties,
 };
 
 /// Path to `peripheral` or `cluster` element
@@ -35,7 +37,7 @@ impl BlockPath {
         Self::parse_vec(s.split('.').collect())
     }
     pub fn parse_vec(mut v: Vec<&str>) -> (Option<Self>, &str) {
-        let name = v.pop().unwrap();
+        let name = v.pop().unwrap_or_default();
         let mut iter = v.into_iter();
         let block = if let Some(p) = iter.next() {
             let mut path = Self::new(p);
@@ -47,7 +49,7 @@ impl BlockPath {
         (block, name)
     }
     pub fn name(&self) -> &String {
-        self.path.last().unwrap()
+        self.path.last().unwrap_or(&self.peripheral)
     }
     pub fn parent(&self) -> Option<Self> {
         let mut p = self.clone();
@@ -156,18 +158,28 @@ impl FieldPath {
     pub fn parse_str(s: &str) -> (Option<RegisterPath>, &str) {
         Self::parse_vec(s.split('.').collect())
     }
-    pub fn parse_vec(mut v: Vec<&str>) -> (Option<RegisterPath>, &str) {
-        let name = v.pop().unwrap();
+    pub fn parse_vec(v: Vec<&str>) -> (Option<RegisterPath>, &str) {
+        Self::try_parse_vec(v).unwrap()
+    }
+    /// Same as [`parse_str`](Self::parse_str), but returns an error
+    /// if the register path has no peripheral
+    pub fn try_parse_str(s: &str) -> Result<(Option<RegisterPath>, &str)> {
+        Self::try_parse_vec(s.split('.').collect())
+    }
+    /// Same as [`parse_vec`](Self::parse_vec), but returns an error
+    /// if the register path has no peripheral
+    pub fn try_parse_vec(mut v: Vec<&str>) -> Result<(Option<RegisterPath>, &str)> {
+        let name = v.pop().unwrap_or_default();
         let register = if !v.is_empty() {
             let (block, rname) = RegisterPath::parse_vec(v);
-            Some(RegisterPath::new(
-                block.expect("Full qualifying field path is expected"),
-                rname,
-            ))
+            let block = block.ok_or_else(|| {
+                anyhow!("Full qualifying field path is expected, found `{rname}.{name}`")
+            })?;
+            Some(RegisterPath::new(block, rname))
         } else {
             None
         };
-        (register, name)
+        Ok((register, name))
     }
     pub fn register(&self) -> &RegisterPath {
         &self.register
@@ -346,10 +358,11 @@ fn expand_register_cluster(
     rc: RegisterCluster,
     path: &BlockPath,
     index: &Index,
+    budget: &Budget,
 ) -> Result<()> {
     match rc {
-        RegisterCluster::Cluster(c) => expand_cluster_array(regs, c, path, index)?,
-        RegisterCluster::Register(r) => expand_register_array(regs, r, path, index)?,
+        RegisterCluster::Cluster(c) => expand_cluster_array(regs, c, path, index, budget)?,
+        RegisterCluster::Register(r) => expand_register_array(regs, r, path, index, budget)?,
     }
     Ok(())
 }
@@ -359,6 +372,7 @@ fn expand_cluster_array(
     mut c: Cluster,
     path: &BlockPath,
     index: &Index,
+    budget: &Budget,
 ) -> Result<()> {
     let mut cpath = None;
     let dpath = c.derived_from.take();
@@ -368,14 +382,21 @@ fn expand_cluster_array(
     let cpath = cpath.unwrap_or_else(|| path.new_cluster(&c.name));
 
     for rc in take(&mut c.children) {
-        expand_register_cluster(&mut c.children, rc, &cpath, index)?;
+        expand_register_cluster(&mut c.children, rc, &cpath, index, budget)?;
     }
 
     match c {
-        Cluster::Single(c) => expand_cluster(regs, c),
+        Cluster::Single(c) => expand_cluster(regs, c)?,
         Cluster::Array(info, dim) => {
+            check_array_end(
+                &info.name,
+                info.address_offset.into(),
+                &dim,
+                u32::MAX.into(),
+            )?;
+            budget.copies(info.children.len(), dim.dim)?;
             for c in cluster::expand(&info, &dim) {
-                expand_cluster(regs, c);
+                expand_cluster(regs, c)?;
             }
         }
     }
@@ -387,6 +408,16 @@ pub fn derive_cluster(
     dpath: &str,
     path: &BlockPath,
     index: &Index,
+) -> Result<Option<BlockPath>> {
+    derive_cluster_chain(c, dpath, path, index, &mut Vec::new())
+}
+
+fn derive_cluster_chain(
+    c: &mut Cluster,
+    dpath: &str,
+    path: &BlockPath,
+    index: &Index,
+    seen: &mut Vec<BlockPath>,
 ) -> Result<Option<BlockPath>> {
     let (dparent, dname) = BlockPath::parse_str(dpath);
     let rdpath;
@@ -398,9 +429,13 @@ pub fn derive_cluster(
         cluster_path = path.new_cluster(dname);
         rdpath = path.clone();
     }
-    if &rdpath == path && dname == &c.name {
+    if &rdpath == path && dname == c.name {
         return Err(anyhow!("Cluster {cluster_path} is self-derived"));
     }
+    if seen.contains(&cluster_path) {
+        return Err(anyhow!("Cluster {cluster_path} has cyclic derivation"));
+    }
+    seen.push(cluster_path.clone());
     let d = index
         .clusters
         .get(&cluster_path)
@@ -412,7 +447,7 @@ pub fn derive_cluster(
     }
     *c = c.derive_from(d);
     if let Some(dpath) = d.derived_from.as_ref() {
-        cpath = derive_cluster(c, dpath, &rdpath, index)?;
+        cpath = derive_cluster_chain(c, dpath, &rdpath, index, seen)?;
     }
     Ok(cpath)
 }
@@ -422,6 +457,16 @@ pub fn derive_register(
     dpath: &str,
     path: &BlockPath,
     index: &Index,
+) -> Result<Option<RegisterPath>> {
+    derive_register_chain(r, dpath, path, index, &mut Vec::new())
+}
+
+fn derive_register_chain(
+    r: &mut Register,
+    dpath: &str,
+    path: &BlockPath,
+    index: &Index,
+    seen: &mut Vec<RegisterPath>,
 ) -> Result<Option<RegisterPath>> {
     let (dblock, dname) = RegisterPath::parse_str(dpath);
     let rdpath;
@@ -433,9 +478,13 @@ pub fn derive_register(
         reg_path = path.new_register(dname);
         rdpath = path.clone();
     }
-    if &rdpath == path && dname == &r.name {
+    if &rdpath == path && dname == r.name {
         return Err(anyhow!("Register {reg_path} is self-derived"));
     }
+    if seen.contains(&reg_path) {
+        return Err(anyhow!("Register {reg_path} has cyclic derivation"));
+    }
+    seen.push(reg_path.clone());
     let d = index
         .registers
         .get(&reg_path)
@@ -447,7 +496,7 @@ pub fn derive_register(
     }
     *r = r.derive_from(d);
     if let Some(dpath) = d.derived_from.as_ref() {
-        rpath = derive_register(r, dpath, &rdpath, index)?;
+        rpath = derive_register_chain(r, dpath, &rdpath, index, seen)?;
     }
     Ok(rpath)
 }
@@ -458,7 +507,17 @@ pub fn derive_field(
     rpath: &RegisterPath,
     index: &Index,
 ) -> Result<Option<FieldPath>> {
-    let (dregister, dname) = FieldPath::parse_str(dpath);
+    derive_field_chain(f, dpath, rpath, index, &mut Vec::new())
+}
+
+fn derive_field_chain(
+    f: &mut Field,
+    dpath: &str,
+    rpath: &RegisterPath,
+    index: &Index,
+    seen: &mut Vec<FieldPath>,
+) -> Result<Option<FieldPath>> {
+    let (dregister, dname) = FieldPath::try_parse_str(dpath)?;
     let rdpath;
     let field_path;
     if let Some(dregister) = dregister {
@@ -468,9 +527,13 @@ pub fn derive_field(
         field_path = rpath.new_field(dname);
         rdpath = rpath.clone();
     }
-    if &rdpath == rpath && dname == &f.name {
+    if &rdpath == rpath && dname == f.name {
         return Err(anyhow!("Field {field_path} is self-derived"));
     }
+    if seen.contains(&field_path) {
+        return Err(anyhow!("Field {field_path} has cyclic derivation"));
+    }
+    seen.push(field_path.clone());
     let d = index
         .fields
         .get(&field_path)
@@ -482,22 +545,38 @@ pub fn derive_field(
     }
     *f = f.derive_from(d);
     if let Some(dpath) = d.derived_from.as_ref() {
-        fpath = derive_field(f, dpath, &rdpath, index)?;
+        fpath = derive_field_chain(f, dpath, &rdpath, index, seen)?;
     }
     Ok(fpath)
 }
 
-fn expand_cluster(regs: &mut Vec<RegisterCluster>, c: ClusterInfo) {
+fn expand_cluster(regs: &mut Vec<RegisterCluster>, c: ClusterInfo) -> Result<()> {
     for rc in c.children {
         match rc {
             RegisterCluster::Cluster(_) => unreachable!(),
             RegisterCluster::Register(mut r) => {
+                r.address_offset =
+                    r.address_offset
+                        .checked_add(c.address_offset)
+                        .ok_or_else(|| {
+                            anyhow!("Address offset of register {}.{} overflows", c.name, r.name)
+                        })?;
                 r.name = format!("{}_{}", c.name, r.name);
-                r.address_offset += c.address_offset;
                 regs.push(r.into());
             }
         }
     }
+    Ok(())
+}
+
+/// Checks that offset of the last element of array fits in `max`
+fn check_array_end(name: &str, start: u64, dim: &DimElement, max: u64) -> Result<()> {
+    u64::from(dim.dim.saturating_sub(1))
+        .checked_mul(u64::from(dim.dim_increment))
+        .and_then(|offset| offset.checked_add(start))
+        .filter(|end| *end <= max)
+        .map(|_| ())
+        .ok_or_else(|| anyhow!("Array {name} does not fit in address space"))
 }
 
 fn expand_register_array(
@@ -505,6 +584,7 @@ fn expand_register_array(
     mut r: Register,
     path: &BlockPath,
     index: &Index,
+    budget: &Budget,
 ) -> Result<()> {
     let mut rpath = None;
     let dpath = r.derived_from.take();
@@ -521,9 +601,17 @@ fn expand_register_array(
 
     match r {
         Register::Single(_) => {
+            budget.take(1)?;
             regs.push(r.into());
         }
         Register::Array(info, dim) => {
+            check_array_end(
+                &info.name,
+                info.address_offset.into(),
+                &dim,
+                u32::MAX.into(),
+            )?;
+            budget.take(dim.dim as usize)?;
             regs.extend(register::expand(&info, &dim).map(|r| r.single().into()));
         }
     }
@@ -555,6 +643,7 @@ fn expand_field(
             fields.push(f);
         }
         Field::Array(info, dim) => {
+            check_array_end(&info.name, info.bit_offset().into(), &dim, u32::MAX.into())?;
             fields.extend(field::expand(&info, &dim).map(Field::Single));
         }
     }
@@ -567,12 +656,22 @@ pub fn derive_enumerated_values(
     dpath: &str,
     fpath: &FieldPath,
     index: &Index,
+) -> Result<EnumPath> {
+    derive_enumerated_values_chain(ev, dpath, fpath, index, &mut Vec::new())
+}
+
+fn derive_enumerated_values_chain(
+    ev: &mut EnumeratedValues,
+    dpath: &str,
+    fpath: &FieldPath,
+    index: &Index,
+    seen: &mut Vec<EnumPath>,
 ) -> Result<EnumPath> {
     if Some(dpath) == ev.name.as_deref() {
         return Err(anyhow!("EnumeratedValues {fpath}.{dpath} is self-derived"));
     }
     let mut v: Vec<&str> = dpath.split('.').collect();
-    let dname = v.pop().unwrap();
+    let dname = v.pop().unwrap_or_default();
     let d = if v.is_empty() {
         // Only EVNAME: Must be in one of fields in same register
         let rdpath = &fpath.register;
@@ -590,7 +689,7 @@ pub fn derive_enumerated_values(
             None
         }
     } else {
-        let fdname = v.pop().unwrap();
+        let fdname = v.pop().unwrap_or_default();
         let fdpath = if v.is_empty() {
             // FIELD.EVNAME
             fpath.register.new_field(fdname)
@@ -613,9 +712,13 @@ pub fn derive_enumerated_values(
     };
 
     if let Some((d, epath)) = d {
+        if seen.contains(&epath) {
+            return Err(anyhow!("EnumeratedValues {epath} has cyclic derivation"));
+        }
+        seen.push(epath.clone());
         *ev = ev.derive_from(d);
         if let Some(dpath) = d.derived_from.as_ref() {
-            derive_enumerated_values(ev, dpath, &epath.field, index)
+            derive_enumerated_values_chain(ev, dpath, &epath.field, index, seen)
         } else {
             Ok(epath)
         }
@@ -633,11 +736,24 @@ pub fn derive_peripheral(
     dpath: &str,
     index: &Index,
 ) -> Result<Option<BlockPath>> {
-    if dpath == &p.name {
+    derive_peripheral_chain(p, dpath, index, &mut Vec::new())
+}
+
+fn derive_peripheral_chain(
+    p: &mut Peripheral,
+    dpath: &str,
+    index: &Index,
+    seen: &mut Vec<BlockPath>,
+) -> Result<Option<BlockPath>> {
+    if dpath == p.name {
         return Err(anyhow!("Peripheral {dpath} is self-derived"));
     }
     let mut path = None;
     let derpath = BlockPath::new(dpath);
+    if seen.contains(&derpath) {
+        return Err(anyhow!("Peripheral {derpath} has cyclic derivation"));
+    }
+    seen.push(derpath.clone());
     let d = index
         .peripherals
         .get(&derpath)
@@ -647,44 +763,147 @@ pub fn derive_peripheral(
     }
     *p = p.derive_from(d);
     if let Some(dpath) = d.derived_from.as_ref() {
-        path = derive_peripheral(p, dpath, index)?;
+        path = derive_peripheral_chain(p, dpath, index, seen)?;
     }
     Ok(path)
 }
 
 /// Creates clone of device with expanded arrays of peripherals, clusters, registers and fields.
-/// Also resolves all `derivedFrom` reference pathes
+/// Also resolves all `derivedFrom` reference pathes.
+///
+/// The number of expanded registers is not limited, use [`expand_with_config`] for untrusted input
 pub fn expand(indevice: &Device) -> Result<Device> {
+    expand_filtered(indevice, &PeripheralFilter::default())
+}
+
+/// Same as [`expand`], but keeps only peripherals accepted by `filter`.
+/// Other peripherals are used only as `derivedFrom` targets.
+///
+/// Like [`expand`], doesn't enforce [`Limits`](crate::Limits)
+pub fn expand_filtered(indevice: &Device, filter: &PeripheralFilter) -> Result<Device> {
+    expand_inner(indevice, filter, &Budget::new(None))
+}
+
+/// Same as [`expand`], but fails if the number of registers exceeds
+/// [`Limits::max_expanded_registers`](crate::Limits::max_expanded_registers)
+pub fn expand_with_config(indevice: &Device, config: &Config) -> Result<Device> {
+    expand_limited(indevice, &PeripheralFilter::default(), config)
+}
+
+/// Same as [`expand_filtered`] with limits of `config`
+pub(crate) fn expand_limited(
+    indevice: &Device,
+    filter: &PeripheralFilter,
+    config: &Config,
+) -> Result<Device> {
+    expand_inner(
+        indevice,
+        filter,
+        &Budget::new(config.limits.max_expanded_registers),
+    )
+}
+
+fn expand_inner(indevice: &Device, filter: &PeripheralFilter, budget: &Budget) -> Result<Device> {
     let mut device = indevice.clone();
 
     let index = Index::create(indevice);
 
-    let peripherals = take(&mut device.peripherals);
-    for mut p in peripherals {
-        let mut path = None;
-        let dpath = p.derived_from.take();
-        if let Some(dpath) = dpath {
-            path = derive_peripheral(&mut p, &dpath, &index)?;
+    let peripherals = take(&mut device.peripherals)
+        .into_iter()
+        .filter(|p| filter.matches(&p.name, p.group_name.as_deref()))
+        .collect::<Vec<_>>();
+    #[cfg(not(feature = "parallel"))]
+    let expanded = peripherals
+        .into_iter()
+        .map(|p| expand_peripheral(p, &index, budget))
+        .collect::<Result<Vec<_>>>()?;
+    #[cfg(feature = "parallel")]
+    let expanded = {
+        use rayon::prelude::*;
+        // Each peripheral counts its registers separately and the counts are added up in order,
+        // so the reported error doesn't depend on which thread exhausts the budget first
+        peripherals
+            .into_par_iter()
+            .map(|p| {
+                let own = Budget::new(budget.limit);
+                expand_peripheral(p, &index, &own).map(|expanded| (expanded, own.used()))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|result| {
+                let (expanded, used) = result?;
+                budget.take(used)?;
+                Ok(expanded)
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    device.peripherals = expanded.into_iter().flatten().collect();
+
+    Ok(device)
+}
+
+fn expand_peripheral(mut p: Peripheral, index: &Index, budget: &Budget) -> Result<Vec<Peripheral>> {
+    let mut path = None;
+    let dpath = p.derived_from.take();
+    if let Some(dpath) = dpath {
+        path = derive_peripheral(&mut p, &dpath, index)?;
+    }
+    let path = path.unwrap_or_else(|| BlockPath::new(&p.name));
+    if let Some(regs) = p.registers.as_mut() {
+        for rc in take(regs) {
+            expand_register_cluster(regs, rc, &path, index, budget)?;
         }
-        let path = path.unwrap_or_else(|| BlockPath::new(&p.name));
-        if let Some(regs) = p.registers.as_mut() {
-            for rc in take(regs) {
-                expand_register_cluster(regs, rc, &path, &index)?;
-            }
+    }
+    Ok(match p {
+        Peripheral::Single(_) => vec![p],
+        Peripheral::Array(info, dim) => {
+            check_array_end(&info.name, info.base_address, &dim, u64::MAX)?;
+            budget.copies(info.registers().count(), dim.dim)?;
+            peripheral::expand(&info, &dim)
+                .map(Peripheral::Single)
+                .collect()
         }
-        match p {
-            Peripheral::Single(_) => {
-                device.peripherals.push(p);
-            }
-            Peripheral::Array(info, dim) => {
-                device
-                    .peripherals
-                    .extend(peripheral::expand(&info, &dim).map(Peripheral::Single));
-            }
+    })
+}
+
+/// Counter of expanded registers shared between threads
+struct Budget {
+    limit: Option<usize>,
+    used: AtomicUsize,
+}
+
+impl Budget {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
         }
     }
 
-    Ok(device)
+    /// Reserves `n` registers
+    fn take(&self, n: usize) -> Result<()> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        let used = self.used.fetch_add(n, Ordering::Relaxed).saturating_add(n);
+        if used > limit {
+            return Err(anyhow!(
+                "Number of expanded registers exceeds limit {limit}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of reserved registers
+    #[cfg(feature = "parallel")]
+    fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Reserves registers for `dim - 1` more copies of block with `registers`
+    fn copies(&self, registers: usize, dim: u32) -> Result<()> {
+        self.take(registers.saturating_mul((dim as usize).saturating_sub(1)))
+    }
 }
 
 /// Takes register `size`, `access`, `reset_value` and `reset_mask`
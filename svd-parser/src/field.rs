@@ -23,31 +23,31 @@ impl Parse for FieldInfo {
         if !tree.has_tag_name("field") {
             return Err(SVDError::NotExpectedTag("field".to_string()).at(tree.id()));
         }
+        check_children(tree, config)?;
 
         let bit_range = BitRange::parse(tree, config)?;
         FieldInfo::builder()
             .name(tree.get_child_text("name")?)
             .description(tree.get_child_text_opt("description")?)
             .bit_range(bit_range)
-            .access(optional::<Access>("access", tree, config)?)
-            .modified_write_values(optional::<ModifiedWriteValues>(
+            .access(optional_lenient::<Access>("access", tree, config)?)
+            .modified_write_values(optional_lenient::<ModifiedWriteValues>(
                 "modifiedWriteValues",
                 tree,
                 config,
             )?)
             .write_constraint(if !config.ignore_enums {
-                optional::<WriteConstraint>("writeConstraint", tree, config)?
+                optional_lenient::<WriteConstraint>("writeConstraint", tree, config)?
             } else {
                 None
             })
-            .read_action(optional::<ReadAction>("readAction", tree, config)?)
+            .read_action(optional_lenient::<ReadAction>("readAction", tree, config)?)
             .enumerated_values(if !config.ignore_enums {
-                let values: Result<Vec<_>, _> = tree
-                    .children()
-                    .filter(|t| t.is_element() && t.has_tag_name("enumeratedValues"))
-                    .map(|t| EnumeratedValues::parse(&t, config))
-                    .collect();
-                values?
+                parse_children::<EnumeratedValues>(
+                    tree.children()
+                        .filter(|t| t.is_element() && t.has_tag_name("enumeratedValues")),
+                    config,
+                )?
             } else {
                 Vec::new()
             })
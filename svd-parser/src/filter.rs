@@ -0,0 +1,121 @@
+//! Selection of peripherals to parse.
+//!
+//! Names are matched against peripheral names as written in the file
+//! (`UART%s` for peripheral arrays). Patterns may contain `*` and `?` wildcards.
+
+use roxmltree::Node;
+
+use super::diagnostic::get_name;
+use super::elementext::ElementExt;
+use crate::svd::glob;
+
+/// Allow/deny list of peripherals
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeripheralFilter {
+    /// Name patterns of peripherals to parse
+    pub include: Vec<String>,
+    /// Group names of peripherals to parse
+    pub groups: Vec<String>,
+    /// Name patterns of peripherals to skip
+    pub exclude: Vec<String>,
+}
+
+impl PeripheralFilter {
+    /// Create filter which accepts all peripherals
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse peripherals with names matching `pattern`
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Parse peripherals with `groupName`
+    pub fn group(mut self, group_name: impl Into<String>) -> Self {
+        self.groups.push(group_name.into());
+        self
+    }
+
+    /// Skip peripherals with names matching `pattern`
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Returns `true` if filter accepts all peripherals
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.groups.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Check if peripheral with `name` and `group_name` should be parsed
+    pub fn matches(&self, name: &str, group_name: Option<&str>) -> bool {
+        let included = (self.include.is_empty() && self.groups.is_empty())
+            || self.include.iter().any(|p| glob(p, name))
+            || group_name.is_some_and(|g| self.groups.iter().any(|p| p == g));
+        included && !self.exclude.iter().any(|p| glob(p, name))
+    }
+
+    pub(crate) fn matches_node(&self, node: &Node) -> bool {
+        let name = get_name(node).unwrap_or_default();
+        let group = node.get_child_text_opt("groupName").ok().flatten();
+        self.matches(name, group.as_deref())
+    }
+
+    /// Selects peripheral nodes accepted by filter.
+    /// If `derived` is `true` also selects peripherals which they derive from
+    pub(crate) fn select<'a, 'input>(
+        &self,
+        peripherals: &[Node<'a, 'input>],
+        derived: bool,
+    ) -> Vec<Node<'a, 'input>> {
+        if self.is_empty() {
+            return peripherals.to_vec();
+        }
+        let mut selected = peripherals
+            .iter()
+            .map(|p| self.matches_node(p))
+            .collect::<Vec<_>>();
+        if derived {
+            let mut queue = (0..peripherals.len())
+                .filter(|i| selected[*i])
+                .collect::<Vec<_>>();
+            while let Some(i) = queue.pop() {
+                for target in derived_peripherals(&peripherals[i]) {
+                    for (j, p) in peripherals.iter().enumerate() {
+                        if !selected[j] && is_derivation_target(p, target) {
+                            selected[j] = true;
+                            queue.push(j);
+                        }
+                    }
+                }
+            }
+        }
+        peripherals
+            .iter()
+            .zip(selected)
+            .filter_map(|(p, s)| s.then_some(*p))
+            .collect()
+    }
+}
+
+/// Names of peripherals referenced in `derivedFrom` of the peripheral or its children
+fn derived_peripherals<'a>(peripheral: &Node<'a, '_>) -> impl Iterator<Item = &'a str> {
+    let own = peripheral.attribute("derivedFrom");
+    let nested = peripheral
+        .descendants()
+        .skip(1)
+        .filter_map(|n| n.attribute("derivedFrom"))
+        .filter_map(|path| path.split_once('.').map(|(first, _)| first));
+    own.into_iter().chain(nested)
+}
+
+fn is_derivation_target(peripheral: &Node, target: &str) -> bool {
+    let name = get_name(peripheral).unwrap_or_default();
+    if name.contains("%s") {
+        glob(&name.replace("[%s]", "*").replace("%s", "*"), target)
+    } else {
+        name == target
+    }
+}
@@ -0,0 +1,48 @@
+//! Parsing of standalone SVD elements.
+//!
+//! Snippets like `<peripheral>...</peripheral>` are parsed without the enclosing `<device>`.
+//! Errors have the same context as in [`parse`](crate::parse), with positions relative
+//! to the snippet. Arrays are not expanded and `derivedFrom` is not resolved.
+
+use super::{error_context, trim_utf8_bom, Config, Parse, SVDError, SVDErrorAt};
+use crate::svd::{Cluster, Field, Peripheral, Register};
+
+/// Parses the root element of `xml`, which must be `<tag>`
+pub fn parse_fragment<T>(xml: &str, tag: &str, config: &Config) -> anyhow::Result<T>
+where
+    T: Parse<Object = T, Error = SVDErrorAt, Config = Config>,
+{
+    let tree = config.limits.parse_document(trim_utf8_bom(xml), 0)?;
+    config
+        .limits
+        .check_tree(&tree.root(), 0, 0)
+        .map_err(|e| error_context(&tree, e))?;
+    let root = tree.root_element();
+    if !root.has_tag_name(tag) {
+        return Err(error_context(
+            &tree,
+            SVDError::NotExpectedTag(tag.into()).at(root.id()),
+        ));
+    }
+    T::parse(&root, config).map_err(|e| error_context(&tree, e))
+}
+
+/// Parses standalone `<peripheral>` element
+pub fn parse_peripheral(xml: &str) -> anyhow::Result<Peripheral> {
+    parse_fragment(xml, "peripheral", &Config::default())
+}
+
+/// Parses standalone `<cluster>` element
+pub fn parse_cluster(xml: &str) -> anyhow::Result<Cluster> {
+    parse_fragment(xml, "cluster", &Config::default())
+}
+
+/// Parses standalone `<register>` element
+pub fn parse_register(xml: &str) -> anyhow::Result<Register> {
+    parse_fragment(xml, "register", &Config::default())
+}
+
+/// Parses standalone `<field>` element
+pub fn parse_field(xml: &str) -> anyhow::Result<Field> {
+    parse_fragment(xml, "field", &Config::default())
+}
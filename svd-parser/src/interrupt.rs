@@ -10,6 +10,7 @@ impl Parse for Interrupt {
         if !tree.has_tag_name("interrupt") {
             return Err(SVDError::NotExpectedTag("interrupt".to_string()).at(tree.id()));
         }
+        check_children(tree, config)?;
         let name = tree.get_child_text("name")?;
 
         Interrupt::builder()
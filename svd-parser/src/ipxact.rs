@@ -0,0 +1,541 @@
+//! Import of IP-XACT (IEEE 1685) components.
+//!
+//! Every `addressBlock` of the component's memory maps becomes a peripheral,
+//! `registerFile`s become clusters. Elements are matched by local name, so both
+//! IP-XACT 1685-2014 (`ipxact:`) and 1685-2009 (`spirit:`) documents are accepted.
+//!
+//! The reset value of a register is collected from resets of its fields.
+//! A register with the only field named as the register and covering all its bits
+//! is read as a register without fields.
+//!
+//! Elements without CMSIS-SVD equivalent are skipped and reported as
+//! [`DiagnosticKind::Ignored`] diagnostics.
+
+use roxmltree::Node;
+
+use super::{
+    bitrange::InvalidBitRange, diagnostic, error_context, trim_utf8_bom, Config, DiagnosticKind,
+    ElementExt, ParseDiagnostic, SVDError, SVDErrorAt,
+};
+use crate::svd::{
+    Access, AddressBlock, AddressBlockUsage, BitRange, Cluster, ClusterInfo, Device, DimElement,
+    EnumeratedValue, EnumeratedValues, FieldInfo, ModifiedWriteValues, Peripheral, PeripheralInfo,
+    ReadAction, Register, RegisterCluster, RegisterInfo, RegisterProperties, Usage,
+    WriteConstraint, WriteConstraintRange,
+};
+
+const COMPONENT: &[&str] = &[
+    "vendor",
+    "library",
+    "name",
+    "version",
+    "displayName",
+    "description",
+    "memoryMaps",
+];
+const MEMORY_MAP: &[&str] = &[
+    "name",
+    "displayName",
+    "description",
+    "addressBlock",
+    "addressUnitBits",
+];
+const ADDRESS_BLOCK: &[&str] = &[
+    "name",
+    "displayName",
+    "description",
+    "baseAddress",
+    "range",
+    "width",
+    "usage",
+    "access",
+    "register",
+    "registerFile",
+];
+const REGISTER_FILE: &[&str] = &[
+    "name",
+    "description",
+    "dim",
+    "addressOffset",
+    "range",
+    "register",
+    "registerFile",
+];
+const REGISTER: &[&str] = &[
+    "name",
+    "displayName",
+    "description",
+    "dim",
+    "addressOffset",
+    "size",
+    "access",
+    "reset",
+    "field",
+];
+const FIELD: &[&str] = &[
+    "name",
+    "description",
+    "bitOffset",
+    "bitWidth",
+    "resets",
+    "access",
+    "enumeratedValues",
+    "modifiedWriteValue",
+    "writeValueConstraint",
+    "readAction",
+];
+const ENUMERATED_VALUE: &[&str] = &["name", "description", "value"];
+
+/// Parses IP-XACT component into a device.
+/// Also returns the list of elements which CMSIS-SVD can't express
+pub fn parse_ipxact(xml: &str, config: &Config) -> anyhow::Result<(Device, Vec<ParseDiagnostic>)> {
+    let xml = trim_utf8_bom(xml);
+    let tree = config.limits.parse_document(xml, 0)?;
+    let root = tree.root();
+    config
+        .limits
+        .check_tree(&root, 0, 0)
+        .map_err(|e| error_context(&tree, e))?;
+    let component = root
+        .get_child("component")
+        .ok_or_else(|| SVDError::MissingTag("component".to_string()).at(root.id()))?;
+    let (device, reports) = diagnostic::collect(|| parse_component(&component, config));
+    let device = device.map_err(|e| error_context(&tree, e))?;
+    Ok((device, diagnostic::resolve(&tree, reports)))
+}
+
+fn parse_component(tree: &Node, config: &Config) -> Result<Device, SVDErrorAt> {
+    ignore_unknown(tree, COMPONENT);
+    let mut peripherals = Vec::new();
+    let mut width = None;
+    let mut address_unit_bits = None;
+    for map in children(tree, "memoryMaps").flat_map(|maps| {
+        ignore_unknown(&maps, &["memoryMap"]);
+        children(&maps, "memoryMap").collect::<Vec<_>>()
+    }) {
+        ignore_unknown(&map, MEMORY_MAP);
+        address_unit_bits = address_unit_bits.or(number_opt(&map, "addressUnitBits")?);
+        for block in children(&map, "addressBlock") {
+            width = width.or(number_opt(&block, "width")?);
+            peripherals.push(parse_address_block(&block, config)?);
+        }
+    }
+    let mut builder = Device::builder()
+        .vendor(tree.get_child_text_opt("vendor")?)
+        .series(tree.get_child_text_opt("library")?)
+        .name(tree.get_child_text("name")?)
+        .description(tree.get_child_text_opt("description")?.unwrap_or_default())
+        .peripherals(peripherals);
+    if let Some(version) = tree.get_child_text_opt("version")? {
+        builder = builder.version(version);
+    }
+    if let Some(aub) = address_unit_bits {
+        builder = builder.address_unit_bits(to_u32(aub, tree)?);
+    }
+    if let Some(width) = width {
+        builder = builder.width(to_u32(width, tree)?);
+    }
+    builder
+        .build(config.validate_level)
+        .map_err(|e| SVDError::from(e).at(tree.id()))
+}
+
+fn parse_address_block(tree: &Node, config: &Config) -> Result<Peripheral, SVDErrorAt> {
+    ignore_unknown(tree, ADDRESS_BLOCK);
+    let usage = match tree.get_child_text_opt("usage")?.as_deref() {
+        None | Some("register") => AddressBlockUsage::Registers,
+        Some("memory") => AddressBlockUsage::Buffer,
+        Some("reserved") => AddressBlockUsage::Reserved,
+        Some(_) => {
+            return Err(SVDError::UnknownAddressBlockUsageVariant.at(tree.id()));
+        }
+    };
+    let mut address_block = None;
+    if let Some(range) = number_opt(tree, "range")? {
+        let block = AddressBlock::builder()
+            .offset(0)
+            .size(to_u32(range, tree)?)
+            .usage(usage)
+            .build(config.validate_level)
+            .map_err(|e| SVDError::from(e).at(tree.id()))?;
+        address_block = Some(vec![block]);
+    }
+    let properties = RegisterProperties::new().access(access(tree)?);
+    let registers = parse_registers(tree, config)?;
+    let name = tree.get_child_text("name")?;
+    let base_address = number(tree, "baseAddress")?;
+    let range = address_block.as_ref().map_or(0, |blocks| blocks[0].size);
+    if base_address.checked_add(range.into()).is_none() {
+        return Err(overflow(&name, tree));
+    }
+    PeripheralInfo::builder()
+        .name(name)
+        .display_name(tree.get_child_text_opt("displayName")?)
+        .description(tree.get_child_text_opt("description")?)
+        .base_address(base_address)
+        .default_register_properties(properties)
+        .address_block(address_block)
+        .registers((!registers.is_empty()).then_some(registers))
+        .build(config.validate_level)
+        .map(PeripheralInfo::single)
+        .map_err(|e| SVDError::from(e).at(tree.id()))
+}
+
+fn parse_registers(tree: &Node, config: &Config) -> Result<Vec<RegisterCluster>, SVDErrorAt> {
+    let mut registers = Vec::new();
+    for child in tree.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "register" => registers.push(parse_register(&child, config)?.into()),
+            "registerFile" => registers.push(parse_register_file(&child, config)?.into()),
+            _ => {}
+        }
+    }
+    Ok(registers)
+}
+
+fn parse_register_file(tree: &Node, config: &Config) -> Result<Cluster, SVDErrorAt> {
+    ignore_unknown(tree, REGISTER_FILE);
+    let dim = parse_dim(tree, config)?;
+    let name = tree.get_child_text("name")?;
+    let address_offset = address_offset(tree, &name, &dim)?;
+    let info = ClusterInfo::builder()
+        .name(array_name(name, &dim))
+        .description(tree.get_child_text_opt("description")?)
+        .address_offset(address_offset)
+        .children(parse_registers(tree, config)?)
+        .build(config.validate_level)
+        .map_err(|e| SVDError::from(e).at(tree.id()))?;
+    Ok(match dim {
+        Some(dim) => info.array(dim),
+        None => info.single(),
+    })
+}
+
+fn parse_register(tree: &Node, config: &Config) -> Result<Register, SVDErrorAt> {
+    ignore_unknown(tree, REGISTER);
+    let dim = parse_dim(tree, config)?;
+    let name = tree.get_child_text("name")?;
+    let size = number_opt(tree, "size")?
+        .map(|size| to_u32(size, tree))
+        .transpose()?;
+    if size == Some(0) {
+        return Err(SVDError::InvalidIpxact(format!("size of `{name}` is 0")).at(tree.id()));
+    }
+    let address_offset = address_offset(tree, &name, &dim)?;
+
+    // Reset value is combined in 64 bits
+    let bits = size.unwrap_or(64).min(64);
+    let mut fields = Vec::new();
+    let mut reset_value = None;
+    let mut reset_mask = None;
+    for node in children(tree, "field") {
+        let (field, reset) = parse_field(&node, config)?;
+        let end = field.bit_range.offset.checked_add(field.bit_range.width);
+        if end.is_none_or(|end| end > bits) {
+            return Err(SVDError::InvalidBitRange(InvalidBitRange::Size).at(node.id()));
+        }
+        if let Some((value, mask)) = reset {
+            let offset = field.bit_range.offset;
+            reset_value = Some(reset_value.unwrap_or(0) | (value << offset));
+            // Reset without mask applies to the whole field
+            let mask = mask.map_or_else(|| field.bitmask(), |mask| mask << offset);
+            reset_mask = Some(reset_mask.unwrap_or(0) | mask);
+        }
+        fields.push(field);
+    }
+    // IP-XACT 1685-2009 resets are on registers
+    if let Some(reset) = tree.get_child("reset") {
+        reset_value = number_opt(&reset, "value")?;
+        reset_mask = number_opt(&reset, "mask")?;
+    }
+
+    let mut builder = RegisterInfo::builder()
+        .name(array_name(name.clone(), &dim))
+        .display_name(tree.get_child_text_opt("displayName")?)
+        .description(tree.get_child_text_opt("description")?)
+        .address_offset(address_offset)
+        .size(size)
+        .access(access(tree)?)
+        .reset_value(reset_value)
+        .reset_mask(reset_mask);
+    match fields.as_slice() {
+        // Field which stands for the whole register
+        [f] if f.name == name
+            && f.bit_range.offset == 0
+            && f.bit_range.width == size.unwrap_or(32)
+            && f.enumerated_values.is_empty() =>
+        {
+            builder = builder
+                .modified_write_values(f.modified_write_values)
+                .write_constraint(f.write_constraint)
+                .read_action(f.read_action);
+        }
+        _ => {
+            let fields = fields.into_iter().map(FieldInfo::single).collect();
+            builder = builder.fields(Some(fields));
+        }
+    }
+    let info = builder
+        .build(config.validate_level)
+        .map_err(|e| SVDError::from(e).at(tree.id()))?;
+    Ok(match dim {
+        Some(dim) => info.array(dim),
+        None => info.single(),
+    })
+}
+
+type Reset = Option<(u64, Option<u64>)>;
+
+fn parse_field(tree: &Node, config: &Config) -> Result<(FieldInfo, Reset), SVDErrorAt> {
+    ignore_unknown(tree, FIELD);
+    let reset = match children(tree, "resets")
+        .flat_map(|resets| children(&resets, "reset").collect::<Vec<_>>())
+        .next()
+    {
+        Some(reset) => Some((number(&reset, "value")?, number_opt(&reset, "mask")?)),
+        None => None,
+    };
+    let mwv =
+        match tree.get_child("modifiedWriteValue") {
+            Some(node) => {
+                let text = node.get_text()?;
+                Some(ModifiedWriteValues::parse_str(text).ok_or_else(|| {
+                    SVDError::InvalidModifiedWriteValues(text.into()).at(node.id())
+                })?)
+            }
+            None => None,
+        };
+    let read_action = match tree.get_child("readAction") {
+        Some(node) => {
+            let text = node.get_text()?;
+            Some(
+                ReadAction::parse_str(text)
+                    .ok_or_else(|| SVDError::InvalidReadAction(text.into()).at(node.id()))?,
+            )
+        }
+        None => None,
+    };
+    let mut enumerated_values = Vec::new();
+    if !config.ignore_enums {
+        for evs in children(tree, "enumeratedValues") {
+            parse_enumerated_values(&evs, config, &mut enumerated_values)?;
+        }
+    }
+    let field = FieldInfo::builder()
+        .name(tree.get_child_text("name")?)
+        .description(tree.get_child_text_opt("description")?)
+        .bit_range(BitRange::from_offset_width(
+            to_u32(number(tree, "bitOffset")?, tree)?,
+            to_u32(number(tree, "bitWidth")?, tree)?,
+        ))
+        .access(access(tree)?)
+        .modified_write_values(mwv)
+        .write_constraint(match tree.get_child("writeValueConstraint") {
+            Some(node) if !config.ignore_enums => Some(parse_write_constraint(&node)?),
+            _ => None,
+        })
+        .read_action(read_action)
+        .enumerated_values(enumerated_values)
+        .build(config.validate_level)
+        .map_err(|e| SVDError::from(e).at(tree.id()))?;
+    Ok((field, reset))
+}
+
+/// Adds values to the set with the same usage
+fn parse_enumerated_values(
+    tree: &Node,
+    config: &Config,
+    sets: &mut Vec<EnumeratedValues>,
+) -> Result<(), SVDErrorAt> {
+    ignore_unknown(tree, &["enumeratedValue"]);
+    for node in children(tree, "enumeratedValue") {
+        ignore_unknown(&node, ENUMERATED_VALUE);
+        let usage = match node.attribute("usage") {
+            Some(usage) => Some(
+                Usage::parse_str(usage)
+                    .ok_or_else(|| SVDError::UnknownUsageVariant.at(node.id()))?,
+            ),
+            None => None,
+        };
+        let value = EnumeratedValue::builder()
+            .name(node.get_child_text("name")?)
+            .description(node.get_child_text_opt("description")?)
+            .value(Some(number(&node, "value")?))
+            .build(config.validate_level)
+            .map_err(|e| SVDError::from(e).at(node.id()))?;
+        match sets.iter_mut().find(|set| set.usage == usage) {
+            Some(set) => set.values.push(value),
+            None => sets.push(
+                EnumeratedValues::builder()
+                    .usage(usage)
+                    .values(vec![value])
+                    .build(config.validate_level)
+                    .map_err(|e| SVDError::from(e).at(node.id()))?,
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn parse_write_constraint(tree: &Node) -> Result<WriteConstraint, SVDErrorAt> {
+    let bool_child = |name| -> Result<Option<bool>, SVDErrorAt> {
+        match tree.get_child(name) {
+            Some(_) => Ok(Some(tree.get_child_bool(name)?)),
+            None => Ok(None),
+        }
+    };
+    if let Some(b) = bool_child("writeAsRead")? {
+        return Ok(WriteConstraint::WriteAsRead(b));
+    }
+    if let Some(b) = bool_child("useEnumeratedValues")? {
+        return Ok(WriteConstraint::UseEnumeratedValues(b));
+    }
+    Ok(WriteConstraint::Range(WriteConstraintRange {
+        min: number(tree, "minimum")?,
+        max: number(tree, "maximum")?,
+    }))
+}
+
+/// Array given by `dim`. Registers of array follow each other,
+/// register files are placed at their `range`
+fn parse_dim(tree: &Node, config: &Config) -> Result<Option<DimElement>, SVDErrorAt> {
+    let dims = children(tree, "dim").collect::<Vec<_>>();
+    let [dim] = dims.as_slice() else {
+        return match dims.first() {
+            None => Ok(None),
+            Some(dim) => Err(SVDError::NoSvdEquivalent("dim".into()).at(dim.id())),
+        };
+    };
+    let count = number_at(dim)?;
+    config.limits.check_dim(count).map_err(|e| e.at(dim.id()))?;
+    let count = to_u32(count, dim)?;
+    let increment = if tree.tag_name().name() == "registerFile" {
+        number(tree, "range")?
+    } else {
+        let address_unit_bits = match tree.ancestors().find(|n| n.has_tag_name("memoryMap")) {
+            Some(map) => number_opt(&map, "addressUnitBits")?,
+            None => None,
+        };
+        let bits = number_opt(tree, "size")?.unwrap_or(32);
+        bits.div_ceil(address_unit_bits.unwrap_or(8).max(1))
+    };
+    let increment = to_u32(increment, tree)?;
+    DimElement::builder()
+        .dim(count)
+        .dim_increment(increment)
+        .build(config.validate_level)
+        .map(Some)
+        .map_err(|e| SVDError::from(e).at(tree.id()))
+}
+
+/// Address offset of register or register file. Elements of array follow each other,
+/// so the array must end in 32-bit address range
+fn address_offset(tree: &Node, name: &str, dim: &Option<DimElement>) -> Result<u32, SVDErrorAt> {
+    let offset = to_u32(number(tree, "addressOffset")?, tree)?;
+    if let Some(dim) = dim {
+        let end = u64::from(dim.dim_increment) * u64::from(dim.dim) + u64::from(offset);
+        if end > 1 << 32 {
+            return Err(overflow(name, tree));
+        }
+    }
+    Ok(offset)
+}
+
+fn overflow(name: &str, tree: &Node) -> SVDErrorAt {
+    SVDError::InvalidIpxact(format!("address of `{name}` overflows")).at(tree.id())
+}
+
+fn array_name(name: String, dim: &Option<DimElement>) -> String {
+    match dim {
+        Some(_) => format!("{name}[%s]"),
+        None => name,
+    }
+}
+
+fn access(tree: &Node) -> Result<Option<Access>, SVDErrorAt> {
+    match tree.get_child("access") {
+        Some(node) => {
+            let text = node.get_text()?;
+            Access::parse_str(text)
+                .map(Some)
+                .ok_or_else(|| SVDError::UnknownAccessType(text.into()).at(node.id()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Reports children which are not in `known`
+fn ignore_unknown(tree: &Node, known: &[&str]) {
+    for child in tree.children().filter(Node::is_element) {
+        let name = child.tag_name().name();
+        if !known.contains(&name) {
+            diagnostic::report(
+                diagnostic::mark(),
+                DiagnosticKind::Ignored,
+                child.id(),
+                SVDError::NoSvdEquivalent(name.into()).at(child.id()),
+            );
+        }
+    }
+}
+
+fn children<'a, 'input>(
+    tree: &Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    tree.children().filter(move |c| c.has_tag_name(name))
+}
+
+fn number(tree: &Node, name: &str) -> Result<u64, SVDErrorAt> {
+    number_opt(tree, name)?.ok_or_else(|| SVDError::MissingTag(name.into()).at(tree.id()))
+}
+
+fn number_opt(tree: &Node, name: &str) -> Result<Option<u64>, SVDErrorAt> {
+    tree.get_child(name)
+        .map(|node| number_at(&node))
+        .transpose()
+}
+
+fn number_at(node: &Node) -> Result<u64, SVDErrorAt> {
+    let text = node.get_text()?;
+    parse_number(text).ok_or_else(|| SVDError::InvalidExpression(text.into()).at(node.id()))
+}
+
+fn to_u32(value: u64, node: &Node) -> Result<u32, SVDErrorAt> {
+    u32::try_from(value).map_err(|_| SVDError::InvalidExpression(value.to_string()).at(node.id()))
+}
+
+/// Parses number literal in SystemVerilog (`'h1F`, `8'b101`) or C (`0x1F`) notation
+fn parse_number(s: &str) -> Option<u64> {
+    let s = s.trim().replace('_', "");
+    if let Some((width, literal)) = s.split_once('\'') {
+        if !width.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let literal = literal.strip_prefix(['s', 'S']).unwrap_or(literal);
+        let mut chars = literal.chars();
+        let radix = match chars.next()?.to_ascii_lowercase() {
+            'h' => 16,
+            'd' => 10,
+            'o' => 8,
+            'b' => 2,
+            _ => return None,
+        };
+        return u64::from_str_radix(chars.as_str(), radix).ok();
+    }
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(parse_number("'h1F"), Some(0x1f));
+    assert_eq!(parse_number("32'hFFFF_0000"), Some(0xffff_0000));
+    assert_eq!(parse_number("4'b1010"), Some(10));
+    assert_eq!(parse_number("'sd12"), Some(12));
+    assert_eq!(parse_number("0x40"), Some(0x40));
+    assert_eq!(parse_number("17"), Some(17));
+    assert_eq!(parse_number("WIDTH-1"), None);
+}
@@ -28,13 +28,14 @@ pub use svd_rs as svd;
 
 pub use anyhow::Context;
 use roxmltree::{Document, Node, NodeId};
+use std::sync::Arc;
 // ElementExt extends XML elements with useful methods
 pub mod elementext;
 use crate::elementext::ElementExt;
 // Types defines simple types and parse/encode implementations
 pub mod types;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 /// Advanced parser options
 pub struct Config {
@@ -51,6 +52,24 @@ pub struct Config {
     pub expand_properties: bool,
     /// Skip parsing and emitting `enumeratedValues` and `writeConstraint` in `Field`
     pub ignore_enums: bool,
+    /// Drop malformed elements instead of failing
+    ///
+    /// Use [`parse_with_diagnostics`] to get the list of recovered errors
+    pub lenient: bool,
+    /// What to do with elements not known by the parser
+    pub unknown_elements: UnknownElements,
+    /// Remember positions of elements in the source XML
+    ///
+    /// Use [`parse_full`] to get them
+    pub source_spans: bool,
+    /// Remember the original form of values, order of children and comments
+    ///
+    /// Use [`parse_full`] to get them
+    pub source_format: bool,
+    /// Parse only selected peripherals
+    pub peripheral_filter: Option<Arc<PeripheralFilter>>,
+    /// Resource limits for untrusted input
+    pub limits: Limits,
 }
 
 impl Config {
@@ -80,6 +99,46 @@ impl Config {
         self.ignore_enums = val;
         self
     }
+
+    /// Skip malformed peripherals, registers, clusters, fields and enumerated values
+    /// and leave unparsable optional values empty instead of failing
+    pub fn lenient(mut self, val: bool) -> Self {
+        self.lenient = val;
+        self
+    }
+
+    /// Ignore, warn about or reject unknown (for example misspelled) elements
+    pub fn unknown_elements(mut self, val: UnknownElements) -> Self {
+        self.unknown_elements = val;
+        self
+    }
+
+    /// Collect [`SourceSpans`] of parsed elements
+    pub fn source_spans(mut self, val: bool) -> Self {
+        self.source_spans = val;
+        self
+    }
+
+    /// Collect [`SourceFormat`] for lossless encoding
+    pub fn source_format(mut self, val: bool) -> Self {
+        self.source_format = val;
+        self
+    }
+
+    /// Parse only peripherals accepted by `filter`.
+    ///
+    /// With `expand` peripherals they derive from are also parsed,
+    /// but removed after expansion
+    pub fn peripheral_filter(mut self, filter: PeripheralFilter) -> Self {
+        self.peripheral_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Fail on documents exceeding `limits`
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -132,6 +191,99 @@ where
     }
 }
 
+/// Parser configuration which is taken from [`Config`]
+pub(crate) trait FromConfig {
+    fn from_config(config: &Config) -> &Self;
+}
+
+impl FromConfig for Config {
+    fn from_config(config: &Config) -> &Self {
+        config
+    }
+}
+
+impl FromConfig for () {
+    fn from_config(_: &Config) -> &Self {
+        &()
+    }
+}
+
+/// Same as [`optional`], but in lenient mode leaves value empty if it can't be parsed
+pub(crate) fn optional_lenient<T>(
+    n: &str,
+    e: &Node,
+    config: &Config,
+) -> Result<Option<T::Object>, SVDErrorAt>
+where
+    T: Parse<Error = SVDErrorAt>,
+    T::Config: FromConfig,
+{
+    let mark = diagnostic::mark();
+    match optional::<T>(n, e, T::Config::from_config(config)) {
+        Err(err) if config.lenient => {
+            let id = e.get_child(n).map(|c| c.id()).unwrap_or(err.id);
+            diagnostic::report(mark, DiagnosticKind::Defaulted, id, err);
+            Ok(None)
+        }
+        res => res,
+    }
+}
+
+/// Parses each of `nodes`. In lenient mode elements which can't be parsed are skipped
+pub(crate) fn parse_children<'a, 'input: 'a, T>(
+    nodes: impl Iterator<Item = Node<'a, 'input>>,
+    config: &Config,
+) -> Result<Vec<T::Object>, SVDErrorAt>
+where
+    T: Parse<Error = SVDErrorAt, Config = Config>,
+{
+    let mut items = Vec::new();
+    for node in nodes {
+        let mark = diagnostic::mark();
+        match T::parse(&node, config) {
+            Ok(item) => items.push(item),
+            Err(err) if config.lenient => {
+                diagnostic::report(mark, DiagnosticKind::Dropped, node.id(), err)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(items)
+}
+
+/// Same as [`parse_children`], but parses elements on rayon thread pool.
+/// Result and reported recoveries are the same as in sequential version
+#[cfg(feature = "parallel")]
+pub(crate) fn parse_children_parallel<T>(
+    nodes: &[Node],
+    config: &Config,
+) -> Result<Vec<T::Object>, SVDErrorAt>
+where
+    T: Parse<Error = SVDErrorAt, Config = Config>,
+    T::Object: Send,
+{
+    use rayon::prelude::*;
+    // Each thread collects own recoveries
+    let results = nodes
+        .par_iter()
+        .map(|node| diagnostic::collect(|| T::parse(node, config)))
+        .collect::<Vec<_>>();
+    let mut items = Vec::with_capacity(results.len());
+    for (node, (res, reports)) in nodes.iter().zip(results) {
+        match res {
+            Ok(item) => {
+                diagnostic::extend(reports);
+                items.push(item);
+            }
+            Err(err) if config.lenient => {
+                diagnostic::report(diagnostic::mark(), DiagnosticKind::Dropped, node.id(), err)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(items)
+}
+
 use crate::svd::Device;
 /// Parses the contents of an SVD (XML) string
 pub fn parse(xml: &str) -> anyhow::Result<Device> {
@@ -139,55 +291,93 @@ pub fn parse(xml: &str) -> anyhow::Result<Device> {
 }
 /// Parses the contents of an SVD (XML) string
 pub fn parse_with_config(xml: &str, config: &Config) -> anyhow::Result<Device> {
-    fn get_name<'a>(node: &'a Node) -> Option<&'a str> {
-        node.children()
-            .find(|t| t.has_tag_name("name"))
-            .and_then(|t| t.text())
-    }
+    parse_full(xml, config).map(|parsed| parsed.device)
+}
+
+/// Parses the contents of an SVD file given as raw bytes.
+/// The encoding is taken from byte order mark or XML declaration
+pub fn parse_bytes(bytes: &[u8]) -> anyhow::Result<Device> {
+    parse_bytes_with_config(bytes, &Config::default())
+}
+
+/// Parses the contents of an SVD file given as raw bytes.
+/// The encoding is taken from byte order mark or XML declaration
+pub fn parse_bytes_with_config(bytes: &[u8], config: &Config) -> anyhow::Result<Device> {
+    parse_with_config(&encoding::decode(bytes)?, config)
+}
 
+/// Reads and parses an SVD file.
+/// The encoding is taken from byte order mark or XML declaration
+pub fn parse_reader(reader: impl std::io::Read) -> anyhow::Result<Device> {
+    parse_reader_with_config(reader, &Config::default())
+}
+
+/// Reads and parses an SVD file.
+/// The encoding is taken from byte order mark or XML declaration
+pub fn parse_reader_with_config(
+    mut reader: impl std::io::Read,
+    config: &Config,
+) -> anyhow::Result<Device> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    parse_bytes_with_config(&bytes, config)
+}
+
+/// Parses the contents of an SVD (XML) string.
+/// Also returns the list of elements dropped or defaulted when [`Config::lenient`] is set
+pub fn parse_with_diagnostics(
+    xml: &str,
+    config: &Config,
+) -> anyhow::Result<(Device, Vec<ParseDiagnostic>)> {
+    parse_full(xml, config).map(|parsed| (parsed.device, parsed.diagnostics))
+}
+
+/// Parsed device with side information
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ParsedDevice {
+    /// Parsed device
+    pub device: Device,
+    /// Elements dropped, defaulted or ignored by the parser
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Positions of elements, if [`Config::source_spans`] is set
+    pub spans: Option<SourceSpans>,
+    /// Original form of elements, if [`Config::source_format`] is set
+    pub format: Option<SourceFormat>,
+}
+
+/// Parses the contents of an SVD (XML) string.
+/// Also returns diagnostics and positions of elements in `xml`
+pub fn parse_full(xml: &str, config: &Config) -> anyhow::Result<ParsedDevice> {
+    let no_filter = PeripheralFilter::default();
+    let filter = config.peripheral_filter.as_deref().unwrap_or(&no_filter);
+    let bom = xml.len() - trim_utf8_bom(xml).len();
     let xml = trim_utf8_bom(xml);
-    let tree = Document::parse(xml)?;
+    let tree = config.limits.parse_document(xml, 0)?;
     let root = tree.root();
+    config
+        .limits
+        .check_tree(&root, 0, 0)
+        .map_err(|e| error_context(&tree, e))?;
     let xmldevice = root
         .get_child("device")
         .ok_or_else(|| SVDError::MissingTag("device".to_string()).at(root.id()))?;
 
+    let (device, reports) =
+        diagnostic::collect(|| device::parse_device(&xmldevice, config, filter));
     #[allow(unused_mut)]
-    let mut device = match Device::parse(&xmldevice, config) {
-        Ok(o) => Ok(o),
-        Err(e) => {
-            let id = e.id;
-            let node = tree.get_node(id).unwrap();
-            let pos = tree.text_pos_at(node.range().start);
-            let tagname = node.tag_name().name();
-            let mut res = Err(e.into());
-            if tagname.is_empty() {
-                res = res.with_context(|| format!("at {}", pos))
-            } else if let Some(name) = get_name(&node) {
-                res = res.with_context(|| format!("Parsing {} `{}` at {}", tagname, name, pos))
-            } else {
-                res = res.with_context(|| format!("Parsing unknown {} at {}", tagname, pos))
-            }
-            for parent in node.ancestors().skip(1) {
-                if parent.id() == NodeId::new(0) {
-                    break;
-                }
-                let tagname = parent.tag_name().name();
-                match tagname {
-                    "device" | "peripheral" | "register" | "field" | "enumeratedValue"
-                    | "interrupt" => {
-                        if let Some(name) = get_name(&parent) {
-                            res = res.with_context(|| format!("In {} `{}`", tagname, name));
-                        } else {
-                            res = res.with_context(|| format!("In unknown {}", tagname));
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            res
-        }
-    }?;
+    let mut device = device.map_err(|e| error_context(&tree, e))?;
+    let spans = config.source_spans.then(|| {
+        let skipped = spans::Skipped {
+            nodes: diagnostic::nodes(&reports).collect(),
+            filter,
+        };
+        SourceSpans::from_parsed(&tree, bom, &skipped)
+    });
+    let diagnostics = diagnostic::resolve(&tree, reports);
+    let format = config
+        .source_format
+        .then(|| sourceformat::source_format(&tree));
 
     #[cfg(feature = "expand")]
     if config.expand_properties {
@@ -196,9 +386,48 @@ pub fn parse_with_config(xml: &str, config: &Config) -> anyhow::Result<Device> {
 
     #[cfg(feature = "expand")]
     if config.expand {
-        device = expand::expand(&device)?;
+        device = expand::expand_limited(&device, filter, config)?;
     }
-    Ok(device)
+    Ok(ParsedDevice {
+        device,
+        diagnostics,
+        spans,
+        format,
+    })
+}
+
+/// Adds position of the failed element and names of its parents to the error
+pub(crate) fn error_context(tree: &Document, e: SVDErrorAt) -> anyhow::Error {
+    let Some(node) = tree.get_node(e.id) else {
+        return e.into();
+    };
+    let pos = tree.text_pos_at(node.range().start);
+    let tagname = node.tag_name().name();
+    let mut err = anyhow::Error::from(e);
+    if tagname.is_empty() {
+        err = err.context(format!("at {}", pos))
+    } else if let Some(name) = get_name(&node) {
+        err = err.context(format!("Parsing {} `{}` at {}", tagname, name, pos))
+    } else {
+        err = err.context(format!("Parsing unknown {} at {}", tagname, pos))
+    }
+    for parent in node.ancestors().skip(1) {
+        if parent.id() == NodeId::new(0) {
+            break;
+        }
+        let tagname = parent.tag_name().name();
+        match tagname {
+            "device" | "peripheral" | "register" | "field" | "enumeratedValue" | "interrupt" => {
+                if let Some(name) = get_name(&parent) {
+                    err = err.context(format!("In {} `{}`", tagname, name));
+                } else {
+                    err = err.context(format!("In unknown {}", tagname));
+                }
+            }
+            _ => {}
+        }
+    }
+    err
 }
 
 /// Return the &str trimmed UTF-8 BOM if the input &str contains the BOM.
@@ -213,6 +442,34 @@ fn trim_utf8_bom(s: &str) -> &str {
 mod array;
 use array::parse_array;
 
+pub mod diagnostic;
+use diagnostic::get_name;
+pub use diagnostic::{DiagnosticKind, ParseDiagnostic};
+
+pub mod elements;
+pub mod encoding;
+pub mod filter;
+pub mod fragment;
+pub mod ipxact;
+use elements::check_children;
+pub use elements::UnknownElements;
+pub use filter::PeripheralFilter;
+pub use fragment::{parse_cluster, parse_field, parse_fragment, parse_peripheral, parse_register};
+pub use ipxact::parse_ipxact;
+pub mod limits;
+pub use limits::Limits;
+
+pub mod schema;
+pub mod sourceformat;
+pub mod spans;
+pub mod stream;
+pub mod systemrdl;
+pub use schema::{check_schema, SchemaVersion, SchemaViolation};
+pub use spans::{SourceSpan, SourceSpans};
+pub use stream::parse_stream;
+pub use svd::SourceFormat;
+pub use systemrdl::parse_systemrdl;
+
 mod access;
 mod addressblock;
 mod bitrange;
@@ -240,9 +497,13 @@ mod writeconstraint;
 pub mod expand;
 
 #[cfg(feature = "expand")]
-pub use expand::{expand, expand_properties};
+pub use expand::{expand, expand_filtered, expand_properties, expand_with_config};
+
+#[cfg(feature = "archive")]
+pub mod archive;
 /// SVD parse Errors.
 #[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
 pub enum SVDError {
     #[error("{0}")]
     Svd(#[from] svd::SvdError),
@@ -286,6 +547,35 @@ pub enum SVDError {
     DimIndexParse,
     #[error("Name `{0}` in tag `{1}` is missing a %s placeholder")]
     MissingPlaceholder(String, String),
+    #[error("Unknown element <{0}> in <{1}>{}", did_you_mean(.2))]
+    UnknownElement(String, String, Option<String>),
+    #[error("Unsupported encoding `{0}`")]
+    UnsupportedEncoding(String),
+    #[error("Document contains more than {0} elements")]
+    TooManyElements(usize),
+    #[error("Elements are nested deeper than {0} levels")]
+    TooDeep(usize),
+    #[error("Array size {0} exceeds limit {1}")]
+    DimTooLarge(u64, u32),
+    #[error("Decompressed file is larger than {0} bytes")]
+    DecompressedTooLarge(u64),
+    #[error("Input is not valid {0}")]
+    InvalidEncoding(String),
+    #[error("Expression `{0}` is not a number")]
+    InvalidExpression(String),
+    #[error("<{0}> has no CMSIS-SVD equivalent")]
+    NoSvdEquivalent(String),
+    #[error("Invalid SystemRDL: {0}")]
+    InvalidSystemRdl(String),
+    #[error("Invalid IP-XACT: {0}")]
+    InvalidIpxact(String),
+}
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    suggestion
+        .as_ref()
+        .map(|s| format!(", did you mean <{}>?", s))
+        .unwrap_or_default()
 }
 
 #[derive(Clone, Debug, PartialEq)]
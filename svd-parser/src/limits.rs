@@ -0,0 +1,124 @@
+//! Limits for parsing untrusted SVD files.
+
+use roxmltree::{Document, Node, ParsingOptions};
+
+use super::{SVDError, SVDErrorAt};
+
+/// Nodes of any kind (elements, text, comments) allowed per element
+/// while building the XML tree, before elements are counted exactly
+const NODES_PER_ELEMENT: usize = 4;
+
+/// Resource limits. `None` means unlimited
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Limits {
+    /// Maximum number of XML elements in the document.
+    ///
+    /// Also stops building the XML tree early if the document has much more nodes,
+    /// so memory use is bounded
+    pub max_elements: Option<usize>,
+    /// Maximum nesting depth of XML elements and of SystemRDL component definitions
+    pub max_depth: Option<usize>,
+    /// Maximum `dim` of arrays and number of indexes in `dimIndex`
+    pub max_dim: Option<u32>,
+    /// Maximum total number of registers after expansion
+    pub max_expanded_registers: Option<usize>,
+    /// Maximum size in bytes of files decompressed from gzip or zip archives
+    pub max_decompressed_size: Option<u64>,
+}
+
+impl Limits {
+    /// Maximum number of XML elements in the document
+    pub fn max_elements(mut self, val: usize) -> Self {
+        self.max_elements = Some(val);
+        self
+    }
+
+    /// Maximum nesting depth of XML elements
+    pub fn max_depth(mut self, val: usize) -> Self {
+        self.max_depth = Some(val);
+        self
+    }
+
+    /// Maximum `dim` of arrays and number of indexes in `dimIndex`
+    pub fn max_dim(mut self, val: u32) -> Self {
+        self.max_dim = Some(val);
+        self
+    }
+
+    /// Maximum total number of registers after expansion
+    pub fn max_expanded_registers(mut self, val: usize) -> Self {
+        self.max_expanded_registers = Some(val);
+        self
+    }
+
+    /// Maximum size in bytes of files decompressed from gzip or zip archives
+    pub fn max_decompressed_size(mut self, val: u64) -> Self {
+        self.max_decompressed_size = Some(val);
+        self
+    }
+
+    /// Parses XML tree with node count bounded by [`max_elements`](Self::max_elements).
+    /// `count` is the number of elements already seen in other trees
+    pub(crate) fn parse_document<'input>(
+        &self,
+        xml: &'input str,
+        count: usize,
+    ) -> anyhow::Result<Document<'input>> {
+        let mut options = ParsingOptions::default();
+        if let Some(max) = self.max_elements {
+            let nodes = max
+                .saturating_sub(count)
+                .saturating_mul(NODES_PER_ELEMENT)
+                .saturating_add(NODES_PER_ELEMENT);
+            options.nodes_limit = u32::try_from(nodes).unwrap_or(u32::MAX);
+        }
+        Document::parse_with_options(xml, options).map_err(|e| match (e, self.max_elements) {
+            (roxmltree::Error::NodesLimitReached, Some(max)) => {
+                SVDError::TooManyElements(max).into()
+            }
+            (e, _) => e.into(),
+        })
+    }
+
+    /// Checks number of elements and nesting depth of the tree starting at `root`.
+    /// `count` is the number of elements already seen in other trees,
+    /// `depth` is the depth of `root` in the original document.
+    /// Returns the total number of elements
+    pub(crate) fn check_tree(
+        &self,
+        root: &Node,
+        mut count: usize,
+        mut depth: usize,
+    ) -> Result<usize, SVDErrorAt> {
+        if self.max_elements.is_none() && self.max_depth.is_none() {
+            return Ok(count);
+        }
+        let mut stack = vec![(*root, depth)];
+        while let Some((node, d)) = stack.pop() {
+            depth = d;
+            if node.is_element() {
+                count += 1;
+                depth += 1;
+                if let Some(max) = self.max_elements.filter(|max| count > *max) {
+                    return Err(SVDError::TooManyElements(max).at(node.id()));
+                }
+                if let Some(max) = self.max_depth.filter(|max| depth > *max) {
+                    return Err(SVDError::TooDeep(max).at(node.id()));
+                }
+            }
+            // Reversed to visit children in document order
+            let children = node.children().filter(Node::is_element).collect::<Vec<_>>();
+            stack.extend(children.into_iter().rev().map(|c| (c, depth)));
+        }
+        Ok(count)
+    }
+
+    /// Checks `dim` value
+    pub(crate) fn check_dim(&self, dim: u64) -> Result<(), SVDError> {
+        match self.max_dim {
+            Some(max) if dim > u64::from(max) => Err(SVDError::DimTooLarge(dim, max)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -22,6 +22,7 @@ impl Parse for PeripheralInfo {
         if !tree.has_tag_name("peripheral") {
             return Err(SVDError::NotExpectedTag("peripheral".to_string()).at(tree.id()));
         }
+        check_children(tree, config)?;
 
         PeripheralInfo::builder()
             .name(tree.get_child_text("name")?)
@@ -36,33 +37,27 @@ impl Parse for PeripheralInfo {
             .base_address(tree.get_child_u64("baseAddress")?)
             .default_register_properties(RegisterProperties::parse(tree, config)?)
             .address_block({
-                let ab: Result<Vec<_>, _> = tree
-                    .children()
-                    .filter(|t| t.is_element() && t.has_tag_name("addressBlock"))
-                    .map(|i| AddressBlock::parse(&i, config))
-                    .collect();
-                let ab = ab?;
+                let ab = parse_children::<AddressBlock>(
+                    tree.children()
+                        .filter(|t| t.is_element() && t.has_tag_name("addressBlock")),
+                    config,
+                )?;
                 if ab.is_empty() {
                     None
                 } else {
                     Some(ab)
                 }
             })
-            .interrupt({
-                let interrupt: Result<Vec<_>, _> = tree
-                    .children()
-                    .filter(|t| t.is_element() && t.has_tag_name("interrupt"))
-                    .map(|i| Interrupt::parse(&i, config))
-                    .collect();
-                Some(interrupt?)
-            })
+            .interrupt(Some(parse_children::<Interrupt>(
+                tree.children()
+                    .filter(|t| t.is_element() && t.has_tag_name("interrupt")),
+                config,
+            )?))
             .registers(if let Some(registers) = tree.get_child("registers") {
-                let rs: Result<Vec<_>, _> = registers
-                    .children()
-                    .filter(Node::is_element)
-                    .map(|t| RegisterCluster::parse(&t, config))
-                    .collect();
-                Some(rs?)
+                Some(parse_children::<RegisterCluster>(
+                    registers.children().filter(Node::is_element),
+                    config,
+                )?)
             } else {
                 None
             })
@@ -20,6 +20,7 @@ impl Parse for RegisterInfo {
     type Config = Config;
 
     fn parse(tree: &Node, config: &Self::Config) -> Result<Self, Self::Error> {
+        check_children(tree, config)?;
         RegisterInfo::builder()
             .name(tree.get_child_text("name")?)
             .display_name(tree.get_child_text_opt("displayName")?)
@@ -28,26 +29,24 @@ impl Parse for RegisterInfo {
             .alternate_register(tree.get_child_text_opt("alternateRegister")?)
             .address_offset(tree.get_child_u32("addressOffset")?)
             .properties(RegisterProperties::parse(tree, config)?)
-            .datatype(optional::<DataType>("dataType", tree, config)?)
-            .modified_write_values(optional::<ModifiedWriteValues>(
+            .datatype(optional_lenient::<DataType>("dataType", tree, config)?)
+            .modified_write_values(optional_lenient::<ModifiedWriteValues>(
                 "modifiedWriteValues",
                 tree,
                 config,
             )?)
-            .write_constraint(optional::<WriteConstraint>(
+            .write_constraint(optional_lenient::<WriteConstraint>(
                 "writeConstraint",
                 tree,
                 config,
             )?)
-            .read_action(optional::<ReadAction>("readAction", tree, config)?)
+            .read_action(optional_lenient::<ReadAction>("readAction", tree, config)?)
             .fields({
                 if let Some(fields) = tree.get_child("fields") {
-                    let fs: Result<Vec<_>, _> = fields
-                        .children()
-                        .filter(Node::is_element)
-                        .map(|t| Field::parse(&t, config))
-                        .collect();
-                    Some(fs?)
+                    Some(parse_children::<Field>(
+                        fields.children().filter(Node::is_element),
+                        config,
+                    )?)
                 } else {
                     None
                 }
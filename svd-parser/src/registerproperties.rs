@@ -8,11 +8,11 @@ impl Parse for RegisterProperties {
 
     fn parse(tree: &Node, config: &Self::Config) -> Result<Self, Self::Error> {
         RegisterProperties::new()
-            .size(optional::<u32>("size", tree, &())?)
-            .access(optional::<Access>("access", tree, config)?)
-            .protection(optional::<Protection>("protection", tree, config)?)
-            .reset_value(optional::<u64>("resetValue", tree, &())?)
-            .reset_mask(optional::<u64>("resetMask", tree, &())?)
+            .size(optional_lenient::<u32>("size", tree, config)?)
+            .access(optional_lenient::<Access>("access", tree, config)?)
+            .protection(optional_lenient::<Protection>("protection", tree, config)?)
+            .reset_value(optional_lenient::<u64>("resetValue", tree, config)?)
+            .reset_mask(optional_lenient::<u64>("resetMask", tree, config)?)
             .build(config.validate_level)
             .map_err(|e| SVDError::from(e).at(tree.id()))
     }
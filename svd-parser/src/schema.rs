@@ -0,0 +1,867 @@
+//! CMSIS-SVD schema conformance checks.
+//!
+//! Rules of the official `CMSIS-SVD.xsd` files for schema versions 1.1 and 1.3 are embedded here,
+//! so documents can be checked without external XML validators.
+//! Element order, cardinality, identifier patterns and enumerated values are verified.
+
+use roxmltree::{Document, Node, TextPos};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::LazyLock;
+
+use super::diagnostic::node_path;
+use super::elementext::ElementExt;
+use super::trim_utf8_bom;
+
+/// Version of the CMSIS-SVD schema
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SchemaVersion {
+    /// Schema 1.1 (also used for 1.0 and 1.2 documents)
+    V1_1,
+    /// Schema 1.3
+    V1_3,
+}
+
+impl SchemaVersion {
+    /// Select rules for `schemaVersion` attribute value
+    pub fn from_attribute(s: &str) -> Self {
+        let mut parts = s.trim().split('.');
+        let major = parts.next().and_then(|p| p.parse::<u32>().ok());
+        let minor = parts.next().and_then(|p| p.parse::<u32>().ok());
+        match (major, minor) {
+            (Some(1), Some(minor)) if minor < 3 => Self::V1_1,
+            (Some(0), _) => Self::V1_1,
+            _ => Self::V1_3,
+        }
+    }
+}
+
+/// Kind of schema violation
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// Element is not allowed in this parent
+    UnexpectedElement {
+        /// Element tag
+        tag: String,
+        /// Parent tag
+        parent: String,
+    },
+    /// Element is allowed in parent, but at other position
+    OutOfOrder {
+        /// Element tag
+        tag: String,
+        /// Parent tag
+        parent: String,
+    },
+    /// Element occurs more times than allowed
+    TooMany {
+        /// Element tag
+        tag: String,
+        /// Parent tag
+        parent: String,
+        /// Maximum number of occurrences
+        max: u32,
+    },
+    /// Required element is absent
+    Missing {
+        /// Expected element tags
+        tags: Vec<&'static str>,
+        /// Parent tag
+        parent: String,
+    },
+    /// Element content doesn't match the required type
+    InvalidValue {
+        /// Element tag
+        tag: String,
+        /// Content of element
+        value: String,
+        /// Description of expected value
+        expected: String,
+    },
+    /// Required attribute is absent
+    MissingAttribute {
+        /// Attribute name
+        attribute: &'static str,
+        /// Element tag
+        tag: String,
+    },
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedElement { tag, parent } => {
+                write!(f, "Element <{tag}> is not allowed in <{parent}>")
+            }
+            Self::OutOfOrder { tag, parent } => {
+                write!(f, "Element <{tag}> is out of order in <{parent}>")
+            }
+            Self::TooMany { tag, parent, max } => {
+                write!(
+                    f,
+                    "Element <{tag}> can occur at most {max} time(s) in <{parent}>"
+                )
+            }
+            Self::Missing { tags, parent } => {
+                write!(f, "Expected <{}> in <{parent}>", tags.join("> or <"))
+            }
+            Self::InvalidValue {
+                tag,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid content `{value}` of <{tag}>, expected {expected}"
+            ),
+            Self::MissingAttribute { attribute, tag } => {
+                write!(f, "Required attribute `{attribute}` is absent in <{tag}>")
+            }
+        }
+    }
+}
+
+/// Schema rule violation found in the document
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SchemaViolation {
+    /// What rule is violated
+    pub kind: ViolationKind,
+    /// Names of parent elements joined with `.`
+    pub path: String,
+    /// Position of the element
+    pub pos: TextPos,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.kind, self.pos)?;
+        if !self.path.is_empty() {
+            write!(f, " in `{}`", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the SVD (XML) string against the schema selected by its `schemaVersion` attribute
+pub fn check_schema(xml: &str) -> anyhow::Result<Vec<SchemaViolation>> {
+    let tree = Document::parse(trim_utf8_bom(xml))?;
+    let version = tree
+        .root_element()
+        .attribute("schemaVersion")
+        .map(SchemaVersion::from_attribute)
+        .unwrap_or(SchemaVersion::V1_1);
+    Ok(check_document(&tree, version))
+}
+
+/// Checks the SVD (XML) string against the specified schema version
+pub fn check_schema_version(
+    xml: &str,
+    version: SchemaVersion,
+) -> anyhow::Result<Vec<SchemaViolation>> {
+    let tree = Document::parse(trim_utf8_bom(xml))?;
+    Ok(check_document(&tree, version))
+}
+
+/// Checks parsed XML document against the specified schema version
+pub fn check_document(tree: &Document, version: SchemaVersion) -> Vec<SchemaViolation> {
+    let schema = match version {
+        SchemaVersion::V1_1 => &*SCHEMA_1_1,
+        SchemaVersion::V1_3 => &*SCHEMA_1_3,
+    };
+    let mut checker = Checker {
+        tree,
+        schema,
+        violations: Vec::new(),
+    };
+    let root = tree.root_element();
+    if root.has_tag_name("device") {
+        if root.attribute("schemaVersion").is_none() {
+            checker.push(
+                &root,
+                ViolationKind::MissingAttribute {
+                    attribute: "schemaVersion",
+                    tag: "device".into(),
+                },
+            );
+        }
+        checker.check_complex(&root, "device");
+    } else {
+        checker.push(
+            &root,
+            ViolationKind::UnexpectedElement {
+                tag: root.tag_name().name().into(),
+                parent: String::new(),
+            },
+        );
+    }
+    checker.violations
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Simple {
+    String,
+    Identifier,
+    DimableIdentifier,
+    ScaledInteger,
+    Integer,
+    Boolean,
+    Revision,
+    Endian,
+    Access,
+    Protection,
+    BlockUsage,
+    EnumUsage,
+    ModifiedWriteValues,
+    ReadAction,
+    DataType,
+    BitRange,
+    EnumeratedValueData,
+    DimIndex,
+}
+
+impl Simple {
+    fn values(self) -> Option<&'static [&'static str]> {
+        Some(match self {
+            Self::Boolean => &["true", "false", "0", "1"],
+            Self::Endian => &["little", "big", "selectable", "other"],
+            Self::Access => &[
+                "read-only",
+                "write-only",
+                "read-write",
+                "writeOnce",
+                "read-writeOnce",
+            ],
+            Self::Protection => &["s", "n", "p"],
+            Self::BlockUsage => &["registers", "buffer", "reserved"],
+            Self::EnumUsage => &["read", "write", "read-write"],
+            Self::ModifiedWriteValues => &[
+                "oneToClear",
+                "oneToSet",
+                "oneToToggle",
+                "zeroToClear",
+                "zeroToSet",
+                "zeroToToggle",
+                "clear",
+                "set",
+                "modify",
+            ],
+            Self::ReadAction => &["clear", "set", "modify", "modifyExternal"],
+            Self::DataType => &[
+                "uint8_t",
+                "uint16_t",
+                "uint32_t",
+                "uint64_t",
+                "int8_t",
+                "int16_t",
+                "int32_t",
+                "int64_t",
+                "uint8_t *",
+                "uint16_t *",
+                "uint32_t *",
+                "uint64_t *",
+                "int8_t *",
+                "int16_t *",
+                "int32_t *",
+                "int64_t *",
+            ],
+            _ => return None,
+        })
+    }
+
+    /// Returns a matcher of the type's `xs:pattern` and the name of the type
+    fn pattern(self) -> Option<(Matcher, &'static str)> {
+        Some(match self {
+            Self::Identifier => (is_identifier, "identifierType"),
+            Self::DimableIdentifier => (is_dimable_identifier, "dimableIdentifierType"),
+            Self::ScaledInteger => (is_scaled_integer, "scaledNonNegativeInteger"),
+            Self::Integer => (is_integer, "integer"),
+            Self::Revision => (is_revision, "revisionType"),
+            Self::BitRange => (is_bit_range, "bitRangeType"),
+            Self::EnumeratedValueData => (is_enumerated_value_data, "enumeratedValueDataType"),
+            Self::DimIndex => (is_dim_index, "dimIndexType"),
+            _ => return None,
+        })
+    }
+
+    /// Returns description of expected value if `text` doesn't conform the type
+    fn check(self, text: &str) -> Option<String> {
+        if let Some(values) = self.values() {
+            if !values.contains(&text) {
+                return Some(format!("one of `{}`", values.join("`, `")));
+            }
+        } else if let Some((matches, name)) = self.pattern() {
+            if !matches(text) {
+                return Some(name.to_string());
+            }
+        }
+        None
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c == '_' || c.is_ascii_alphanumeric()
+}
+
+/// `[_A-Za-z][_A-Za-z0-9]*`
+fn is_name(s: &str) -> bool {
+    s.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) && s.chars().all(is_word)
+}
+
+/// `[_A-Za-z0-9]*`
+fn is_identifier(s: &str) -> bool {
+    s.chars().all(is_word)
+}
+
+/// `(%s)|(%s)[_A-Za-z][_A-Za-z0-9]*|[_A-Za-z][_A-Za-z0-9]*(\[%s\])?|[_A-Za-z][_A-Za-z0-9]*(%s)?[_A-Za-z0-9]*`
+fn is_dimable_identifier(s: &str) -> bool {
+    if let Some(rest) = s.strip_prefix("%s") {
+        return rest.is_empty() || is_name(rest);
+    }
+    if let Some(name) = s.strip_suffix("[%s]") {
+        return is_name(name);
+    }
+    match s.split_once("%s") {
+        Some((name, rest)) => is_name(name) && is_identifier(rest),
+        None => is_name(s),
+    }
+}
+
+/// `[+]?(0x|0X|#)?[0-9a-fA-F]+[kmgtKMGT]?`
+fn is_scaled_integer(s: &str) -> bool {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    let s = ["0x", "0X", "#"]
+        .iter()
+        .find_map(|p| s.strip_prefix(p))
+        .unwrap_or(s);
+    let s = s.strip_suffix(|c| "kmgtKMGT".contains(c)).unwrap_or(s);
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// `[+-]?[0-9]+`
+fn is_integer(s: &str) -> bool {
+    let s = s.strip_prefix(['+', '-']).unwrap_or(s);
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+/// `r[0-9]*p[0-9]*`
+fn is_revision(s: &str) -> bool {
+    s.strip_prefix('r')
+        .and_then(|s| s.split_once('p'))
+        .is_some_and(|(r, p)| (r.chars().chain(p.chars())).all(|c| c.is_ascii_digit()))
+}
+
+/// `\[([0-4])?[0-9]:([0-4])?[0-9]\]`
+fn is_bit_range(s: &str) -> bool {
+    let bit = |s: &str| match s.as_bytes() {
+        [d] => d.is_ascii_digit(),
+        [t, d] => (b'0'..=b'4').contains(t) && d.is_ascii_digit(),
+        _ => false,
+    };
+    s.strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .and_then(|s| s.split_once(':'))
+        .is_some_and(|(msb, lsb)| bit(msb) && bit(lsb))
+}
+
+/// `[+]?(0x|0X|#|0b)?[0-9a-fA-FxX]+`
+fn is_enumerated_value_data(s: &str) -> bool {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    // `0x`, `0X` and `0b` consist of digit characters themselves
+    let s = s.strip_prefix('#').unwrap_or(s);
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_hexdigit() || c == 'x' || c == 'X')
+}
+
+/// `[0-9]+\-[0-9]+|[A-Z]-[A-Z]|[_0-9a-zA-Z]+(,\s*[_0-9a-zA-Z]+)+`
+fn is_dim_index(s: &str) -> bool {
+    let range = |s: &str, f: Matcher| {
+        s.split_once('-')
+            .is_some_and(|(start, end)| f(start) && f(end))
+    };
+    let number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let letter = |s: &str| s.len() == 1 && s.chars().all(|c| c.is_ascii_uppercase());
+    let word = |s: &str| !s.is_empty() && s.chars().all(is_word);
+    range(s, number)
+        || range(s, letter)
+        || (s.contains(',')
+            && s.split(',')
+                .enumerate()
+                .all(|(i, w)| word(if i == 0 { w } else { w.trim_start() })))
+}
+
+/// Checks that a value matches the whole `xs:pattern` of a simple type
+type Matcher = fn(&str) -> bool;
+
+#[derive(Clone, Copy, Debug)]
+enum Content {
+    Simple(Simple),
+    Complex(&'static str),
+    /// Content is not checked
+    Any,
+}
+
+/// One or several (`xs:choice`) elements in a sequence
+#[derive(Clone, Debug)]
+struct Particle {
+    elements: Vec<(&'static str, Content)>,
+    min: u32,
+    max: Option<u32>,
+}
+
+impl Particle {
+    fn find(&self, tag: &str) -> Option<Content> {
+        self.elements
+            .iter()
+            .find(|(name, _)| *name == tag)
+            .map(|(_, c)| *c)
+    }
+}
+
+type Schema = HashMap<&'static str, Vec<Particle>>;
+
+static SCHEMA_1_1: LazyLock<Schema> = LazyLock::new(|| build_schema(SchemaVersion::V1_1));
+static SCHEMA_1_3: LazyLock<Schema> = LazyLock::new(|| build_schema(SchemaVersion::V1_3));
+
+fn build_schema(version: SchemaVersion) -> Schema {
+    use Content::{Any, Complex as C, Simple as S};
+    use Simple::*;
+    let v1_3 = version >= SchemaVersion::V1_3;
+    let one = |name, content| Particle {
+        elements: vec![(name, content)],
+        min: 1,
+        max: Some(1),
+    };
+    let opt = |name, content| Particle {
+        elements: vec![(name, content)],
+        min: 0,
+        max: Some(1),
+    };
+    let many = |name, content, min| Particle {
+        elements: vec![(name, content)],
+        min,
+        max: None,
+    };
+    let dim_group = || {
+        let mut v = vec![
+            opt("dim", S(ScaledInteger)),
+            opt("dimIncrement", S(ScaledInteger)),
+            opt("dimIndex", S(DimIndex)),
+        ];
+        if v1_3 {
+            v.push(opt("dimName", S(Identifier)));
+            v.push(opt("dimArrayIndex", C("dimArrayIndex")));
+        }
+        v
+    };
+    let properties = || {
+        let mut v = vec![opt("size", S(ScaledInteger)), opt("access", S(Access))];
+        if v1_3 {
+            v.push(opt("protection", S(Protection)));
+        }
+        v.push(opt("resetValue", S(ScaledInteger)));
+        v.push(opt("resetMask", S(ScaledInteger)));
+        v
+    };
+
+    let mut schema = Schema::new();
+
+    let mut device = vec![
+        opt("vendor", S(String)),
+        opt("vendorID", S(Identifier)),
+        one("name", S(String)),
+        opt("series", S(String)),
+        one("version", S(String)),
+        one("description", S(String)),
+        opt("licenseText", S(String)),
+        opt("cpu", C("cpu")),
+        opt("headerSystemFilename", S(Identifier)),
+        opt("headerDefinitionsPrefix", S(Identifier)),
+        one("addressUnitBits", S(ScaledInteger)),
+        one("width", S(ScaledInteger)),
+    ];
+    device.extend(properties());
+    device.push(one("peripherals", C("peripherals")));
+    device.push(opt("vendorExtensions", Any));
+    schema.insert("device", device);
+
+    let mut cpu = vec![
+        one("name", S(String)),
+        one("revision", S(Revision)),
+        one("endian", S(Endian)),
+        one("mpuPresent", S(Boolean)),
+        one("fpuPresent", S(Boolean)),
+        opt("fpuDP", S(Boolean)),
+    ];
+    if v1_3 {
+        cpu.push(opt("dspPresent", S(Boolean)));
+    }
+    cpu.extend([
+        opt("icachePresent", S(Boolean)),
+        opt("dcachePresent", S(Boolean)),
+        opt("itcmPresent", S(Boolean)),
+        opt("dtcmPresent", S(Boolean)),
+        opt("vtorPresent", S(Boolean)),
+        one("nvicPrioBits", S(ScaledInteger)),
+        one("vendorSystickConfig", S(Boolean)),
+        opt("deviceNumInterrupts", S(ScaledInteger)),
+    ]);
+    if v1_3 {
+        cpu.push(opt("sauNumRegions", S(ScaledInteger)));
+        cpu.push(opt("sauRegionsConfig", Any));
+    }
+    schema.insert("cpu", cpu);
+
+    schema.insert("peripherals", vec![many("peripheral", C("peripheral"), 1)]);
+
+    let mut peripheral = if v1_3 { dim_group() } else { Vec::new() };
+    peripheral.extend([
+        one("name", S(DimableIdentifier)),
+        opt("version", S(String)),
+        opt("description", S(String)),
+        opt("alternatePeripheral", S(DimableIdentifier)),
+        opt("groupName", S(String)),
+        opt("prependToName", S(Identifier)),
+        opt("appendToName", S(Identifier)),
+        opt("headerStructName", S(DimableIdentifier)),
+        opt("disableCondition", S(String)),
+        one("baseAddress", S(ScaledInteger)),
+    ]);
+    peripheral.extend(properties());
+    peripheral.extend([
+        many("addressBlock", C("addressBlock"), 0),
+        many("interrupt", C("interrupt"), 0),
+        opt("registers", C("registers")),
+    ]);
+    schema.insert("peripheral", peripheral);
+
+    let mut block = vec![
+        one("offset", S(ScaledInteger)),
+        one("size", S(ScaledInteger)),
+        one("usage", S(BlockUsage)),
+    ];
+    if v1_3 {
+        block.push(opt("protection", S(Protection)));
+    }
+    schema.insert("addressBlock", block);
+
+    schema.insert(
+        "interrupt",
+        vec![
+            one("name", S(String)),
+            opt("description", S(String)),
+            one("value", S(Integer)),
+        ],
+    );
+
+    let register_or_cluster = |min| Particle {
+        elements: vec![("register", C("register")), ("cluster", C("cluster"))],
+        min,
+        max: None,
+    };
+    schema.insert("registers", vec![register_or_cluster(1)]);
+
+    let mut cluster = dim_group();
+    cluster.extend([
+        one("name", S(DimableIdentifier)),
+        opt("description", S(String)),
+        opt("alternateCluster", S(DimableIdentifier)),
+        opt("headerStructName", S(Identifier)),
+        one("addressOffset", S(ScaledInteger)),
+    ]);
+    cluster.extend(properties());
+    if v1_3 {
+        cluster.push(register_or_cluster(1));
+    } else {
+        cluster.push(many("register", C("register"), 0));
+        cluster.push(many("cluster", C("cluster"), 0));
+    }
+    schema.insert("cluster", cluster);
+
+    let mut register = dim_group();
+    register.extend([
+        one("name", S(DimableIdentifier)),
+        opt("displayName", S(String)),
+        opt("description", S(String)),
+        Particle {
+            elements: vec![
+                ("alternateGroup", S(Identifier)),
+                ("alternateRegister", S(DimableIdentifier)),
+            ],
+            min: 0,
+            max: Some(1),
+        },
+        one("addressOffset", S(ScaledInteger)),
+    ]);
+    register.extend(properties());
+    register.extend([
+        opt("dataType", S(DataType)),
+        opt("modifiedWriteValues", S(ModifiedWriteValues)),
+        opt("writeConstraint", C("writeConstraint")),
+        opt("readAction", S(ReadAction)),
+        opt("fields", C("fields")),
+    ]);
+    schema.insert("register", register);
+
+    schema.insert("fields", vec![many("field", C("field"), 1)]);
+
+    let mut field = dim_group();
+    field.extend([
+        one("name", S(DimableIdentifier)),
+        opt("description", S(String)),
+        // The `bitRange` choice is checked separately
+        opt("bitOffset", S(ScaledInteger)),
+        opt("bitWidth", S(ScaledInteger)),
+        opt("lsb", S(ScaledInteger)),
+        opt("msb", S(ScaledInteger)),
+        opt("bitRange", S(BitRange)),
+        opt("access", S(Access)),
+        opt("modifiedWriteValues", S(ModifiedWriteValues)),
+        opt("writeConstraint", C("writeConstraint")),
+        opt("readAction", S(ReadAction)),
+        Particle {
+            elements: vec![("enumeratedValues", C("enumeratedValues"))],
+            min: 0,
+            max: Some(2),
+        },
+    ]);
+    schema.insert("field", field);
+
+    schema.insert(
+        "writeConstraint",
+        vec![Particle {
+            elements: vec![
+                ("writeAsRead", S(Boolean)),
+                ("useEnumeratedValues", S(Boolean)),
+                ("range", C("range")),
+            ],
+            min: 1,
+            max: Some(1),
+        }],
+    );
+    schema.insert(
+        "range",
+        vec![
+            one("minimum", S(ScaledInteger)),
+            one("maximum", S(ScaledInteger)),
+        ],
+    );
+
+    let mut evs = vec![opt("name", S(Identifier))];
+    if v1_3 {
+        evs.push(opt("headerEnumName", S(Identifier)));
+    }
+    evs.push(opt("usage", S(EnumUsage)));
+    evs.push(many("enumeratedValue", C("enumeratedValue"), 1));
+    schema.insert("enumeratedValues", evs);
+
+    schema.insert(
+        "enumeratedValue",
+        vec![
+            one("name", S(Identifier)),
+            opt("description", S(String)),
+            Particle {
+                elements: vec![("value", S(EnumeratedValueData)), ("isDefault", S(Boolean))],
+                min: 1,
+                max: Some(1),
+            },
+        ],
+    );
+
+    schema.insert(
+        "dimArrayIndex",
+        vec![
+            opt("headerEnumName", S(Identifier)),
+            many("enumeratedValue", C("enumeratedValue"), 1),
+        ],
+    );
+
+    schema
+}
+
+struct Checker<'a, 'input> {
+    tree: &'a Document<'input>,
+    schema: &'a Schema,
+    violations: Vec<SchemaViolation>,
+}
+
+impl Checker<'_, '_> {
+    fn push(&mut self, node: &Node, kind: ViolationKind) {
+        self.violations.push(SchemaViolation {
+            kind,
+            path: node_path(node),
+            pos: self.tree.text_pos_at(node.range().start),
+        });
+    }
+
+    fn check_simple(&mut self, node: &Node, simple: Simple) {
+        if node.first_element_child().is_some() {
+            self.push(
+                node,
+                ViolationKind::InvalidValue {
+                    tag: node.tag_name().name().into(),
+                    value: String::new(),
+                    expected: "text content".into(),
+                },
+            );
+            return;
+        }
+        let text = node.get_text().unwrap_or_default();
+        if let Some(expected) = simple.check(text) {
+            self.push(
+                node,
+                ViolationKind::InvalidValue {
+                    tag: node.tag_name().name().into(),
+                    value: text.into(),
+                    expected,
+                },
+            );
+        }
+    }
+
+    fn check_complex(&mut self, node: &Node, type_name: &'static str) {
+        let Some(particles) = self.schema.get(type_name) else {
+            return;
+        };
+        let parent = node.tag_name().name();
+        let mut idx = 0;
+        let mut count = 0;
+        for child in node.children().filter(Node::is_element) {
+            let tag = child.tag_name().name();
+            // Find particle for the element, starting from current one
+            let found = particles[idx..]
+                .iter()
+                .position(|p| p.find(tag).is_some())
+                .map(|i| i + idx);
+            let Some(found) = found else {
+                if let Some(content) = particles[..idx].iter().find_map(|p| p.find(tag)) {
+                    self.push(
+                        &child,
+                        ViolationKind::OutOfOrder {
+                            tag: tag.into(),
+                            parent: parent.into(),
+                        },
+                    );
+                    self.check_content(&child, content);
+                } else {
+                    self.push(
+                        &child,
+                        ViolationKind::UnexpectedElement {
+                            tag: tag.into(),
+                            parent: parent.into(),
+                        },
+                    );
+                }
+                continue;
+            };
+            if found != idx {
+                // Skipped particles must be optional
+                self.check_min(node, &particles[idx], count);
+                for p in &particles[idx + 1..found] {
+                    self.check_min(node, p, 0);
+                }
+                idx = found;
+                count = 0;
+            }
+            let particle = &particles[idx];
+            count += 1;
+            if particle.max.is_some_and(|max| count > max) {
+                self.push(
+                    &child,
+                    ViolationKind::TooMany {
+                        tag: tag.into(),
+                        parent: parent.into(),
+                        max: particle.max.unwrap_or_default(),
+                    },
+                );
+            }
+            if let Some(content) = particle.find(tag) {
+                self.check_content(&child, content);
+            }
+        }
+        if let Some(p) = particles.get(idx) {
+            self.check_min(node, p, count);
+            for p in &particles[idx + 1..] {
+                self.check_min(node, p, 0);
+            }
+        }
+        if type_name == "field" {
+            self.check_bit_range(node);
+        }
+    }
+
+    fn check_content(&mut self, node: &Node, content: Content) {
+        match content {
+            Content::Simple(simple) => self.check_simple(node, simple),
+            Content::Complex(type_name) => self.check_complex(node, type_name),
+            Content::Any => {}
+        }
+    }
+
+    fn check_min(&mut self, node: &Node, particle: &Particle, count: u32) {
+        // Elements present at wrong position are already reported as out of order
+        let misplaced = || {
+            node.children()
+                .any(|c| c.is_element() && particle.find(c.tag_name().name()).is_some())
+        };
+        if count < particle.min && (count > 0 || !misplaced()) {
+            self.push(
+                node,
+                ViolationKind::Missing {
+                    tags: particle.elements.iter().map(|(name, _)| *name).collect(),
+                    parent: node.tag_name().name().into(),
+                },
+            );
+        }
+    }
+
+    /// Field position is `xs:choice` of `bitOffset`+`bitWidth`, `lsb`+`msb` or `bitRange`
+    fn check_bit_range(&mut self, node: &Node) {
+        let has = |tag| node.get_child(tag).is_some();
+        let forms = [
+            has("bitOffset") || has("bitWidth"),
+            has("lsb") || has("msb"),
+            has("bitRange"),
+        ];
+        let missing = match forms.iter().filter(|f| **f).count() {
+            0 => Some(vec!["bitOffset", "lsb", "bitRange"]),
+            1 if has("bitWidth") && !has("bitOffset") => Some(vec!["bitOffset"]),
+            1 if has("lsb") != has("msb") => Some(vec![if has("lsb") { "msb" } else { "lsb" }]),
+            1 => None,
+            _ => {
+                for tag in ["lsb", "msb", "bitRange"] {
+                    if let Some(child) = node.get_child(tag) {
+                        if forms[0] || (tag == "bitRange" && forms[1]) {
+                            self.push(
+                                &child,
+                                ViolationKind::UnexpectedElement {
+                                    tag: tag.into(),
+                                    parent: "field".into(),
+                                },
+                            );
+                        }
+                    }
+                }
+                None
+            }
+        };
+        if let Some(tags) = missing {
+            self.push(
+                node,
+                ViolationKind::Missing {
+                    tags,
+                    parent: "field".into(),
+                },
+            );
+        }
+    }
+}
+
+#[test]
+fn test_schema_version() {
+    assert_eq!(SchemaVersion::from_attribute("1.0"), SchemaVersion::V1_1);
+    assert_eq!(SchemaVersion::from_attribute("1.1"), SchemaVersion::V1_1);
+    assert_eq!(SchemaVersion::from_attribute("1.3"), SchemaVersion::V1_3);
+    assert_eq!(SchemaVersion::from_attribute("1.3.9"), SchemaVersion::V1_3);
+}
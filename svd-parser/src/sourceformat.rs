@@ -0,0 +1,136 @@
+//! Recording of the original form of elements.
+//!
+//! Enabled by [`Config::source_format`](crate::Config::source_format).
+//! See [`svd_rs::sourceformat`] for the description of the table.
+
+use roxmltree::{Document, Node};
+
+use crate::svd::sourceformat::{child_path, segments, ElementFormat, SourceFormat};
+
+/// Records the original form of all elements of the document
+pub fn source_format(tree: &Document) -> SourceFormat {
+    let mut format = SourceFormat::new();
+    let input = tree.input_text();
+    format.text = input.to_string();
+    format.indent = indent(input);
+    let root = tree.root_element();
+    let segment = segments([(root.tag_name().name(), name(&root))]).remove(0);
+    record(&mut format, input, &root, segment);
+    format
+}
+
+fn record(format: &mut SourceFormat, input: &str, node: &Node, path: String) {
+    let children = node.children().filter(Node::is_element).collect::<Vec<_>>();
+    let segs = segments(children.iter().map(|c| (c.tag_name().name(), name(c))));
+
+    let range = node.range();
+    let text = &input[range.clone()];
+    let self_closing = text.ends_with("/>");
+    let end_tag = range.start + text.rfind("</").unwrap_or(text.len());
+    let mut element = ElementFormat::default();
+    element.start_tag_end = match node.first_child() {
+        Some(child) => child.range().start,
+        None if self_closing => range.end,
+        None => end_tag,
+    };
+    element.attributes = attributes(node, &input[range.start..element.start_tag_end]);
+    if !self_closing {
+        let mut start = element.start_tag_end;
+        for child in &children {
+            element.gaps.push(start..child.range().start);
+            start = child.range().end;
+        }
+        element.gaps.push(start..end_tag);
+    }
+    for (child, seg) in children.iter().zip(&segs) {
+        if !child.children().any(|c| c.is_element()) {
+            let text = child
+                .children()
+                .filter(Node::is_text)
+                .filter_map(|c| c.text())
+                .collect::<String>();
+            element.values.insert(seg.clone(), text);
+        }
+    }
+    element.children = segs.clone();
+    element.range = range;
+    format.insert(path.clone(), element);
+
+    for (child, seg) in children.iter().zip(&segs) {
+        record(format, input, child, child_path(&path, seg));
+    }
+}
+
+/// Text of `<name>` child of element
+fn name<'a>(node: &Node<'a, '_>) -> Option<&'a str> {
+    node.children()
+        .find(|c| c.has_tag_name("name"))
+        .and_then(|c| c.text())
+        .map(str::trim)
+}
+
+/// Attributes and namespace declarations of element with `start_tag` in written order
+fn attributes(node: &Node, start_tag: &str) -> Vec<(String, String)> {
+    let start = node.range().start;
+    let parent = node.parent_element();
+    let mut attrs = node
+        .namespaces()
+        .filter(|ns| parent.is_none_or(|p| !p.namespaces().any(|p| p == *ns)))
+        .map(|ns| {
+            let key = match ns.name() {
+                Some(prefix) => format!("xmlns:{prefix}"),
+                None => "xmlns".to_string(),
+            };
+            // Namespace declarations have no positions
+            let pos = start_tag
+                .match_indices(&key)
+                .map(|(i, _)| i)
+                .find(|i| start_tag[i + key.len()..].trim_start().starts_with('='))
+                .unwrap_or(start_tag.len());
+            (pos, key, ns.uri().to_string())
+        })
+        .collect::<Vec<_>>();
+    for attr in node.attributes() {
+        let key = node.document().input_text()[attr.range_qname()].to_string();
+        attrs.push((attr.range().start - start, key, attr.value().to_string()));
+    }
+    attrs.sort_by_key(|(pos, ..)| *pos);
+    attrs
+        .into_iter()
+        .map(|(_, key, value)| (key, value))
+        .collect()
+}
+
+/// Indentation of the first indented element
+fn indent(input: &str) -> Option<String> {
+    input.lines().find_map(|line| {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        (trimmed.len() < line.len() && trimmed.starts_with('<'))
+            .then(|| line[..line.len() - trimmed.len()].to_string())
+    })
+}
+
+#[test]
+fn test_attributes() {
+    let xml = r#"<device schemaVersion="1.1" xmlns:xs='http://x' xs:noNamespaceSchemaLocation="a.xsd" >
+<field derivedFrom = "A"/></device>"#;
+    let tree = Document::parse(xml).unwrap();
+    let root = tree.root_element();
+    let end = root.first_child().unwrap().range().start;
+    assert_eq!(
+        attributes(&root, &xml[..end]),
+        [
+            ("schemaVersion".to_string(), "1.1".to_string()),
+            ("xmlns:xs".to_string(), "http://x".to_string()),
+            (
+                "xs:noNamespaceSchemaLocation".to_string(),
+                "a.xsd".to_string()
+            ),
+        ]
+    );
+    let field = root.first_element_child().unwrap();
+    assert_eq!(
+        attributes(&field, &xml[field.range()]),
+        [("derivedFrom".to_string(), "A".to_string())]
+    );
+}
@@ -0,0 +1,221 @@
+//! Positions of parsed elements in the source XML.
+//!
+//! Enabled by [`Config::source_spans`](crate::Config::source_spans).
+//! Elements are addressed by their path: names of `peripheral`, `cluster`, `register`, `field`,
+//! `enumeratedValues` and `enumeratedValue` elements joined with `.` as written in the file,
+//! before arrays are expanded (`"GPIOA.MODER.MODER%s"`).
+//! Registers of alternate groups are addressed as `NAME_GROUP` (`"TIMER0.CNT_BYTE"`),
+//! so they differ from the registers they alternate.
+//! Values of `enumeratedValues` without a name are addressed directly from their field
+//! (`"TIMER0.CR.MODE.ONESHOT"`).
+//! `interrupt`s are stored under the path of their peripheral.
+//! The `device` element itself has an empty path.
+//! Elements dropped in lenient mode, ignored unknown elements and peripherals
+//! rejected by the filter are not stored.
+
+use roxmltree::{Document, Node, NodeId, TextPos};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use super::diagnostic::get_name;
+use super::PeripheralFilter;
+
+/// Location of an element in the source XML
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// Byte range of the element including start and end tags.
+    /// Byte order mark at the start of the source is counted
+    pub range: Range<usize>,
+    /// Position of the start tag
+    pub start: TextPos,
+    /// Position after the end tag
+    pub end: TextPos,
+}
+
+impl SourceSpan {
+    fn new(tree: &Document, node: &Node, offset: usize) -> Self {
+        let range = node.range();
+        Self {
+            start: tree.text_pos_at(range.start),
+            end: tree.text_pos_at(range.end),
+            range: range.start + offset..range.end + offset,
+        }
+    }
+}
+
+/// Elements of the document which are not in the parsed device
+pub(crate) struct Skipped<'a> {
+    /// Elements dropped, defaulted or ignored by the parser
+    pub nodes: HashSet<NodeId>,
+    /// Filter of peripherals
+    pub filter: &'a PeripheralFilter,
+}
+
+impl Skipped<'_> {
+    fn contains(&self, node: &Node) -> bool {
+        self.nodes.contains(&node.id())
+            || (node.has_tag_name("peripheral")
+                && node.parent().is_some_and(|p| p.has_tag_name("peripherals"))
+                && !self.filter.matches_node(node))
+    }
+}
+
+struct Context<'a, 'input> {
+    tree: &'a Document<'input>,
+    offset: usize,
+    skipped: &'a Skipped<'a>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ElementSpans {
+    span: SourceSpan,
+    /// Spans of child elements which are not addressed by own path
+    children: Vec<(String, SourceSpan)>,
+}
+
+/// Name of the element in paths
+fn path_name(node: &Node) -> Option<String> {
+    let name = get_name(node)?;
+    let group = node
+        .children()
+        .find(|c| c.has_tag_name("alternateGroup"))
+        .and_then(|c| c.text());
+    match group {
+        Some(group) if node.has_tag_name("register") => Some(format!("{name}_{group}")),
+        _ => Some(name.to_string()),
+    }
+}
+
+/// Side table mapping element paths to their positions in the source XML
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceSpans {
+    elements: HashMap<String, ElementSpans>,
+}
+
+const NAMED: [&str; 6] = [
+    "peripheral",
+    "cluster",
+    "register",
+    "field",
+    "enumeratedValues",
+    "enumeratedValue",
+];
+
+impl SourceSpans {
+    /// Collects spans of all elements of the document
+    pub fn from_document(tree: &Document) -> Self {
+        let skipped = Skipped {
+            nodes: HashSet::new(),
+            filter: &PeripheralFilter::default(),
+        };
+        Self::from_parsed(tree, 0, &skipped)
+    }
+
+    /// Collects spans of elements which are not `skipped`.
+    /// `offset` is the length of the byte order mark trimmed before parsing
+    pub(crate) fn from_parsed(tree: &Document, offset: usize, skipped: &Skipped) -> Self {
+        let mut spans = Self::default();
+        let root = tree.root_element();
+        let ctx = Context {
+            tree,
+            offset,
+            skipped,
+        };
+        spans.add(&ctx, &root, String::new());
+        spans
+    }
+
+    fn add(&mut self, ctx: &Context, node: &Node, path: String) {
+        let tree = ctx.tree;
+        let mut children = Vec::new();
+        for child in node
+            .children()
+            .filter(|c| c.is_element() && !ctx.skipped.contains(c))
+        {
+            let tag = child.tag_name().name();
+            let name = path_name(&child).filter(|_| NAMED.contains(&tag));
+            match name {
+                Some(name) => {
+                    let path = if path.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{path}.{name}")
+                    };
+                    self.add(ctx, &child, path);
+                }
+                // Containers which don't have own name
+                None if matches!(tag, "peripherals" | "registers" | "fields") => {
+                    self.add_transparent(ctx, &child, &path)
+                }
+                None if tag == "enumeratedValues" => {
+                    children.push((tag.to_string(), SourceSpan::new(tree, &child, ctx.offset)));
+                    self.add_transparent(ctx, &child, &path);
+                }
+                None => children.push((tag.to_string(), SourceSpan::new(tree, &child, ctx.offset))),
+            }
+        }
+        // If names are duplicated, keep the first element
+        self.elements.entry(path).or_insert(ElementSpans {
+            span: SourceSpan::new(tree, node, ctx.offset),
+            children,
+        });
+    }
+
+    fn add_transparent(&mut self, ctx: &Context, node: &Node, path: &str) {
+        for child in node
+            .children()
+            .filter(|c| c.is_element() && !ctx.skipped.contains(c))
+        {
+            let Some(name) = path_name(&child) else {
+                continue;
+            };
+            let path = if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{path}.{name}")
+            };
+            self.add(ctx, &child, path);
+        }
+    }
+
+    /// Location of the element with `path`
+    pub fn get(&self, path: &str) -> Option<&SourceSpan> {
+        self.elements.get(path).map(|e| &e.span)
+    }
+
+    /// Location of the first `tag` child of the element with `path`,
+    /// for example `baseAddress` of a peripheral or `interrupt`
+    pub fn child(&self, path: &str, tag: &str) -> Option<&SourceSpan> {
+        let e = self.elements.get(path)?;
+        e.children.iter().find(|(t, _)| t == tag).map(|(_, s)| s)
+    }
+
+    /// Locations of all `tag` children of the element with `path`
+    pub fn children<'a>(
+        &'a self,
+        path: &str,
+        tag: &'a str,
+    ) -> impl Iterator<Item = &'a SourceSpan> + 'a {
+        self.elements
+            .get(path)
+            .into_iter()
+            .flat_map(|e| e.children.iter())
+            .filter(move |(t, _)| t == tag)
+            .map(|(_, s)| s)
+    }
+
+    /// Paths of all stored elements
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.elements.keys().map(String::as_str)
+    }
+
+    /// Number of stored elements
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if no elements were stored
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
@@ -0,0 +1,452 @@
+//! Parsing of large SVD files one peripheral at a time.
+//!
+//! [`parse_stream`] reads the device header (everything before `<peripherals>`) and returns
+//! an iterator that reads and parses peripherals one by one. Only the text of the current
+//! peripheral is kept in memory. Peripherals are returned as written in the file:
+//! arrays are not expanded and `derivedFrom` is not resolved.
+//! Positions in error messages are relative to the start of the peripheral.
+
+use anyhow::anyhow;
+use std::io::{self, Read};
+
+use super::diagnostic;
+use super::{
+    error_context, Config, DiagnosticKind, Parse, ParseDiagnostic, SVDError, ValidateLevel,
+};
+use crate::svd::{Device, Peripheral};
+
+const CHUNK: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    /// Start tag
+    Start,
+    /// Self-closing tag
+    Empty,
+    /// End tag
+    End,
+    /// Text, comment, processing instruction, etc.
+    Other,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token {
+    kind: Kind,
+    start: usize,
+    end: usize,
+}
+
+/// Splits XML read from `reader` on tags without building a tree
+struct Scanner<R> {
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> Scanner<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Reads next chunk. Returns `false` at the end of input
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK, 0);
+        let n = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(e);
+                }
+            }
+        };
+        self.buf.truncate(len + n);
+        self.eof = n == 0;
+        Ok(!self.eof)
+    }
+
+    /// Reads until at least `n` bytes after `pos` are available or input ends
+    fn ensure(&mut self, n: usize) -> io::Result<()> {
+        while self.buf.len() < self.pos + n && self.fill()? {}
+        Ok(())
+    }
+
+    /// Returns the position after the first occurrence of `pat` after `from`
+    fn find(&mut self, mut from: usize, pat: &[u8]) -> io::Result<Option<usize>> {
+        loop {
+            if let Some(i) = self.buf[from..].windows(pat.len()).position(|w| w == pat) {
+                return Ok(Some(from + i + pat.len()));
+            }
+            from = self.buf.len().saturating_sub(pat.len() - 1).max(from);
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Returns the position after the end of the tag starting at `from`
+    fn find_tag_end(&mut self, from: usize) -> io::Result<Option<usize>> {
+        let mut quote = None;
+        let mut i = from;
+        loop {
+            if i == self.buf.len() && !self.fill()? {
+                return Ok(None);
+            }
+            let c = self.buf[i];
+            i += 1;
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == b'"' || c == b'\'' => quote = Some(c),
+                None if c == b'>' => return Ok(Some(i)),
+                None => {}
+            }
+        }
+    }
+
+    fn next(&mut self) -> anyhow::Result<Option<Token>> {
+        self.ensure(9)?;
+        let start = self.pos;
+        if start == self.buf.len() {
+            return Ok(None);
+        }
+        let rest = &self.buf[start..];
+        let (kind, end) = if rest[0] != b'<' {
+            let end = self.find(start, b"<")?.map(|e| e - 1);
+            (Kind::Other, end.unwrap_or(self.buf.len()))
+        } else {
+            let (kind, end) = if rest.starts_with(b"<!--") {
+                (Kind::Other, self.find(start + 4, b"-->")?)
+            } else if rest.starts_with(b"<![CDATA[") {
+                (Kind::Other, self.find(start + 9, b"]]>")?)
+            } else if rest.starts_with(b"<?") {
+                (Kind::Other, self.find(start + 2, b"?>")?)
+            } else if rest.starts_with(b"<!") {
+                (Kind::Other, self.find(start + 2, b">")?)
+            } else if rest.starts_with(b"</") {
+                (Kind::End, self.find(start + 2, b">")?)
+            } else {
+                let end = self.find_tag_end(start + 1)?;
+                let kind = match end {
+                    Some(end) if self.buf[end - 2] == b'/' => Kind::Empty,
+                    _ => Kind::Start,
+                };
+                (kind, end)
+            };
+            let end = end.ok_or_else(|| anyhow!("Unexpected end of file"))?;
+            (kind, end)
+        };
+        self.pos = end;
+        Ok(Some(Token { kind, start, end }))
+    }
+
+    /// Name of the tag
+    fn name(&self, token: &Token) -> &[u8] {
+        let tag = &self.buf[token.start..token.end];
+        let tag = tag.strip_prefix(b"</").unwrap_or(&tag[1..]);
+        let len = tag
+            .iter()
+            .position(|c| c.is_ascii_whitespace() || *c == b'/' || *c == b'>')
+            .unwrap_or(tag.len());
+        &tag[..len]
+    }
+
+    /// Reads tokens until the end of element started by `token`.
+    /// Returns position after the end tag
+    fn skip_element(&mut self, token: &Token) -> anyhow::Result<usize> {
+        if token.kind != Kind::Start {
+            return Ok(token.end);
+        }
+        let mut depth = 1;
+        while let Some(t) = self.next()? {
+            match t.kind {
+                Kind::Start => depth += 1,
+                Kind::End => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(t.end);
+            }
+        }
+        Err(anyhow!("Unexpected end of file"))
+    }
+
+    /// Same as [`skip_element`](Self::skip_element), but also returns text
+    /// of the `name` and `groupName` children
+    fn skip_peripheral(&mut self, token: &Token) -> anyhow::Result<(usize, Selector)> {
+        let mut selector = Selector::default();
+        if token.kind != Kind::Start {
+            return Ok((token.end, selector));
+        }
+        let mut depth = 1;
+        // Start of text of `name` or `groupName` child
+        let mut text_start = None;
+        while let Some(t) = self.next()? {
+            match t.kind {
+                Kind::Start => {
+                    if depth == 1 && matches!(self.name(&t), b"name" | b"groupName") {
+                        text_start = Some(t.end);
+                    }
+                    depth += 1;
+                }
+                Kind::End => {
+                    depth -= 1;
+                    if let (1, Some(start)) = (depth, text_start.take()) {
+                        let text = unescape(self.text(start, t.start)?.trim());
+                        match self.name(&t) {
+                            b"name" => selector.name = Some(text),
+                            _ => selector.group = Some(text),
+                        }
+                    }
+                }
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok((t.end, selector));
+            }
+        }
+        Err(anyhow!("Unexpected end of file"))
+    }
+
+    fn text(&self, start: usize, end: usize) -> Result<&str, SVDError> {
+        std::str::from_utf8(&self.buf[start..end])
+            .map_err(|_| SVDError::InvalidEncoding("UTF-8".into()))
+    }
+
+    /// Forgets everything before current position
+    fn consume(&mut self) {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+    }
+}
+
+/// Name and group name of the peripheral, read without parsing it
+#[derive(Default)]
+struct Selector {
+    name: Option<String>,
+    group: Option<String>,
+}
+
+/// Replaces predefined XML entities
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Iterator over peripherals of the SVD file. See [module documentation](self)
+pub struct PeripheralStream<R> {
+    scanner: Scanner<R>,
+    config: Config,
+    device: Device,
+    /// Attributes of `device` with namespace declarations
+    device_attrs: String,
+    diagnostics: Vec<ParseDiagnostic>,
+    /// Number of elements parsed so far
+    elements: usize,
+    done: bool,
+}
+
+/// Reads the device header from UTF-8 encoded SVD file and
+/// returns the iterator over its peripherals
+pub fn parse_stream<R: Read>(reader: R, config: &Config) -> anyhow::Result<PeripheralStream<R>> {
+    let mut scanner = Scanner::new(reader);
+    scanner.ensure(3)?;
+    if scanner.buf.starts_with(b"\xef\xbb\xbf") {
+        scanner.pos = 3;
+    }
+    let device_start = loop {
+        let token = scanner
+            .next()?
+            .ok_or_else(|| SVDError::MissingTag("device".into()))?;
+        match token.kind {
+            Kind::Start if scanner.name(&token) == b"device" => break token,
+            Kind::Start | Kind::Empty | Kind::End => {
+                return Err(SVDError::NotExpectedTag("device".into()).into())
+            }
+            Kind::Other => {}
+        }
+    };
+    let device_attrs = scanner
+        .text(device_start.start + "<device".len(), device_start.end)?
+        .to_string();
+
+    // Read device children until `peripherals`
+    let mut header_end = None;
+    let mut done = true;
+    while let Some(token) = scanner.next()? {
+        match token.kind {
+            Kind::Start | Kind::Empty if scanner.name(&token) == b"peripherals" => {
+                header_end = Some(token.start);
+                done = token.kind == Kind::Empty;
+                break;
+            }
+            Kind::Start => {
+                scanner.skip_element(&token)?;
+            }
+            Kind::End => {
+                header_end = Some(token.end);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let header_end = header_end.ok_or_else(|| anyhow!("Unexpected end of file"))?;
+    let mut header = scanner.text(device_start.start, header_end)?.to_string();
+    if header_end < scanner.pos {
+        header.push_str("<peripherals></peripherals></device>");
+    }
+    let (device, elements) = parse_header(&header, config)?;
+    scanner.consume();
+
+    Ok(PeripheralStream {
+        scanner,
+        config: config.clone(),
+        device,
+        device_attrs,
+        diagnostics: Vec::new(),
+        elements,
+        done,
+    })
+}
+
+/// Parses the device without peripherals
+fn parse_header(xml: &str, config: &Config) -> anyhow::Result<(Device, usize)> {
+    let tree = config.limits.parse_document(xml, 0)?;
+    let elements = config
+        .limits
+        .check_tree(&tree.root(), 0, 0)
+        .map_err(|e| error_context(&tree, e))?;
+    let lvl = config.validate_level;
+    // Empty device is not valid
+    let device = Device::parse(
+        &tree.root_element(),
+        &config.clone().validate_level(ValidateLevel::Disabled),
+    )
+    .map_err(|e| error_context(&tree, e))?;
+    if let Some(cpu) = device.cpu.as_ref() {
+        cpu.validate(lvl)?;
+    }
+    device.default_register_properties.validate(lvl)?;
+    Ok((device, elements))
+}
+
+impl<R: Read> PeripheralStream<R> {
+    /// Device header. Its `peripherals` are empty
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Peripherals dropped and values defaulted so far when [`Config::lenient`] is set.
+    /// Positions are relative to the start of the peripheral
+    pub fn diagnostics(&self) -> &[ParseDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Parses remaining peripherals and returns the complete device
+    pub fn into_device(mut self) -> anyhow::Result<Device> {
+        let peripherals = (&mut self).collect::<anyhow::Result<Vec<_>>>()?;
+        let mut device = self.device;
+        device.peripherals = peripherals;
+        device.validate(self.config.validate_level)?;
+        Ok(device)
+    }
+
+    fn next_peripheral(&mut self) -> anyhow::Result<Option<Peripheral>> {
+        loop {
+            self.scanner.consume();
+            let Some(token) = self.scanner.next()? else {
+                return Err(anyhow!("Unexpected end of file"));
+            };
+            match token.kind {
+                Kind::Start | Kind::Empty if self.scanner.name(&token) == b"peripheral" => {
+                    let (end, selector) = self.scanner.skip_peripheral(&token)?;
+                    if !self.selected(&selector) {
+                        continue;
+                    }
+                    if let Some(p) = self.parse_peripheral(token.start, end)? {
+                        return Ok(Some(p));
+                    }
+                }
+                Kind::Start => {
+                    self.scanner.skip_element(&token)?;
+                }
+                Kind::End => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the peripheral is accepted by [`Config::peripheral_filter`].
+    /// `derivedFrom` is not resolved, so peripherals they derive from are not added
+    fn selected(&self, selector: &Selector) -> bool {
+        let name = selector.name.as_deref().unwrap_or_default();
+        self.config
+            .peripheral_filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(name, selector.group.as_deref()))
+    }
+
+    fn parse_peripheral(&mut self, start: usize, end: usize) -> anyhow::Result<Option<Peripheral>> {
+        // Keep namespaces declared in `device`
+        let xml = format!(
+            "<peripherals{}{}</peripherals>",
+            self.device_attrs,
+            self.scanner.text(start, end)?
+        );
+        let tree = self
+            .config
+            .limits
+            .parse_document(&xml, self.elements.saturating_sub(1))?;
+        // Wrapper stands for `peripherals`, which is counted in header
+        self.elements = self
+            .config
+            .limits
+            .check_tree(&tree.root(), self.elements.saturating_sub(1), 1)
+            .map_err(|e| error_context(&tree, e))?;
+        let Some(node) = tree.root_element().first_element_child() else {
+            return Ok(None);
+        };
+        let config = &self.config;
+        let (res, reports) = diagnostic::collect(|| {
+            let mark = diagnostic::mark();
+            match Peripheral::parse(&node, config) {
+                Err(e) if config.lenient => {
+                    diagnostic::report(mark, DiagnosticKind::Dropped, node.id(), e);
+                    Ok(None)
+                }
+                res => res.map(Some),
+            }
+        });
+        self.diagnostics.extend(diagnostic::resolve(&tree, reports));
+        res.map_err(|e| error_context(&tree, e))
+    }
+}
+
+impl<R: Read> Iterator for PeripheralStream<R> {
+    type Item = anyhow::Result<Peripheral>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_peripheral().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
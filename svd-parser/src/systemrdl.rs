@@ -0,0 +1,1160 @@
+//! Import of SystemRDL 2.0 register descriptions.
+//!
+//! The common subset used for register maps is supported: `addrmap`, `regfile`, `reg`
+//! and `field` definitions, named or anonymous, their instances and arrays with
+//! `@`, `+=` and `%=` addressing, property assignments with `default`, and `enum`s
+//! used by `encode`. Instances without address are placed with `regalign` rules.
+//!
+//! The top-level `addrmap` is the one not instantiated by others. Its `addrmap` instances
+//! become peripherals, registers placed directly in it form a peripheral named as the device.
+//! Nested `addrmap`s and `regfile`s become clusters.
+//!
+//! Properties and constructs without CMSIS-SVD equivalent, like `mem`, `signal`,
+//! parameters and dynamic assignments, are skipped and reported as
+//! [`DiagnosticKind::Ignored`] diagnostics.
+
+use std::rc::Rc;
+
+use roxmltree::TextPos;
+
+use super::{Config, DiagnosticKind, ParseDiagnostic, SVDError};
+use crate::svd::{
+    Access, ClusterInfo, Device, DimElement, EnumeratedValue, EnumeratedValues, FieldInfo,
+    MaybeArray, ModifiedWriteValues, Peripheral, PeripheralInfo, ReadAction, RegisterCluster,
+    RegisterInfo,
+};
+
+/// Parses SystemRDL source into a device.
+/// Also returns the list of constructs which CMSIS-SVD can't express
+pub fn parse_systemrdl(
+    src: &str,
+    config: &Config,
+) -> anyhow::Result<(Device, Vec<ParseDiagnostic>)> {
+    let tokens = lex(src)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        path: Vec::new(),
+        depth: 0,
+        max_depth: config.limits.max_depth.unwrap_or(MAX_DEPTH),
+        diagnostics: Vec::new(),
+    };
+    let root = parser.root()?;
+    let mut e = Elaborator {
+        config,
+        scopes: vec![&root],
+        path: Vec::new(),
+        diagnostics: parser.diagnostics,
+    };
+    let device = e.device(&root)?;
+    Ok((device, e.diagnostics))
+}
+
+fn error(pos: TextPos, msg: impl Into<String>) -> anyhow::Error {
+    anyhow::Error::from(SVDError::InvalidSystemRdl(msg.into())).context(format!("at {pos}"))
+}
+
+fn overflow(inst: &Instance) -> anyhow::Error {
+    error(inst.pos, format!("address of `{}` overflows", inst.name))
+}
+
+fn bits_overflow(inst: &Instance, bits: u64) -> anyhow::Error {
+    error(
+        inst.pos,
+        format!("field `{}` doesn't fit in {bits} bits", inst.name),
+    )
+}
+
+fn ignored(tag: &str, path: &[String], pos: TextPos) -> ParseDiagnostic {
+    ParseDiagnostic {
+        kind: DiagnosticKind::Ignored,
+        tag: tag.into(),
+        name: None,
+        path: path.join("."),
+        pos,
+        error: SVDError::NoSvdEquivalent(tag.into()),
+        error_pos: pos,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Ident(String),
+    Num(u64),
+    Str(String),
+    Punct(&'static str),
+    Eof,
+}
+
+struct Token {
+    tok: Tok,
+    pos: TextPos,
+}
+
+const PUNCTS: &[&str] = &[
+    "+=", "%=", "->", "{", "}", "[", "]", ":", ";", "=", "@", ",", ".", "#", "(", ")",
+];
+
+fn lex(src: &str) -> anyhow::Result<Vec<Token>> {
+    let chars = src.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let (mut i, mut row, mut col) = (0, 1, 1);
+    // Moves to `to`, updating position
+    let advance = |i: &mut usize, row: &mut u32, col: &mut u32, to: usize| {
+        while *i < to {
+            if chars[*i] == '\n' {
+                *row += 1;
+                *col = 1;
+            } else {
+                *col += 1;
+            }
+            *i += 1;
+        }
+    };
+    let find = |from: usize, pred: &dyn Fn(char) -> bool| {
+        (from..chars.len())
+            .find(|&j| pred(chars[j]))
+            .unwrap_or(chars.len())
+    };
+    while i < chars.len() {
+        let pos = TextPos::new(row, col);
+        let c = chars[i];
+        let rest = &chars[i..];
+        let end;
+        let tok;
+        if c.is_whitespace() {
+            let to = i + 1;
+            advance(&mut i, &mut row, &mut col, to);
+            continue;
+        } else if rest.starts_with(&['/', '/']) {
+            let to = find(i, &|c| c == '\n');
+            advance(&mut i, &mut row, &mut col, to);
+            continue;
+        } else if rest.starts_with(&['/', '*']) {
+            let close = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .ok_or_else(|| error(pos, "unterminated comment"))?;
+            advance(&mut i, &mut row, &mut col, close + 2);
+            continue;
+        } else if c == '"' {
+            let mut s = String::new();
+            let mut j = i + 1;
+            loop {
+                match chars.get(j) {
+                    None => return Err(error(pos, "unterminated string")),
+                    Some('"') => break,
+                    // `\"` is the only escape sequence
+                    Some('\\') if chars.get(j + 1) == Some(&'"') => {
+                        s.push('"');
+                        j += 2;
+                    }
+                    Some(&c) => {
+                        s.push(c);
+                        j += 1;
+                    }
+                }
+            }
+            end = j + 1;
+            tok = Tok::Str(s);
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '\\' {
+            let start = if c == '\\' { i + 1 } else { i };
+            end = find(start, &|c| !(c.is_ascii_alphanumeric() || c == '_'));
+            if end == start {
+                return Err(error(pos, "empty escaped identifier"));
+            }
+            tok = Tok::Ident(chars[start..end].iter().collect());
+        } else if c.is_ascii_digit() || c == '\'' {
+            end = find(i, &|c| {
+                !(c.is_ascii_alphanumeric() || c == '_' || c == '\'')
+            });
+            let text = chars[i..end].iter().collect::<String>();
+            let value = parse_number(&text)
+                .ok_or_else(|| error(pos, format!("invalid number `{text}`")))?;
+            tok = Tok::Num(value);
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|p| rest.iter().zip(p.chars()).filter(|(a, b)| *a == b).count() == p.len())
+                .ok_or_else(|| error(pos, format!("unexpected character `{c}`")))?;
+            end = i + punct.len();
+            tok = Tok::Punct(punct);
+        }
+        tokens.push(Token { tok, pos });
+        advance(&mut i, &mut row, &mut col, end);
+    }
+    tokens.push(Token {
+        tok: Tok::Eof,
+        pos: TextPos::new(row, col),
+    });
+    Ok(tokens)
+}
+
+/// Parses Verilog style (`4'hF`, `'d10`) or C style (`0xF`, `15`) number
+fn parse_number(s: &str) -> Option<u64> {
+    let s = s.replace('_', "");
+    if let Some((width, literal)) = s.split_once('\'') {
+        if !width.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let mut chars = literal.chars();
+        let radix = match chars.next()?.to_ascii_lowercase() {
+            'h' => 16,
+            'd' => 10,
+            'o' => 8,
+            'b' => 2,
+            _ => return None,
+        };
+        return u64::from_str_radix(chars.as_str(), radix).ok();
+    }
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Addrmap,
+    Regfile,
+    Reg,
+    Field,
+}
+
+impl Kind {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "addrmap" => Some(Self::Addrmap),
+            "regfile" => Some(Self::Regfile),
+            "reg" => Some(Self::Reg),
+            "field" => Some(Self::Field),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+    Bool(bool),
+    Num(u64),
+    Str(String),
+    Ident(String),
+}
+
+struct Prop {
+    name: String,
+    value: Value,
+    pos: TextPos,
+}
+
+struct Enum {
+    name: String,
+    /// Name, value and description of entries
+    entries: Vec<(String, u64, Option<String>)>,
+}
+
+/// Component definition
+struct Component {
+    kind: Kind,
+    name: Option<String>,
+    pos: TextPos,
+    props: Vec<Prop>,
+    defaults: Vec<Prop>,
+    types: Vec<Rc<Component>>,
+    enums: Vec<Enum>,
+    instances: Vec<Instance>,
+}
+
+impl Component {
+    fn new(kind: Kind, name: Option<String>, pos: TextPos) -> Self {
+        Self {
+            kind,
+            name,
+            pos,
+            props: Vec::new(),
+            defaults: Vec::new(),
+            types: Vec::new(),
+            enums: Vec::new(),
+            instances: Vec::new(),
+        }
+    }
+
+    fn prop(&self, name: &str) -> Option<&Prop> {
+        self.props.iter().rev().find(|p| p.name == name)
+    }
+}
+
+enum TypeRef {
+    Anonymous(Rc<Component>),
+    Named(String),
+}
+
+/// Content of `[...]` after instance name
+enum Bracket {
+    Single(u64),
+    Range(u64, u64),
+}
+
+struct Instance {
+    ty: TypeRef,
+    name: String,
+    bracket: Option<Bracket>,
+    reset: Option<u64>,
+    addr: Option<u64>,
+    stride: Option<u64>,
+    align: Option<u64>,
+    pos: TextPos,
+}
+
+/// Nesting depth of component definitions if [`Limits::max_depth`](crate::Limits::max_depth)
+/// is not set. Definitions are parsed recursively, so deeper input would overflow the stack
+const MAX_DEPTH: usize = 256;
+
+/// Unsupported component types and statements
+const UNSUPPORTED: &[&str] = &["mem", "signal", "constraint", "struct", "property", "alias"];
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Names of enclosing definitions
+    path: Vec<String>,
+    /// Number of enclosing definitions
+    depth: usize,
+    max_depth: usize,
+    diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.pos].tok
+    }
+
+    fn peek_at(&self, n: usize) -> &Tok {
+        let i = (self.pos + n).min(self.tokens.len() - 1);
+        &self.tokens[i].tok
+    }
+
+    fn here(&self) -> TextPos {
+        self.tokens[self.pos].pos
+    }
+
+    fn next(&mut self) -> Tok {
+        let tok = self.tokens[self.pos].tok.clone();
+        if tok != Tok::Eof {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn is_punct(&self, p: &str) -> bool {
+        matches!(self.peek(), Tok::Punct(q) if *q == p)
+    }
+
+    fn eat(&mut self, p: &str) -> bool {
+        let found = self.is_punct(p);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, p: &str) -> anyhow::Result<()> {
+        if self.eat(p) {
+            Ok(())
+        } else {
+            Err(error(self.here(), format!("expected `{p}`")))
+        }
+    }
+
+    fn ident(&mut self) -> anyhow::Result<String> {
+        match self.next() {
+            Tok::Ident(s) => Ok(s),
+            _ => Err(error(self.tokens[self.pos - 1].pos, "expected identifier")),
+        }
+    }
+
+    fn number(&mut self) -> anyhow::Result<u64> {
+        match self.next() {
+            Tok::Num(n) => Ok(n),
+            _ => Err(error(self.tokens[self.pos - 1].pos, "expected number")),
+        }
+    }
+
+    /// Skips to the end of statement
+    fn skip_statement(&mut self) {
+        let mut depth = 0usize;
+        loop {
+            match self.next() {
+                Tok::Eof => return,
+                Tok::Punct("{") | Tok::Punct("(") => depth += 1,
+                Tok::Punct("}") | Tok::Punct(")") => depth = depth.saturating_sub(1),
+                Tok::Punct(";") if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips parenthesized parameters after `#`
+    fn skip_parameters(&mut self) -> anyhow::Result<()> {
+        let pos = self.here();
+        self.expect("#")?;
+        self.diagnostics.push(ignored("parameter", &self.path, pos));
+        self.expect("(")?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Tok::Eof => return Err(error(pos, "unterminated parameters")),
+                Tok::Punct("(") => depth += 1,
+                Tok::Punct(")") => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn root(&mut self) -> anyhow::Result<Component> {
+        let mut root = Component::new(Kind::Addrmap, None, self.here());
+        while *self.peek() != Tok::Eof {
+            self.statement(&mut root)?;
+        }
+        Ok(root)
+    }
+
+    fn statement(&mut self, comp: &mut Component) -> anyhow::Result<()> {
+        while matches!(self.peek(), Tok::Ident(s) if s == "external" || s == "internal") {
+            self.next();
+        }
+        let pos = self.here();
+        let word = match self.peek() {
+            Tok::Ident(s) => s.clone(),
+            Tok::Punct(";") => {
+                self.next();
+                return Ok(());
+            }
+            _ => return Err(error(pos, "expected statement")),
+        };
+        if let Some(kind) = Kind::parse(&word) {
+            self.next();
+            return self.definition(kind, pos, comp);
+        }
+        if UNSUPPORTED.contains(&word.as_str()) {
+            self.diagnostics.push(ignored(&word, &self.path, pos));
+            self.skip_statement();
+            return Ok(());
+        }
+        match word.as_str() {
+            "enum" => {
+                self.next();
+                let e = self.enumeration()?;
+                comp.enums.push(e);
+            }
+            "default" => {
+                self.next();
+                let prop = self.property()?;
+                comp.defaults.push(prop);
+            }
+            _ => match self.peek_at(1) {
+                Tok::Punct("=") | Tok::Punct(";") => {
+                    let prop = self.property()?;
+                    comp.props.push(prop);
+                }
+                Tok::Ident(_) | Tok::Punct("#") => {
+                    self.next();
+                    if self.is_punct("#") {
+                        self.skip_parameters()?;
+                    }
+                    let ty = TypeRef::Named(word);
+                    self.instances(ty, comp)?;
+                }
+                _ => {
+                    // Dynamic assignment like `inst->prop = value`
+                    self.diagnostics
+                        .push(ignored("dynamic assignment", &self.path, pos));
+                    self.skip_statement();
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn definition(
+        &mut self,
+        kind: Kind,
+        pos: TextPos,
+        parent: &mut Component,
+    ) -> anyhow::Result<()> {
+        let name = match self.peek() {
+            Tok::Ident(_) => Some(self.ident()?),
+            _ => None,
+        };
+        if self.is_punct("#") {
+            self.skip_parameters()?;
+        }
+        let mut comp = Component::new(kind, name.clone(), pos);
+        self.expect("{")?;
+        if self.depth == self.max_depth {
+            return Err(
+                anyhow::Error::from(SVDError::TooDeep(self.max_depth)).context(format!("at {pos}"))
+            );
+        }
+        self.depth += 1;
+        // Instance names of anonymous definitions follow the body
+        self.path.extend(name.clone());
+        while !self.eat("}") {
+            if *self.peek() == Tok::Eof {
+                return Err(error(pos, "unterminated component"));
+            }
+            self.statement(&mut comp)?;
+        }
+        self.depth -= 1;
+        if name.is_some() {
+            self.path.pop();
+        }
+        let comp = Rc::new(comp);
+        match name {
+            Some(name) => {
+                parent.types.push(comp);
+                if !self.eat(";") {
+                    self.instances(TypeRef::Named(name), parent)?;
+                }
+            }
+            None => {
+                if self.is_punct(";") {
+                    return Err(error(pos, "anonymous component without instance"));
+                }
+                self.instances(TypeRef::Anonymous(comp), parent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Comma separated instances ending with `;`
+    fn instances(&mut self, ty: TypeRef, parent: &mut Component) -> anyhow::Result<()> {
+        loop {
+            let pos = self.here();
+            let name = self.ident()?;
+            let mut bracket = None;
+            if self.eat("[") {
+                let first = self.number()?;
+                bracket = Some(if self.eat(":") {
+                    Bracket::Range(first, self.number()?)
+                } else {
+                    Bracket::Single(first)
+                });
+                self.expect("]")?;
+                if self.is_punct("[") {
+                    return Err(error(
+                        self.here(),
+                        "multidimensional arrays are not supported",
+                    ));
+                }
+            }
+            let mut inst = Instance {
+                ty: match &ty {
+                    TypeRef::Anonymous(c) => TypeRef::Anonymous(c.clone()),
+                    TypeRef::Named(n) => TypeRef::Named(n.clone()),
+                },
+                name,
+                bracket,
+                reset: None,
+                addr: None,
+                stride: None,
+                align: None,
+                pos,
+            };
+            if self.eat("=") {
+                inst.reset = Some(self.number()?);
+            }
+            if self.eat("@") {
+                inst.addr = Some(self.number()?);
+            }
+            if self.eat("+=") {
+                inst.stride = Some(self.number()?);
+            }
+            if self.eat("%=") {
+                inst.align = Some(self.number()?);
+            }
+            parent.instances.push(inst);
+            if self.eat(";") {
+                return Ok(());
+            }
+            if !self.eat(",") {
+                return Err(error(self.here(), "expected `,` or `;`"));
+            }
+        }
+    }
+
+    /// `name = value;` or `name;` for boolean properties
+    fn property(&mut self) -> anyhow::Result<Prop> {
+        let pos = self.here();
+        let name = self.ident()?;
+        let value = if self.eat("=") {
+            match self.next() {
+                Tok::Num(n) => Value::Num(n),
+                Tok::Str(s) => Value::Str(s),
+                Tok::Ident(s) if s == "true" => Value::Bool(true),
+                Tok::Ident(s) if s == "false" => Value::Bool(false),
+                Tok::Ident(s) => Value::Ident(s),
+                _ => return Err(error(pos, format!("invalid value of `{name}`"))),
+            }
+        } else {
+            Value::Bool(true)
+        };
+        self.expect(";")?;
+        Ok(Prop { name, value, pos })
+    }
+
+    fn enumeration(&mut self) -> anyhow::Result<Enum> {
+        let name = self.ident()?;
+        self.expect("{")?;
+        let mut entries = Vec::new();
+        let mut next = 0;
+        while !self.eat("}") {
+            let entry = self.ident()?;
+            let value = if self.eat("=") { self.number()? } else { next };
+            next = value + 1;
+            let mut desc = None;
+            if self.eat("{") {
+                while !self.eat("}") {
+                    let prop = self.property()?;
+                    if let ("desc", Value::Str(s)) = (prop.name.as_str(), prop.value) {
+                        desc = Some(s);
+                    }
+                }
+            }
+            self.expect(";")?;
+            entries.push((entry, value, desc));
+        }
+        self.expect(";")?;
+        Ok(Enum { name, entries })
+    }
+}
+
+/// Properties without CMSIS-SVD equivalent which are expected and not reported
+const ACCEPTED: &[&str] = &["hw", "accesswidth", "addressing", "lsb0", "fieldwidth"];
+
+struct Elaborator<'a> {
+    config: &'a Config,
+    /// Components whose definitions and defaults are visible
+    scopes: Vec<&'a Component>,
+    /// Names of instances
+    path: Vec<String>,
+    diagnostics: Vec<ParseDiagnostic>,
+}
+
+/// Elaborated instance
+struct Placed<T> {
+    item: T,
+    size: u64,
+}
+
+impl<'a> Elaborator<'a> {
+    fn lookup(&self, name: &str, pos: TextPos) -> anyhow::Result<&'a Component> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|s| &s.types)
+            .find(|t| t.name.as_deref() == Some(name))
+            .map(|t| &**t)
+            .ok_or_else(|| error(pos, format!("unknown component `{name}`")))
+    }
+
+    fn component(&self, inst: &'a Instance) -> anyhow::Result<&'a Component> {
+        match &inst.ty {
+            TypeRef::Anonymous(c) => Ok(c),
+            TypeRef::Named(name) => self.lookup(name, inst.pos),
+        }
+    }
+
+    /// Property assigned in the component or by `default` in enclosing ones
+    fn prop(&self, comp: &'a Component, name: &str) -> Option<&'a Prop> {
+        comp.prop(name).or_else(|| {
+            self.scopes
+                .iter()
+                .rev()
+                .find_map(|s| s.defaults.iter().rev().find(|p| p.name == name))
+        })
+    }
+
+    fn string(&self, comp: &'a Component, name: &str) -> Option<String> {
+        match self.prop(comp, name).map(|p| &p.value) {
+            Some(Value::Str(s)) => Some(s.split_whitespace().collect::<Vec<_>>().join(" ")),
+            _ => None,
+        }
+    }
+
+    fn keyword(
+        &self,
+        comp: &'a Component,
+        name: &str,
+    ) -> anyhow::Result<Option<(&'a str, TextPos)>> {
+        match self.prop(comp, name) {
+            Some(Prop {
+                value: Value::Ident(s),
+                pos,
+                ..
+            }) => Ok(Some((s, *pos))),
+            Some(p) => Err(error(p.pos, format!("invalid value of `{name}`"))),
+            None => Ok(None),
+        }
+    }
+
+    fn number(&self, comp: &'a Component, name: &str) -> anyhow::Result<Option<u64>> {
+        match self.prop(comp, name) {
+            Some(Prop {
+                value: Value::Num(n),
+                ..
+            }) => Ok(Some(*n)),
+            Some(p) => Err(error(p.pos, format!("invalid value of `{name}`"))),
+            None => Ok(None),
+        }
+    }
+
+    /// Reports properties of `comp` which are not in `known`
+    fn check_props(&mut self, comp: &Component, known: &[&str]) {
+        for p in &comp.props {
+            if !known.contains(&p.name.as_str()) && !ACCEPTED.contains(&p.name.as_str()) {
+                self.diagnostics.push(ignored(&p.name, &self.path, p.pos));
+            }
+        }
+    }
+
+    fn device(&mut self, root: &'a Component) -> anyhow::Result<Device> {
+        let (top, name) = self.top(root)?;
+        self.scopes.push(top);
+        self.check_props(top, &["name", "desc"]);
+        let mut peripherals = Vec::new();
+        let mut registers = Vec::new();
+        let mut next = 0;
+        for inst in &top.instances {
+            let comp = self.component(inst)?;
+            match comp.kind {
+                Kind::Addrmap => {
+                    let p = self.peripheral(inst, comp, &mut next)?;
+                    peripherals.push(p);
+                }
+                Kind::Reg | Kind::Regfile => {
+                    let rc = self.register_cluster(inst, comp, &mut next)?;
+                    registers.push(rc);
+                }
+                Kind::Field => {
+                    self.diagnostics
+                        .push(ignored("field", &self.path, inst.pos));
+                }
+            }
+        }
+        if !registers.is_empty() {
+            let p = PeripheralInfo::builder()
+                .name(name.clone())
+                .description(self.string(top, "desc"))
+                .base_address(0)
+                .registers(Some(registers))
+                .build(self.config.validate_level)
+                .map_err(|e| error(top.pos, SVDError::from(e).to_string()))?;
+            peripherals.insert(0, p.single());
+        }
+        self.scopes.pop();
+        Device::builder()
+            .name(name)
+            .description(self.string(top, "desc").unwrap_or_default())
+            .peripherals(peripherals)
+            .build(self.config.validate_level)
+            .map_err(|e| error(top.pos, SVDError::from(e).to_string()))
+    }
+
+    /// Top-level `addrmap` and its name
+    fn top(&self, root: &'a Component) -> anyhow::Result<(&'a Component, String)> {
+        for inst in &root.instances {
+            let comp = self.component(inst)?;
+            if comp.kind == Kind::Addrmap {
+                return Ok((comp, inst.name.clone()));
+            }
+        }
+        let mut used = Vec::new();
+        collect_used(root, &mut used);
+        root.types
+            .iter()
+            .rev()
+            .find(|t| {
+                t.kind == Kind::Addrmap && !used.contains(&t.name.as_deref().unwrap_or_default())
+            })
+            .map(|t| (&**t, t.name.clone().unwrap_or_default()))
+            .ok_or_else(|| error(root.pos, "no top-level addrmap"))
+    }
+
+    /// Address of instance and size of its element
+    fn place(&self, inst: &Instance, size: u64, next: &mut u64) -> anyhow::Result<u64> {
+        let dim = match inst.bracket {
+            Some(Bracket::Single(dim)) => dim,
+            _ => 1,
+        };
+        let stride = inst.stride.unwrap_or(size);
+        let addr = match inst.addr {
+            Some(addr) => addr,
+            None => {
+                let align = match inst.align {
+                    Some(align) => align,
+                    None => size
+                        .checked_next_power_of_two()
+                        .ok_or_else(|| overflow(inst))?,
+                };
+                let align = align.max(1);
+                next.div_ceil(align)
+                    .checked_mul(align)
+                    .ok_or_else(|| overflow(inst))?
+            }
+        };
+        let end = stride
+            .checked_mul(dim.saturating_sub(1))
+            .and_then(|offset| offset.checked_add(addr))
+            .and_then(|end| end.checked_add(size))
+            .ok_or_else(|| overflow(inst))?;
+        *next = (*next).max(end);
+        Ok(addr)
+    }
+
+    fn dim(&self, inst: &Instance, size: u64) -> anyhow::Result<Option<DimElement>> {
+        match inst.bracket {
+            None => Ok(None),
+            Some(Bracket::Single(dim)) => DimElement::builder()
+                .dim(dim as u32)
+                .dim_increment(
+                    u32::try_from(inst.stride.unwrap_or(size)).map_err(|_| overflow(inst))?,
+                )
+                .build(self.config.validate_level)
+                .map(Some)
+                .map_err(|e| error(inst.pos, SVDError::from(e).to_string())),
+            Some(Bracket::Range(..)) => Err(error(inst.pos, "bit range on non-field instance")),
+        }
+    }
+
+    fn peripheral(
+        &mut self,
+        inst: &'a Instance,
+        comp: &'a Component,
+        next: &mut u64,
+    ) -> anyhow::Result<Peripheral> {
+        let children = self.children(inst, comp)?;
+        let addr = self.place(inst, children.size, next)?;
+        let dim = self.dim(inst, children.size)?;
+        let info = PeripheralInfo::builder()
+            .name(array_name(&inst.name, &dim))
+            .display_name(self.string(comp, "name"))
+            .description(self.string(comp, "desc"))
+            .base_address(addr)
+            .registers((!children.item.is_empty()).then_some(children.item))
+            .build(self.config.validate_level)
+            .map_err(|e| error(inst.pos, SVDError::from(e).to_string()))?;
+        Ok(maybe_array(info, dim))
+    }
+
+    /// Registers and clusters in `comp`, and its size
+    fn children(
+        &mut self,
+        inst: &Instance,
+        comp: &'a Component,
+    ) -> anyhow::Result<Placed<Vec<RegisterCluster>>> {
+        self.path.push(inst.name.clone());
+        self.check_props(comp, &["name", "desc"]);
+        self.scopes.push(comp);
+        let mut children = Vec::new();
+        let mut next = 0;
+        for inst in &comp.instances {
+            let child = self.component(inst)?;
+            if child.kind == Kind::Field {
+                self.diagnostics
+                    .push(ignored("field", &self.path, inst.pos));
+                continue;
+            }
+            children.push(self.register_cluster(inst, child, &mut next)?);
+        }
+        self.scopes.pop();
+        self.path.pop();
+        Ok(Placed {
+            item: children,
+            size: next,
+        })
+    }
+
+    fn register_cluster(
+        &mut self,
+        inst: &'a Instance,
+        comp: &'a Component,
+        next: &mut u64,
+    ) -> anyhow::Result<RegisterCluster> {
+        if comp.kind == Kind::Reg {
+            let reg = self.register(inst, comp)?;
+            let addr = self.place(inst, reg.size, next)?;
+            let dim = self.dim(inst, reg.size)?;
+            let mut info = reg.item;
+            info.name = array_name(&inst.name, &dim);
+            info.address_offset = u32::try_from(addr).map_err(|_| overflow(inst))?;
+            return Ok(maybe_array(info, dim).into());
+        }
+        let children = self.children(inst, comp)?;
+        let addr = self.place(inst, children.size, next)?;
+        let dim = self.dim(inst, children.size)?;
+        let info = ClusterInfo::builder()
+            .name(array_name(&inst.name, &dim))
+            .description(self.string(comp, "desc"))
+            .address_offset(u32::try_from(addr).map_err(|_| overflow(inst))?)
+            .children(children.item)
+            .build(self.config.validate_level)
+            .map_err(|e| error(inst.pos, SVDError::from(e).to_string()))?;
+        Ok(maybe_array(info, dim).into())
+    }
+
+    fn register(
+        &mut self,
+        inst: &'a Instance,
+        comp: &'a Component,
+    ) -> anyhow::Result<Placed<RegisterInfo>> {
+        self.path.push(inst.name.clone());
+        self.check_props(comp, &["name", "desc", "regwidth"]);
+        self.scopes.push(comp);
+        let width = self.number(comp, "regwidth")?;
+        let size = width.unwrap_or(32);
+        // Reset value is combined in 64 bits
+        let bits = size.min(64);
+        let mut fields = Vec::new();
+        let mut reset = None;
+        let mut next_bit = 0;
+        for finst in &comp.instances {
+            let fcomp = self.component(finst)?;
+            if fcomp.kind != Kind::Field {
+                self.diagnostics
+                    .push(ignored("register content", &self.path, finst.pos));
+                continue;
+            }
+            let (field, field_reset) = self.field(finst, fcomp, &mut next_bit, bits)?;
+            if let Some(value) = field_reset {
+                let value = value
+                    .checked_shl(field.bit_range.offset)
+                    .ok_or_else(|| bits_overflow(finst, bits))?;
+                reset = Some(reset.unwrap_or(0) | value);
+            }
+            fields.push(field);
+        }
+        self.scopes.pop();
+        self.path.pop();
+
+        let mut builder = RegisterInfo::builder()
+            .name(inst.name.clone())
+            .display_name(self.string(comp, "name"))
+            .description(self.string(comp, "desc"))
+            .address_offset(0)
+            .size(
+                width
+                    .map(u32::try_from)
+                    .transpose()
+                    .map_err(|_| error(inst.pos, "`regwidth` is too large"))?,
+            )
+            .reset_value(reset);
+        match fields.as_mut_slice() {
+            // Field which stands for the whole register
+            [f] if f.name == inst.name
+                && f.bit_range.offset == 0
+                && u64::from(f.bit_range.width) == size
+                && f.enumerated_values.is_empty() =>
+            {
+                builder = builder
+                    .access(f.access)
+                    .modified_write_values(f.modified_write_values)
+                    .read_action(f.read_action);
+            }
+            fields => {
+                // Access shared by all fields is set on the register
+                let access = fields[0].access;
+                if fields.iter().all(|f| f.access == access) {
+                    for f in fields.iter_mut() {
+                        f.access = None;
+                    }
+                    builder = builder.access(access);
+                }
+                let fields = fields.iter().cloned().map(FieldInfo::single).collect();
+                builder = builder.fields(Some(fields));
+            }
+        }
+        let info = builder
+            .build(self.config.validate_level)
+            .map_err(|e| error(inst.pos, SVDError::from(e).to_string()))?;
+        Ok(Placed {
+            item: info,
+            size: size.div_ceil(8),
+        })
+    }
+
+    /// Field placed at `next_bit` if it has no position, which must fit in `bits`
+    fn field(
+        &mut self,
+        inst: &'a Instance,
+        comp: &'a Component,
+        next_bit: &mut u32,
+        bits: u64,
+    ) -> anyhow::Result<(FieldInfo, Option<u64>)> {
+        self.path.push(inst.name.clone());
+        self.check_props(
+            comp,
+            &[
+                "name", "desc", "sw", "onread", "onwrite", "reset", "encode", "rclr", "rset",
+                "woclr", "woset",
+            ],
+        );
+        if inst.stride.is_some() || inst.align.is_some() {
+            self.diagnostics
+                .push(ignored("field array", &self.path, inst.pos));
+        }
+        let next = inst.addr.unwrap_or(u64::from(*next_bit));
+        let (lsb, width) = match inst.bracket {
+            Some(Bracket::Range(a, b)) => (a.min(b), (a.max(b) - a.min(b)).checked_add(1)),
+            Some(Bracket::Single(width)) => (next, Some(width)),
+            None => (next, Some(self.number(comp, "fieldwidth")?.unwrap_or(1))),
+        };
+        let end = width
+            .and_then(|width| lsb.checked_add(width))
+            .filter(|end| *end <= bits)
+            .ok_or_else(|| bits_overflow(inst, bits))?;
+        let lsb = u32::try_from(lsb).map_err(|_| bits_overflow(inst, bits))?;
+        let width = u32::try_from(end - u64::from(lsb)).map_err(|_| bits_overflow(inst, bits))?;
+        *next_bit = u32::try_from(end).map_err(|_| bits_overflow(inst, bits))?;
+
+        let access = match self.keyword(comp, "sw")? {
+            None => Some(Access::ReadWrite),
+            Some((sw, pos)) => match sw {
+                "r" => Some(Access::ReadOnly),
+                "w" => Some(Access::WriteOnly),
+                "rw" | "wr" => Some(Access::ReadWrite),
+                "w1" => Some(Access::WriteOnce),
+                "rw1" => Some(Access::ReadWriteOnce),
+                "na" => None,
+                _ => return Err(error(pos, format!("invalid sw access `{sw}`"))),
+            },
+        };
+        let mut read_action = match self.keyword(comp, "onread")? {
+            None => None,
+            Some((onread, pos)) => Some(match onread {
+                "rclr" => ReadAction::Clear,
+                "rset" => ReadAction::Set,
+                "ruser" => ReadAction::Modify,
+                _ => return Err(error(pos, format!("invalid onread `{onread}`"))),
+            }),
+        };
+        let mut mwv = match self.keyword(comp, "onwrite")? {
+            None => None,
+            Some((onwrite, pos)) => Some(match onwrite {
+                "woclr" => ModifiedWriteValues::OneToClear,
+                "woset" => ModifiedWriteValues::OneToSet,
+                "wot" => ModifiedWriteValues::OneToToggle,
+                "wzc" => ModifiedWriteValues::ZeroToClear,
+                "wzs" => ModifiedWriteValues::ZeroToSet,
+                "wzt" => ModifiedWriteValues::ZeroToToggle,
+                "wclr" => ModifiedWriteValues::Clear,
+                "wset" => ModifiedWriteValues::Set,
+                "wuser" => ModifiedWriteValues::Modify,
+                _ => return Err(error(pos, format!("invalid onwrite `{onwrite}`"))),
+            }),
+        };
+        // SystemRDL 1.0 style boolean properties
+        let flag = |name| {
+            matches!(
+                self.prop(comp, name).map(|p| &p.value),
+                Some(Value::Bool(true))
+            )
+        };
+        if flag("rclr") {
+            read_action = Some(ReadAction::Clear);
+        }
+        if flag("rset") {
+            read_action = Some(ReadAction::Set);
+        }
+        if flag("woclr") {
+            mwv = Some(ModifiedWriteValues::OneToClear);
+        }
+        if flag("woset") {
+            mwv = Some(ModifiedWriteValues::OneToSet);
+        }
+        let reset = match inst.reset {
+            Some(reset) => Some(reset),
+            None => self.number(comp, "reset")?,
+        };
+
+        let mut enumerated_values = Vec::new();
+        if let Some((name, pos)) = self.keyword(comp, "encode")? {
+            let e = self
+                .scopes
+                .iter()
+                .chain([&comp])
+                .rev()
+                .flat_map(|s| &s.enums)
+                .find(|e| e.name == name)
+                .ok_or_else(|| error(pos, format!("unknown enum `{name}`")))?;
+            if !self.config.ignore_enums {
+                let mut values = Vec::new();
+                for (name, value, desc) in &e.entries {
+                    values.push(
+                        EnumeratedValue::builder()
+                            .name(name.clone())
+                            .description(desc.clone())
+                            .value(Some(*value))
+                            .build(self.config.validate_level)
+                            .map_err(|e| error(pos, SVDError::from(e).to_string()))?,
+                    );
+                }
+                enumerated_values.push(
+                    EnumeratedValues::builder()
+                        .values(values)
+                        .build(self.config.validate_level)
+                        .map_err(|e| error(pos, SVDError::from(e).to_string()))?,
+                );
+            }
+        }
+        self.path.pop();
+
+        let field = FieldInfo::builder()
+            .name(inst.name.clone())
+            .description(self.string(comp, "desc"))
+            .bit_range(crate::svd::BitRange::from_offset_width(lsb, width))
+            .access(access)
+            .modified_write_values(mwv)
+            .read_action(read_action)
+            .enumerated_values(enumerated_values)
+            .build(self.config.validate_level)
+            .map_err(|e| error(inst.pos, SVDError::from(e).to_string()))?;
+        Ok((field, reset))
+    }
+}
+
+/// Names of types instantiated anywhere in `comp`
+fn collect_used<'a>(comp: &'a Component, used: &mut Vec<&'a str>) {
+    for inst in &comp.instances {
+        match &inst.ty {
+            TypeRef::Named(name) => used.push(name),
+            TypeRef::Anonymous(c) => collect_used(c, used),
+        }
+    }
+    for t in &comp.types {
+        collect_used(t, used);
+    }
+}
+
+fn array_name(name: &str, dim: &Option<DimElement>) -> String {
+    match dim {
+        Some(_) => format!("{name}[%s]"),
+        None => name.into(),
+    }
+}
+
+fn maybe_array<T>(info: T, dim: Option<DimElement>) -> MaybeArray<T> {
+    match dim {
+        Some(dim) => MaybeArray::Array(info, dim),
+        None => MaybeArray::Single(info),
+    }
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(parse_number("4'hF"), Some(15));
+    assert_eq!(parse_number("'b1_0"), Some(2));
+    assert_eq!(parse_number("0x40"), Some(0x40));
+    assert_eq!(parse_number("12"), Some(12));
+    assert_eq!(parse_number("4'q1"), None);
+}
@@ -6,8 +6,11 @@ impl Parse for WriteConstraint {
     type Error = SVDErrorAt;
     type Config = Config;
 
-    fn parse(tree: &Node, _config: &Self::Config) -> Result<Self, Self::Error> {
-        let child = tree.first_element_child().unwrap();
+    fn parse(tree: &Node, config: &Self::Config) -> Result<Self, Self::Error> {
+        check_children(tree, config)?;
+        let child = tree
+            .first_element_child()
+            .ok_or_else(|| SVDError::EmptyTag("writeConstraint".into()).at(tree.id()))?;
         if child.next_sibling_element().is_some() {
             return Err(SVDError::MoreThanOneWriteConstraint.at(tree.id()));
         }
@@ -18,8 +21,11 @@ impl Parse for WriteConstraint {
             "useEnumeratedValues" => tree
                 .get_child_bool(field)
                 .map(WriteConstraint::UseEnumeratedValues),
-            "range" => WriteConstraintRange::parse(&tree.get_child_elem(field)?, &())
-                .map(WriteConstraint::Range),
+            "range" => {
+                let range = tree.get_child_elem(field)?;
+                check_children(&range, config)?;
+                WriteConstraintRange::parse(&range, &()).map(WriteConstraint::Range)
+            }
             _ => Err(SVDError::UnknownWriteConstraint.at(tree.id())),
         }
     }
@@ -1,7 +1,7 @@
 /// Defines access rights for fields on the device, though it may be specified at a
 /// higher level than individual fields.
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum Access {
     /// Read access is permitted. Write operations have an undefined effect.
     #[cfg_attr(feature = "serde", serde(rename = "read-only"))]
@@ -9,6 +9,7 @@ pub enum Access {
 
     /// Read and write accesses are permitted.
     #[cfg_attr(feature = "serde", serde(rename = "read-write"))]
+    #[default]
     ReadWrite,
 
     /// Read access is always permitted.
@@ -39,12 +40,6 @@ impl Access {
     }
 }
 
-impl Default for Access {
-    fn default() -> Self {
-        Self::ReadWrite
-    }
-}
-
 impl Access {
     /// Parse a string into an [`Access`] value, returning [`Option::None`] if the string is not valid.
     pub fn parse_str(s: &str) -> Option<Self> {
@@ -25,9 +25,10 @@ pub struct AddressBlock {
     derive(serde::Deserialize, serde::Serialize),
     serde(rename_all = "kebab-case")
 )]
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
 pub enum AddressBlockUsage {
     /// Registers
+    #[default]
     Registers,
     /// Buffer / Memory
     Buffer,
@@ -35,12 +36,6 @@ pub enum AddressBlockUsage {
     Reserved,
 }
 
-impl Default for AddressBlockUsage {
-    fn default() -> Self {
-        Self::Registers
-    }
-}
-
 impl AddressBlockUsage {
     /// Parse a string into an [`AddressBlockUsage`] value, returning [`Option::None`] if the string is not valid.
     pub fn parse_str(s: &str) -> Option<Self> {
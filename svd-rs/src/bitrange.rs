@@ -37,7 +37,7 @@ impl BitRange {
     }
     /// Get the position of the most significant bit
     pub fn msb(&self) -> u32 {
-        self.offset + self.width - 1
+        self.offset.saturating_add(self.width).saturating_sub(1)
     }
     /// Get the bit range in the format `[<msb>:<lsb>]`
     pub fn bit_range(&self) -> String {
@@ -53,12 +53,22 @@ impl BitRange {
     }
 
     /// Construct a [`BitRange`] from a msb and lsb
+    ///
+    /// # Panics
+    ///
+    /// Panics if `msb` is less than `lsb`. See [`try_from_msb_lsb`](Self::try_from_msb_lsb)
     pub fn from_msb_lsb(msb: u32, lsb: u32) -> Self {
-        Self {
+        Self::try_from_msb_lsb(msb, lsb).expect("msb is less than lsb")
+    }
+
+    /// Construct a [`BitRange`] from a msb and lsb.
+    /// Returns `None` if `msb` is less than `lsb`
+    pub fn try_from_msb_lsb(msb: u32, lsb: u32) -> Option<Self> {
+        Some(Self {
             offset: lsb,
-            width: msb - lsb + 1,
+            width: msb.checked_sub(lsb)?.checked_add(1)?,
             range_type: BitRangeType::MsbLsb,
-        }
+        })
     }
     /// Construct a [`BitRange`] from a string in the format `[<msb>:<lsb>]`
     pub fn from_bit_range(text: &str) -> Option<Self> {
@@ -70,7 +80,7 @@ impl BitRange {
         let lsb = parts.next()?.parse::<u32>().ok()?;
         Some(Self {
             offset: lsb,
-            width: msb - lsb + 1,
+            width: msb.checked_sub(lsb)?.checked_add(1)?,
             range_type: BitRangeType::BitRange,
         })
     }
@@ -139,7 +149,8 @@ mod ser_de {
                     bit_offset,
                     bit_width,
                 } => Ok(BitRange::from_offset_width(bit_offset, bit_width)),
-                SerBitRange::MsbLsb { msb, lsb } => Ok(BitRange::from_msb_lsb(msb, lsb)),
+                SerBitRange::MsbLsb { msb, lsb } => BitRange::try_from_msb_lsb(msb, lsb)
+                    .ok_or_else(|| serde::de::Error::custom("msb is less than lsb")),
             }
         }
     }
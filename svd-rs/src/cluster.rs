@@ -73,12 +73,16 @@ pub struct ClusterInfo {
     pub derived_from: Option<String>,
 }
 
-/// Return iterator over address offsets of each cluster in array
+/// Return iterator over address offsets of each cluster in array.
+/// Stops at the first cluster whose address offset overflows
 pub fn address_offsets<'a>(
     info: &'a ClusterInfo,
     dim: &'a DimElement,
 ) -> impl Iterator<Item = u32> + 'a {
-    (0..dim.dim).map(move |i| info.address_offset + i * dim.dim_increment)
+    (0..dim.dim).map_while(move |i| {
+        i.checked_mul(dim.dim_increment)?
+            .checked_add(info.address_offset)
+    })
 }
 
 /// Extract `ClusterInfo` items from array
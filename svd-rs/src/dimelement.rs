@@ -166,7 +166,7 @@ impl DimElement {
     }
     /// Try to represent [`DimElement`] as range of integer indexes
     pub fn indexes_as_range(&self) -> Option<RangeInclusive<u32>> {
-        let mut integers = Vec::with_capacity(self.dim as usize);
+        let mut integers = Vec::new();
         for idx in self.indexes() {
             // XXX: indexes that begin with leading zero are not compatible with range (`0-x`) syntax in serialization
             // see https://github.com/rust-embedded/svdtools/pull/178#issuecomment-1801433808
@@ -178,7 +178,7 @@ impl DimElement {
         }
         let min = *integers.iter().min()?;
         let max = *integers.iter().max()?;
-        if max - min + 1 != self.dim {
+        if (max - min).checked_add(1) != Some(self.dim) {
             return None;
         }
         for (&i, r) in integers.iter().zip(min..=max) {
@@ -221,6 +221,7 @@ impl DimElement {
         Ok(())
     }
     /// Get the indexes of the array or list.
+    /// Stops at the end of `dim_index` if it is shorter than `dim`
     pub fn indexes(&self) -> Indexes<'_> {
         Indexes {
             i: 0,
@@ -246,7 +247,7 @@ impl<'a> Iterator for Indexes<'a> {
         let i = self.i;
         self.i += 1;
         if let Some(index) = self.dim_index.as_ref() {
-            Some(index[i as usize].as_str().into())
+            index.get(i as usize).map(|s| s.as_str().into())
         } else {
             Some(i.to_string().into())
         }
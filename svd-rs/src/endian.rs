@@ -4,9 +4,10 @@
     derive(serde::Deserialize, serde::Serialize),
     serde(rename_all = "kebab-case")
 )]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum Endian {
     /// Little endian.
+    #[default]
     Little,
     /// Big endian.
     Big,
@@ -16,12 +17,6 @@ pub enum Endian {
     Other,
 }
 
-impl Default for Endian {
-    fn default() -> Self {
-        Self::Little
-    }
-}
-
 impl Endian {
     /// Parse a string into an [Endian] value, returning [`Option::None`] if the string is not valid.
     pub fn parse_str(s: &str) -> Option<Self> {
@@ -84,9 +84,13 @@ pub struct FieldInfo {
     pub derived_from: Option<String>,
 }
 
-/// Return iterator over bit offsets of each field in array
+/// Return iterator over bit offsets of each field in array.
+/// Stops at the first field whose bit offset overflows
 pub fn bit_offsets<'a>(info: &'a FieldInfo, dim: &'a DimElement) -> impl Iterator<Item = u32> + 'a {
-    (0..dim.dim).map(|i| info.bit_offset() + i * dim.dim_increment)
+    (0..dim.dim).map_while(|i| {
+        i.checked_mul(dim.dim_increment)?
+            .checked_add(info.bit_offset())
+    })
 }
 
 /// Extract `FieldInfo` items from array
@@ -378,7 +382,10 @@ impl FieldInfo {
     /// Get bits which is affected by field
     pub fn bitmask(&self) -> u64 {
         let BitRange { offset, width, .. } = self.bit_range;
-        (!0u64 >> (64 - width)) << offset
+        let mask = (!0u64)
+            .checked_shr(64u32.saturating_sub(width))
+            .unwrap_or(0);
+        mask.checked_shl(offset).unwrap_or(0)
     }
 
     /// Get enumeratedValues cluster by usage
@@ -421,7 +428,10 @@ impl Field {
                 let mask = f.bitmask();
                 let mut bits = 0;
                 for i in 0..d.dim {
-                    bits |= mask << (i * d.dim_increment);
+                    bits |= i
+                        .checked_mul(d.dim_increment)
+                        .and_then(|shift| mask.checked_shl(shift))
+                        .unwrap_or(0);
                 }
                 bits
             }
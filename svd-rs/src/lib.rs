@@ -94,6 +94,10 @@ pub use self::protection::Protection;
 pub mod datatype;
 pub use self::datatype::DataType;
 
+/// Original form of elements in the source file
+pub mod sourceformat;
+pub use self::sourceformat::{ElementFormat, SourceFormat};
+
 /// Level of validation
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum ValidateLevel {
@@ -213,6 +217,36 @@ pub(crate) fn check_derived_name(name: &str, tag: &str) -> Result<(), NameError>
     Ok(())
 }
 
+/// Matches `name` against `pattern` with `*` and `?` wildcards
+pub fn glob(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // Position of last `*` in pattern and matched position in name
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 trait EmptyToNone {
     fn empty_to_none(self) -> Self;
 }
@@ -276,3 +310,17 @@ where
         T::description(*self)
     }
 }
+
+#[test]
+fn test_glob() {
+    assert!(glob("GPIO*", "GPIOA"));
+    assert!(glob("GPIO*", "GPIO"));
+    assert!(glob("*", ""));
+    assert!(glob("U?ART*", "USART1"));
+    assert!(glob("*_NS", "TIM1_NS"));
+    assert!(!glob("GPIO?", "GPIO"));
+    assert!(!glob("RCC", "RCC_S"));
+    assert!(glob("A*B*C", "AxxBxBxC"));
+    assert!(glob("*.CR?", "TIM1.CR1"));
+    assert!(glob("UART%s", "UART%s"));
+}
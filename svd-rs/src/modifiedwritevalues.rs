@@ -5,7 +5,7 @@
     derive(serde::Deserialize, serde::Serialize),
     serde(rename_all = "camelCase")
 )]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum ModifiedWriteValues {
     /// Write data bit of one shall clear (set to zero) the corresponding bit in the field
     OneToClear,
@@ -32,15 +32,10 @@ pub enum ModifiedWriteValues {
     Set,
 
     /// After a write operation all bit in the field may be modified (default)
+    #[default]
     Modify,
 }
 
-impl Default for ModifiedWriteValues {
-    fn default() -> Self {
-        Self::Modify
-    }
-}
-
 impl ModifiedWriteValues {
     /// Parse a string into an [`ModifiedWriteValues`] value, returning [`Option::None`] if the string is not valid.
     pub fn parse_str(s: &str) -> Option<Self> {
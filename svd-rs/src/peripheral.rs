@@ -125,12 +125,16 @@ pub struct PeripheralInfo {
     pub derived_from: Option<String>,
 }
 
-/// Return iterator over base addresses of each peripheral in array
+/// Return iterator over base addresses of each peripheral in array.
+/// Stops at the first peripheral whose base address overflows
 pub fn base_addresses<'a>(
     info: &'a PeripheralInfo,
     dim: &'a DimElement,
 ) -> impl Iterator<Item = u64> + 'a {
-    (0..dim.dim as u64).map(|i| info.base_address + i * dim.dim_increment as u64)
+    (0..dim.dim as u64).map_while(|i| {
+        i.checked_mul(dim.dim_increment as u64)?
+            .checked_add(info.base_address)
+    })
 }
 
 /// Extract `PeripheralInfo` items from array
@@ -4,7 +4,7 @@
 /// when no universal access permissions have been granted.
 /// If no specific information is provided, an address region is accessible in any mode
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum Protection {
     /// Secure permission required for access
     #[cfg_attr(feature = "serde", serde(rename = "s"))]
@@ -12,6 +12,7 @@ pub enum Protection {
 
     /// Non-secure or secure permission required for access
     #[cfg_attr(feature = "serde", serde(rename = "n"))]
+    #[default]
     NonSecure,
 
     /// Privileged permission required for access
@@ -19,12 +20,6 @@ pub enum Protection {
     Privileged,
 }
 
-impl Default for Protection {
-    fn default() -> Self {
-        Self::NonSecure
-    }
-}
-
 impl Protection {
     /// Parse a string into an [`Protection`] value, returning [`Option::None`] if the string is not valid.
     pub fn parse_str(s: &str) -> Option<Self> {
@@ -111,12 +111,16 @@ pub struct RegisterInfo {
     pub derived_from: Option<String>,
 }
 
-/// Return iterator over address offsets of each register in array
+/// Return iterator over address offsets of each register in array.
+/// Stops at the first register whose address offset overflows
 pub fn address_offsets<'a>(
     info: &'a RegisterInfo,
     dim: &'a DimElement,
 ) -> impl Iterator<Item = u32> + 'a {
-    (0..dim.dim).map(move |i| info.address_offset + i * dim.dim_increment)
+    (0..dim.dim).map_while(move |i| {
+        i.checked_mul(dim.dim_increment)?
+            .checked_add(info.address_offset)
+    })
 }
 
 /// Extract `RegisterInfo` items from array
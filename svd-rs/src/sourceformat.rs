@@ -0,0 +1,101 @@
+//! Original textual form of SVD elements.
+//!
+//! Recorded by the parser and used by the encoder to write elements which were not
+//! changed exactly as they were in the source file, with their whitespace and comments.
+//! Changed values keep the radix and width of numbers and the bit range style of fields,
+//! and children and attributes keep their order.
+//!
+//! Elements are addressed by the path of their [`segment`]s from the root element joined with `/`.
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Side table mapping element paths to their original form
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceFormat {
+    /// Text of the source file
+    pub text: String,
+    /// Indentation unit of the source file
+    pub indent: Option<String>,
+    elements: HashMap<String, ElementFormat>,
+}
+
+/// Original form of an element
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ElementFormat {
+    /// Byte range of the element in [`SourceFormat::text`]
+    pub range: Range<usize>,
+    /// End of the start tag. Equal to the end of `range` if the element is self-closing
+    pub start_tag_end: usize,
+    /// Attributes and namespace declarations in original order
+    pub attributes: Vec<(String, String)>,
+    /// Segments of element children in original order
+    pub children: Vec<String>,
+    /// Byte ranges of the text before each element child and after the last one
+    /// with whitespace and comments. Empty if the element is self-closing
+    pub gaps: Vec<Range<usize>>,
+    /// Original text of children which contain only text, by segment
+    pub values: HashMap<String, String>,
+}
+
+impl SourceFormat {
+    /// Create empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Original form of element with `path`
+    pub fn get(&self, path: &str) -> Option<&ElementFormat> {
+        self.elements.get(path)
+    }
+
+    /// Set original form of element with `path`
+    pub fn insert(&mut self, path: String, format: ElementFormat) {
+        self.elements.insert(path, format);
+    }
+
+    /// Number of recorded elements
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if nothing is recorded
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+/// Identifies an element among its siblings.
+///
+/// Elements with `<name>` child are identified as `tag[name]`, other elements by `tag`.
+/// `index` among previous siblings with the same tag and name is added as `#index` if not 0
+pub fn segment(tag: &str, name: Option<&str>, index: usize) -> String {
+    match (name, index) {
+        (Some(name), 0) => format!("{tag}[{name}]"),
+        (Some(name), i) => format!("{tag}[{name}]#{i}"),
+        (None, 0) => tag.to_string(),
+        (None, i) => format!("{tag}#{i}"),
+    }
+}
+
+/// Path of the child element with `segment`
+pub fn child_path(parent: &str, segment: &str) -> String {
+    if parent.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{parent}/{segment}")
+    }
+}
+
+/// Computes segments of sibling elements given their tags and names
+pub fn segments<'a>(children: impl IntoIterator<Item = (&'a str, Option<&'a str>)>) -> Vec<String> {
+    let mut counts = HashMap::<(&str, Option<&str>), usize>::new();
+    children
+        .into_iter()
+        .map(|(tag, name)| {
+            let count = counts.entry((tag, name)).or_default();
+            *count += 1;
+            segment(tag, name, *count - 1)
+        })
+        .collect()
+}
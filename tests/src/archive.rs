@@ -0,0 +1,104 @@
+use super::device;
+use flate2::{write::GzEncoder, Compression};
+use std::io::{Cursor, Write};
+use svd_parser::archive::{load_bytes, pdsc_devices, Pack, PackDevice};
+use svd_parser::{Config, Limits};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+/// [`device`] named `name`
+fn svd(name: &str) -> String {
+    let mut device = device();
+    device.name = name.into();
+    svd_encoder::encode(&device).unwrap()
+}
+
+const PDSC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package schemaVersion="1.7.2">
+  <vendor>Test</vendor>
+  <name>TEST_DFP</name>
+  <devices>
+    <family Dfamily="TEST Series" Dvendor="Test:0">
+      <debug svd="SVD/TEST_common.svd"/>
+      <subFamily DsubFamily="TEST1">
+        <device Dname="TEST1A"/>
+        <device Dname="TEST1B">
+          <debug svd="SVD\TEST1B.svd"/>
+          <variant Dvariant="TEST1B-Q"/>
+        </device>
+      </subFamily>
+    </family>
+  </devices>
+</package>
+"#;
+
+fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn gzip() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(svd("GZ").as_bytes()).unwrap();
+    let bytes = encoder.finish().unwrap();
+    let device = load_bytes(&bytes, &Config::default()).unwrap();
+    assert_eq!(device.name, "GZ");
+}
+
+#[test]
+fn single_svd_zip() {
+    let bytes = zip(&[("readme.txt", "text"), ("dev/ZIP.svd", &svd("ZIP"))]);
+    let device = load_bytes(&bytes, &Config::default()).unwrap();
+    assert_eq!(device.name, "ZIP");
+
+    let bytes = zip(&[("A.svd", &svd("A")), ("B.svd", &svd("B"))]);
+    assert!(load_bytes(&bytes, &Config::default()).is_err());
+}
+
+#[test]
+fn decompressed_size() {
+    let svd = svd("BIG");
+    let limits = |max| Config::default().limits(Limits::default().max_decompressed_size(max));
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(svd.as_bytes()).unwrap();
+    let gzip = encoder.finish().unwrap();
+    let zip = zip(&[("BIG.svd", &svd)]);
+    let max = svd.len() as u64;
+    for bytes in [&gzip, &zip] {
+        assert!(load_bytes(bytes, &limits(max)).is_ok());
+        let err = load_bytes(bytes, &limits(max - 1)).unwrap_err();
+        assert!(err.to_string().contains("larger than"), "{err}");
+    }
+}
+
+#[test]
+fn pack() {
+    let names = pdsc_devices(PDSC)
+        .unwrap()
+        .into_iter()
+        .map(|PackDevice { name, svd, .. }| (name, svd))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            ("TEST1A".to_string(), "SVD/TEST_common.svd".to_string()),
+            ("TEST1B".to_string(), "SVD/TEST1B.svd".to_string()),
+            ("TEST1B-Q".to_string(), "SVD/TEST1B.svd".to_string()),
+        ]
+    );
+
+    let bytes = zip(&[
+        ("Test.TEST_DFP.pdsc", PDSC),
+        ("SVD/TEST_common.svd", &svd("COMMON")),
+        ("SVD/TEST1B.svd", &svd("TEST1B")),
+    ]);
+    let mut pack = Pack::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(pack.devices().len(), 3);
+    assert_eq!(pack.device("TEST1A").unwrap().name, "COMMON");
+    assert_eq!(pack.device("test1b-q").unwrap().name, "TEST1B");
+    assert!(pack.device("TEST2").is_err());
+}
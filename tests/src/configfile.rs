@@ -0,0 +1,110 @@
+use svd_encoder::{
+    Config, ConfigError, DerivableSorting, IdentifierFormat, NumberFormat, RcSorting,
+    SchemaVersion, Sorting,
+};
+
+const TOML: &str = r#"
+schema_version = "1.1"
+
+[peripheral]
+base_address = "LowerHex8"
+sorting = "Offset,DerivedLast"
+
+[register]
+name = "Constant"
+cluster_sorting = "RegistersFirst,Name"
+
+[enumerated_value]
+value = "Bin"
+"#;
+
+fn check(config: &Config) {
+    assert_eq!(config.schema_version, Some(SchemaVersion::V1_1));
+    assert_eq!(config.peripheral_base_address, NumberFormat::LowerHex8);
+    assert_eq!(
+        config.peripheral_sorting,
+        DerivableSorting::DeriveLast(Some(Sorting::Offset))
+    );
+    assert_eq!(config.register_name, Some(IdentifierFormat::Constant));
+    assert_eq!(
+        config.register_cluster_sorting,
+        RcSorting::RegistersFirst(DerivableSorting::Unchanged(Some(Sorting::Name)))
+    );
+    assert_eq!(config.enumerated_value_value, NumberFormat::Bin);
+    // Not specified
+    assert_eq!(config.field_name, None);
+    assert_eq!(config.register_size, NumberFormat::LowerHex);
+}
+
+#[test]
+fn formats() {
+    let config = Config::from_toml(TOML).unwrap();
+    check(&config);
+    check(&Config::from_toml(&config.to_toml().unwrap()).unwrap());
+    check(&Config::from_yaml(&config.to_yaml().unwrap()).unwrap());
+    check(&Config::from_json(&config.to_json().unwrap()).unwrap());
+
+    let yaml = "schema_version: 1.1\nregister:\n  name: Constant\n";
+    let config = Config::from_yaml(yaml).unwrap();
+    assert_eq!(config.schema_version, Some(SchemaVersion::V1_1));
+    assert_eq!(config.register_name, Some(IdentifierFormat::Constant));
+
+    let config: Config = "register_name = Constant\nperipheral_sorting=Offset,DerivedLast"
+        .parse()
+        .unwrap();
+    assert_eq!(config.register_name, Some(IdentifierFormat::Constant));
+    assert_eq!(
+        config.peripheral_sorting,
+        DerivableSorting::DeriveLast(Some(Sorting::Offset))
+    );
+}
+
+#[test]
+fn files() {
+    let dir = std::env::temp_dir().join(format!("svd-format-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = Config::from_toml(TOML).unwrap();
+    for ext in ["toml", "yaml", "json"] {
+        let path = dir.join(format!("svd-format.{ext}"));
+        config.save(&path).unwrap();
+        check(&Config::load(&path).unwrap());
+    }
+    let err = Config::load(dir.join("missing.toml")).unwrap_err();
+    assert!(matches!(err, ConfigError::Io(_)));
+    // Format is checked before the file is read or written
+    let err = Config::load(dir.join("missing.ini")).unwrap_err();
+    assert!(matches!(err, ConfigError::UnsupportedFormat(ext) if ext == "ini"));
+    let err = config.save(dir.join("svd-format.ini")).unwrap_err();
+    assert!(matches!(err, ConfigError::UnsupportedFormat(ext) if ext == "ini"));
+    assert!(!dir.join("svd-format.ini").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn errors() {
+    let err = Config::from_toml("[register]\nsize = \"Hex\"\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value `Hex` of config key `register.size`"
+    );
+    let err = Config::from_toml("[register]\nwidth = \"Dec\"\n").unwrap_err();
+    assert!(matches!(err, ConfigError::UnknownKey(key) if key == "register.width"));
+    let err = Config::from_yaml("registers:\n  name: Snake\n").unwrap_err();
+    assert!(matches!(err, ConfigError::UnknownKey(key) if key == "registers"));
+    let err = Config::from_json(r#"{"peripheral_sorting": "Offset,Last"}"#).unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidValue { key, .. } if key == "peripheral_sorting"));
+    assert!(matches!(
+        Config::from_toml("schema_version = ").unwrap_err(),
+        ConfigError::Format(_)
+    ));
+
+    let mut config = Config::default();
+    assert!(matches!(
+        config.try_update("register_name", "Kebab"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        "field_name".parse::<Config>(),
+        Err(ConfigError::InvalidEntry(_))
+    ));
+}
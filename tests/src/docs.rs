@@ -0,0 +1,116 @@
+use super::{device, field};
+use svd_encoder::{encode_docs, DocsConfig, DocsFormat, EncodeError, Page};
+use svd_rs::{Field, RegisterCluster};
+
+fn pages(config: &DocsConfig) -> Vec<Page> {
+    encode_docs(&device(), config).unwrap()
+}
+
+#[test]
+fn markdown() {
+    let pages = pages(&DocsConfig::default());
+    let paths = pages.iter().map(|p| p.path.as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, ["index.md", "TIMER0.md", "TIMER1.md", "UART.md"]);
+
+    let index = &pages[0].content;
+    assert!(index.starts_with("# TEST\n"));
+    assert!(index.contains("| [TIMER1](TIMER1.md) | `0x40001000` |  | [TIMER0](TIMER0.md) |  |\n"));
+    // Interrupts are sorted by number
+    let timer1 = index.find("| 1 | TIMER1 | [TIMER1](TIMER1.md) |").unwrap();
+    let timer0 = index
+        .find("| 3 | TIMER0 | [TIMER0](TIMER0.md) | Timer 0 interrupt |")
+        .unwrap();
+    assert!(timer1 < timer0);
+
+    let timer0 = &pages[1].content;
+    // Properties are inherited from the device and the base register
+    assert!(timer0.contains("| `0x000` | [CR](#CR) | 32 | read-write | `0x00000020` | Control |\n"));
+    assert!(
+        timer0.contains("| `0x004` | [CR2](#CR2) | 32 | read-write | `0x00000020` | Control |\n")
+    );
+    assert!(timer0.contains("| `0x008` | [SR](#SR) | 16 | read-only | `0x8000` |  |\n"));
+    assert!(timer0.contains("| Derived from | [CR](#CR) |\n"));
+    // Registers of alternate group have their own anchors
+    assert!(timer0.contains("| `0x010` | [CNT\\_BYTE](#CNT_BYTE) | 8 |"));
+    assert!(timer0.contains("<a id=\"CR\"></a>\n\n### CR\n"));
+    assert!(timer0.contains(
+        "| 31:6 | 5:4 | 3:1 | 0 |\n| :---: | :---: | :---: | :---: |\n| - | MODE | - | EN |\n"
+    ));
+    assert!(timer0.contains("| 5:4 | MODE | write-only | `0x2` |  |\n"));
+    assert!(timer0.contains("| `0x2` | ONESHOT | One pulse |\n"));
+    assert!(timer0.contains("| `0x034` | [CH\\[%s\\].CFG](#CH.CFG) |"));
+
+    let timer1 = &pages[2].content;
+    assert!(timer1.contains("Registers are the same as in [TIMER0](TIMER0.md)."));
+    assert!(!timer1.contains("Address map"));
+}
+
+#[test]
+fn html() {
+    let config = DocsConfig::default()
+        .format(DocsFormat::Html)
+        .enumerated_values(false);
+    let pages = pages(&config);
+    assert_eq!(pages[1].path, "TIMER0.html");
+    let timer0 = &pages[1].content;
+    assert!(timer0.starts_with("<!DOCTYPE html>"));
+    assert!(timer0.contains("<style>"));
+    assert!(timer0.contains("<h3 id=\"CR\">CR</h3>"));
+    assert!(timer0.contains("<td colspan=\"2\">MODE</td>"));
+    assert!(timer0.contains("<td colspan=\"26\" class=\"reserved\"></td>"));
+    assert!(!timer0.contains("ONESHOT"));
+    assert!(pages[0]
+        .content
+        .contains("<a href=\"TIMER0.html\">TIMER0</a>"));
+}
+
+#[test]
+fn field_outside_reset() {
+    let mut device = device();
+    let cr = device.peripherals[0].get_mut_register("CR").unwrap();
+    cr.fields = Some(vec![Field::Single(field("MODE", 70, 2))]);
+    let pages = encode_docs(&device, &DocsConfig::default()).unwrap();
+    assert!(pages[1]
+        .content
+        .contains("| 71:70 | MODE | read-write |  |  |\n"));
+}
+
+#[test]
+fn unknown_base() {
+    let mut derived_register = device();
+    let cr2 = derived_register.peripherals[0]
+        .get_mut_register("CR2")
+        .unwrap();
+    cr2.derived_from = Some("CR9".into());
+    let mut derived_peripheral = device();
+    derived_peripheral.peripherals[1].derived_from = Some("TIMER9".into());
+    for device in [derived_register, derived_peripheral] {
+        assert!(matches!(
+            encode_docs(&device, &DocsConfig::default()),
+            Err(EncodeError::UnknownDerivedFrom(..))
+        ));
+    }
+}
+
+#[test]
+fn derived_from_other_peripheral() {
+    let mut device = device();
+    let mut ch = device.peripherals[0].get_cluster("CH[%s]").unwrap().clone();
+    let uart = &mut device.peripherals[2];
+    let dr = uart.get_mut_register("DR").unwrap();
+    dr.derived_from = Some("TIMER0.CR".into());
+    dr.fields = None;
+    ch.derived_from = Some("TIMER0.CH[%s]".into());
+    ch.children.clear();
+    uart.registers
+        .as_mut()
+        .unwrap()
+        .push(RegisterCluster::Cluster(ch));
+
+    let pages = encode_docs(&device, &DocsConfig::default()).unwrap();
+    let uart = &pages[3].content;
+    assert!(uart.contains("| `0x000` | [DR](#DR) | 32 | read-write | `0x00000020` | Control |\n"));
+    assert!(uart.contains("| Derived from | [TIMER0.CR](TIMER0.md#CR) |\n"));
+    assert!(uart.contains("| 5:4 | MODE | write-only | `0x2` |  |\n"));
+    assert!(uart.contains("| `0x034` | [CH\\[%s\\].CFG](#CH.CFG) |"));
+}
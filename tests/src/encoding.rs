@@ -0,0 +1,62 @@
+use svd_parser::SVDError;
+
+/// [`svd`](super::svd) declared in `encoding` with non-ASCII description of `TIMER0`
+fn svd(encoding: &str) -> String {
+    super::svd().replacen("UTF-8", encoding, 1).replace(
+        "<description>Timer</description>",
+        "<description>Sensor range 0-125 °C, 5 µs</description>",
+    )
+}
+
+fn description(device: &svd_parser::svd::Device) -> &str {
+    device.peripherals[0].description.as_deref().unwrap()
+}
+
+/// Encodes characters below U+0100 as single bytes
+fn to_bytes(s: &str) -> Vec<u8> {
+    s.chars().map(|c| u8::try_from(c).unwrap()).collect()
+}
+
+#[test]
+fn single_byte() {
+    for encoding in ["ISO-8859-1", "windows-1252"] {
+        let bytes = to_bytes(&svd(encoding));
+        assert!(std::str::from_utf8(&bytes).is_err());
+        let device = svd_parser::parse_bytes(&bytes).unwrap();
+        assert_eq!(description(&device), "Sensor range 0-125 °C, 5 µs");
+    }
+    // Euro sign is 0x80 in windows-1252
+    let bytes = to_bytes(&svd("windows-1252").replace('°', "\u{80}"));
+    let device = svd_parser::parse_bytes(&bytes).unwrap();
+    assert_eq!(description(&device), "Sensor range 0-125 €C, 5 µs");
+}
+
+#[test]
+fn utf16() {
+    let text = svd("UTF-16");
+    let le = [0xFF, 0xFE]
+        .into_iter()
+        .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect::<Vec<_>>();
+    let be = [0xFE, 0xFF]
+        .into_iter()
+        .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+        .collect::<Vec<_>>();
+    for bytes in [le, be] {
+        let device = svd_parser::parse_reader(bytes.as_slice()).unwrap();
+        assert_eq!(description(&device), "Sensor range 0-125 °C, 5 µs");
+    }
+}
+
+#[test]
+fn utf8() {
+    let text = svd("utf-8");
+    let device = svd_parser::parse_bytes(text.as_bytes()).unwrap();
+    assert_eq!(description(&device), "Sensor range 0-125 °C, 5 µs");
+
+    let err = svd_parser::parse_bytes(svd("EUC-JP").as_bytes()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SVDError>(),
+        Some(&SVDError::UnsupportedEncoding("EUC-JP".into()))
+    );
+}
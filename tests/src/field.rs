@@ -6,7 +6,7 @@ use crate::svd::{
 
 #[test]
 fn decode_encode() {
-    let tests = vec![(
+    let tests = [(
         Field::Array(
             FieldInfo::builder()
                 .name("MODE%s".to_string())
@@ -81,7 +81,7 @@ fn decode_encode() {
 
 #[test]
 fn decode_encode_info() {
-    let tests = vec![
+    let tests = [
         (
             FieldInfo::builder()
                 .name("MODE".to_string())
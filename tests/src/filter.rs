@@ -0,0 +1,75 @@
+use super::{device, peripheral, register};
+use svd_parser::{parse_stream, parse_with_config, Config, PeripheralFilter};
+
+/// [`device`] with register derived from other peripheral
+fn svd() -> String {
+    let mut device = device();
+    let mut cr = register("CR", 0x0);
+    cr.derived_from = Some("TIMER0.CR".into());
+    let rcc = peripheral("RCC", 0x4000_3000, vec![cr.single().into()]);
+    device.peripherals.push(rcc.single());
+    svd_encoder::encode(&device).unwrap()
+}
+
+fn names(config: Config) -> Vec<String> {
+    parse_with_config(&svd(), &config)
+        .unwrap()
+        .peripherals
+        .into_iter()
+        .map(|p| p.name.clone())
+        .collect()
+}
+
+#[test]
+fn filter() {
+    let filter = PeripheralFilter::new().include("TIMER*").exclude("TIMER0");
+    assert_eq!(
+        names(Config::default().peripheral_filter(filter)),
+        ["TIMER1"]
+    );
+    let filter = PeripheralFilter::new().group("UART").include("RCC");
+    assert_eq!(
+        names(Config::default().peripheral_filter(filter)),
+        ["UART%s", "RCC"]
+    );
+    let filter = PeripheralFilter::new().exclude("UART*");
+    assert_eq!(
+        names(Config::default().peripheral_filter(filter)),
+        ["TIMER0", "TIMER1", "RCC"]
+    );
+}
+
+#[test]
+fn expand() {
+    let config = Config::default().expand(true);
+    // `derivedFrom` targets are resolved even if they are filtered out
+    let filter = PeripheralFilter::new().include("TIMER1").include("RCC");
+    let device = parse_with_config(&svd(), &config.clone().peripheral_filter(filter)).unwrap();
+    let found = device
+        .peripherals
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(found, ["TIMER1", "RCC"]);
+    assert!(device.peripherals[0].get_register("CR").is_some());
+    assert!(device.peripherals[1].get_register("CR").is_some());
+
+    let filter = PeripheralFilter::new().group("UART");
+    assert_eq!(names(config.peripheral_filter(filter)), ["UART0", "UART1"]);
+}
+
+#[test]
+fn stream() {
+    let filter = PeripheralFilter::new().group("UART").include("RCC");
+    let config = Config::default().peripheral_filter(filter);
+    let device = parse_stream(svd().as_bytes(), &config)
+        .unwrap()
+        .into_device()
+        .unwrap();
+    let found = device
+        .peripherals
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(found, ["UART%s", "RCC"]);
+}
@@ -0,0 +1,128 @@
+use svd_encoder::{
+    encode_fragment, encode_with_config, Config as EncoderConfig, DescriptionFormat, Encoding,
+    Indent, LineEnding, SelfClosing,
+};
+use svd_parser::{parse_with_config, Config};
+use svd_rs::Device;
+
+fn device() -> Device {
+    let mut device = super::device();
+    device.description =
+        "Temperature sensor with range\n    from 0 to 125 °C and accuracy of 1 °C".into();
+    device
+}
+
+#[test]
+fn layout() {
+    let device = device();
+    let config = EncoderConfig::default()
+        .indent(Indent::Tab)
+        .line_ending(LineEnding::CrLf);
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert!(xml.contains("\r\n\t<name>TEST</name>\r\n"));
+    assert!(xml.contains("\r\n\t\t<peripheral>\r\n"));
+    assert!(xml.contains("range\r\n    from"));
+    assert!(!xml.replace("\r\n", "").contains('\n'));
+    assert_eq!(parse_with_config(&xml, &Config::default()).unwrap(), device);
+
+    let config = EncoderConfig::default().indent(Indent::Spaces(4));
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert!(xml.contains("\n        <peripheral>\n"));
+}
+
+#[test]
+fn declaration_encoding() {
+    let device = device();
+    let config = EncoderConfig::default().declaration_encoding(Encoding::Iso8859(1));
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert!(xml.starts_with(r#"<?xml version="1.0" encoding="ISO-8859-1"?>"#));
+    assert!(xml.is_ascii());
+    assert!(xml.contains("125 &#xB0;C"));
+    assert_eq!(svd_parser::parse_bytes(xml.as_bytes()).unwrap(), device);
+
+    let xml = encode_with_config(&device, &EncoderConfig::default()).unwrap();
+    assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert!(xml.contains("125 °C"));
+}
+
+#[test]
+fn self_closing() {
+    let mut p = device().peripherals.remove(0);
+    p.registers = Some(Vec::new());
+    for (style, expected) in [
+        (SelfClosing::Compact, "<registers/>"),
+        (SelfClosing::Padded, "<registers />"),
+        (SelfClosing::Expanded, "<registers></registers>"),
+    ] {
+        let config = EncoderConfig::default().self_closing(style);
+        let xml = encode_fragment(&p, &config).unwrap();
+        assert!(xml.contains(expected), "{xml}");
+    }
+}
+
+#[test]
+fn descriptions() {
+    let device = device();
+    let config = EncoderConfig::default().description_format(DescriptionFormat::SingleLine);
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert!(xml.contains(
+        "<description>Temperature sensor with range from 0 to 125 °C and accuracy of 1 °C</description>"
+    ));
+
+    let config = EncoderConfig::default().description_format(DescriptionFormat::Wrap(30));
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert!(xml.contains(
+        "<description>Temperature sensor with range\n    from 0 to 125 °C and accuracy\n    of 1 °C</description>"
+    ));
+}
+
+#[test]
+fn attribute_order() {
+    let config = EncoderConfig::default().attribute_order(["xmlns:xs", "schemaVersion"]);
+    let xml = encode_with_config(&device(), &config).unwrap();
+    assert!(xml.contains(r#"<device xmlns:xs="http://www.w3.org/2001/XMLSchema-instance" schemaVersion="1.3" xs:noNamespaceSchemaLocation="CMSIS-SVD.xsd">"#));
+}
+
+#[test]
+fn options() {
+    let config: EncoderConfig =
+        "indent = Tab; line_ending = CrLf; self_closing = Padded; description_format = Wrap(80); attribute_order = xmlns:xs, schemaVersion; declaration_encoding = us-ascii"
+            .parse()
+            .unwrap();
+    assert_eq!(config.indent, Indent::Tab);
+    assert_eq!(config.line_ending, LineEnding::CrLf);
+    assert_eq!(config.self_closing, SelfClosing::Padded);
+    assert_eq!(config.description_format, DescriptionFormat::Wrap(80));
+    assert_eq!(*config.attribute_order, ["xmlns:xs", "schemaVersion"]);
+    assert_eq!(config.declaration_encoding, Encoding::UsAscii);
+    assert_eq!(
+        config.get("declaration_encoding").as_deref(),
+        Some("US-ASCII")
+    );
+    assert_eq!(
+        config.get("description_format").as_deref(),
+        Some("Wrap(80)")
+    );
+    assert_eq!(config.get("indent").as_deref(), Some("Tab"));
+    assert_eq!(EncoderConfig::default().get("indent").as_deref(), Some("2"));
+    assert_eq!(EncoderConfig::default().get("attribute_order"), None);
+    for (name, encoding) in [
+        ("iso-8859-15", Encoding::Iso8859(15)),
+        ("Windows-1252", Encoding::Windows(1252)),
+        ("utf-8", Encoding::Utf8),
+    ] {
+        assert_eq!(name.parse(), Ok(encoding));
+        assert!(name.eq_ignore_ascii_case(&encoding.to_string()));
+    }
+    for entry in [
+        "indent = -1",
+        "description_format = Wrap(0)",
+        "declaration_encoding = \"utf-8\"",
+        "declaration_encoding = UTF-16",
+        "declaration_encoding = ISO-8859-12",
+        "declaration_encoding = windows-1249",
+        "declaration_encoding = Shift_JIS",
+    ] {
+        assert!(entry.parse::<EncoderConfig>().is_err(), "{entry}");
+    }
+}
@@ -0,0 +1,58 @@
+use super::device;
+use svd_encoder::{encode_cluster, encode_field, encode_peripheral, encode_register};
+use svd_parser::{parse_cluster, parse_field, parse_peripheral, parse_register};
+
+#[test]
+fn round_trip() {
+    let device = device();
+    let p = &device.peripherals[2];
+    assert!(p.is_array());
+    let xml = encode_peripheral(p).unwrap();
+    assert!(xml.starts_with("<peripheral>"));
+    assert_eq!(&parse_peripheral(&xml).unwrap(), p);
+
+    let timer0 = &device.peripherals[0];
+    let c = timer0.get_cluster("CH[%s]").unwrap();
+    let xml = encode_cluster(c).unwrap();
+    assert_eq!(&parse_cluster(&xml).unwrap(), c);
+
+    let r = timer0.get_register("CR").unwrap();
+    let xml = encode_register(r).unwrap();
+    assert_eq!(&parse_register(&xml).unwrap(), r);
+
+    let f = r.get_field("MODE").unwrap();
+    let xml = encode_field(f).unwrap();
+    assert_eq!(&parse_field(&xml).unwrap(), f);
+}
+
+#[test]
+fn errors() {
+    let err = parse_register(
+        "<register>
+  <name>CR</name>
+  <addressOffset>0x0</addressOffset>
+  <fields>
+    <field>
+      <name>EN</name>
+      <bitRange>[0:1]</bitRange>
+    </field>
+  </fields>
+</register>",
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.chain().map(|e| e.to_string()).collect::<Vec<_>>(),
+        [
+            "In register `CR`",
+            "Parsing field `EN` at 5:5",
+            "Bit range invalid, Size",
+        ]
+    );
+
+    let xml = encode_peripheral(&device().peripherals[0]).unwrap();
+    let err = parse_field(&xml).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Expected a <field>, found ..."
+    );
+}
@@ -0,0 +1,137 @@
+use super::{device, field};
+use svd_encoder::{encode_c_header, EncodeError, HeaderConfig};
+use svd_rs::{Device, Field};
+
+fn header(device: &Device, config: &HeaderConfig) -> String {
+    encode_c_header(device, config).unwrap()
+}
+
+/// Lines of `typedef struct` with `name` without comments
+fn members(h: &str, name: &str) -> Vec<String> {
+    let end = h.find(&format!("}} {name};")).unwrap();
+    let start = h[..end].rfind("typedef struct {").unwrap();
+    h[start..end]
+        .lines()
+        .skip(1)
+        .map(|l| l.split("/*!<").next().unwrap().trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn interrupts() {
+    let h = header(&device(), &HeaderConfig::default());
+    assert!(h.contains("  HardFault_IRQn"));
+    assert!(h.contains("  UsageFault_IRQn"));
+    assert!(!h.contains("SecureFault_IRQn"));
+    // Sorted by number
+    assert!(h.find("  TIMER1_IRQn") < h.find("  TIMER0_IRQn"));
+    assert!(h.contains("} IRQn_Type;"));
+}
+
+#[test]
+fn processor() {
+    let h = header(&device(), &HeaderConfig::default());
+    assert!(h.contains("#define __CM4_REV                               0x0001U\n"));
+    assert!(h.contains("#define __NVIC_PRIO_BITS                        4\n"));
+    assert!(h.contains("#define __MPU_PRESENT                           1\n"));
+    assert!(h.contains("#include \"core_cm4.h\""));
+    assert!(h.contains("#include \"system_TEST.h\""));
+}
+
+#[test]
+fn structure() {
+    let h = header(&device(), &HeaderConfig::default());
+    assert_eq!(
+        members(&h, "TIMER0_Type"),
+        [
+            "  __IOM uint32_t CR;",
+            "  __IOM uint32_t CR2;",
+            "  __IM  uint16_t SR;",
+            "  __IM  uint16_t RESERVED[3];",
+            "  union {",
+            "    __IOM uint16_t CNT;",
+            "    __IOM uint8_t CNT_BYTE;",
+            "  };",
+            "  __IM  uint16_t RESERVED1[7];",
+            "  __IOM uint32_t CC[2];",
+            "  __IM  uint32_t RESERVED2[2];",
+            "  TIMER0_CH_Type CH[2];",
+        ]
+    );
+    // Cluster is padded to its increment
+    assert_eq!(
+        members(&h, "TIMER0_CH_Type"),
+        [
+            "  __IM  uint32_t RESERVED;",
+            "  __IOM uint32_t CFG;",
+            "  __IM  uint32_t RESERVED1[2];",
+        ]
+    );
+    // Derived peripheral uses the structure of its base
+    assert_eq!(h.matches("} TIMER0_Type;").count(), 1);
+    assert!(!h.contains("TIMER1_Type"));
+}
+
+#[test]
+fn memory_map() {
+    let h = header(&device(), &HeaderConfig::default());
+    assert!(h.contains("#define TIMER1_BASE                             0x40001000UL\n"));
+    assert!(h.contains(
+        "#define TIMER1                                  ((TIMER0_Type*) TIMER1_BASE)\n"
+    ));
+    // Peripheral arrays are expanded
+    assert!(h.contains("#define UART1_BASE                              0x40002400UL\n"));
+    assert!(
+        h.contains("#define UART1                                   ((UART_Type*) UART1_BASE)\n")
+    );
+}
+
+#[test]
+fn fields() {
+    let h = header(&device(), &HeaderConfig::default());
+    assert!(h.contains("#define TIMER0_CR_MODE_Pos                      (4UL)"));
+    assert!(h.contains("#define TIMER0_CR_MODE_Msk                      (0x30UL)"));
+    // Fields of the base register
+    assert!(h.contains("#define TIMER0_CR2_MODE_Msk                     (0x30UL)"));
+
+    let h = header(&device(), &HeaderConfig::default().fields(false));
+    assert!(!h.contains("_Pos"));
+}
+
+#[test]
+fn unknown_base() {
+    let mut device = device();
+    device.peripherals[1].derived_from = Some("TIMER9".into());
+    assert!(matches!(
+        encode_c_header(&device, &HeaderConfig::default()),
+        Err(EncodeError::UnknownDerivedFrom(..))
+    ));
+}
+
+#[test]
+fn field_too_wide() {
+    let mut device = device();
+    let cr = device.peripherals[0].get_mut_register("CR").unwrap();
+    cr.fields = Some(vec![Field::Single(field("MODE", 70, 2))]);
+    assert!(matches!(
+        encode_c_header(&device, &HeaderConfig::default()),
+        Err(EncodeError::FieldTooWide(name)) if name == "TIMER0_CR_MODE"
+    ));
+}
+
+#[test]
+fn derived_from_other_peripheral() {
+    let mut device = device();
+    let dr = device.peripherals[2].get_mut_register("DR").unwrap();
+    dr.derived_from = Some("TIMER0.CR".into());
+    dr.fields = None;
+    let h = header(&device, &HeaderConfig::default());
+    assert_eq!(members(&h, "UART_Type"), ["  __IOM uint32_t DR;"]);
+    assert!(h.contains("#define UART_DR_MODE_Msk                        (0x30UL)"));
+
+    device.peripherals[0].registers = None;
+    assert!(matches!(
+        encode_c_header(&device, &HeaderConfig::default()),
+        Err(EncodeError::UnknownDerivedFrom(name, base)) if name == "DR" && base == "TIMER0.CR"
+    ));
+}
@@ -0,0 +1,105 @@
+use svd_encoder::{
+    change_case, change_case_with_config, encode_fragment, Config, IdentifierFormat, WordBoundary,
+};
+use svd_rs::{Field, FieldInfo, Register, RegisterInfo, ValidateLevel};
+
+fn register() -> Register {
+    let field = FieldInfo::builder()
+        .name("I2C_EN".into())
+        .bit_offset(0)
+        .bit_width(1)
+        .build(ValidateLevel::Disabled)
+        .unwrap();
+    RegisterInfo::builder()
+        .name("USART1_CR1".into())
+        .address_offset(0)
+        .fields(Some(vec![Field::Single(field)]))
+        .build(ValidateLevel::Disabled)
+        .unwrap()
+        .single()
+}
+
+/// Names of the register and its field
+fn names(config: &Config) -> (String, String) {
+    let xml = encode_fragment(&register(), config).unwrap();
+    let mut names = xml
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("<name>")?.strip_suffix("</name>"))
+        .map(String::from);
+    (names.next().unwrap(), names.next().unwrap())
+}
+
+fn config(case: IdentifierFormat) -> Config {
+    Config::default()
+        .register_name(Some(case))
+        .field_name(Some(case))
+}
+
+#[test]
+fn acronyms() {
+    let pascal = config(IdentifierFormat::Pascal);
+    assert_eq!(names(&pascal), ("Usart1Cr1".into(), "I2cEn".into()));
+    let pascal = pascal.identifier_acronyms(["USART", "CR", "I2C"]);
+    assert_eq!(names(&pascal), ("USART1CR1".into(), "I2CEn".into()));
+
+    // The first word of camel case is in lower case
+    let camel = config(IdentifierFormat::Camel).identifier_acronyms(["USART", "CR", "I2C"]);
+    assert_eq!(names(&camel), ("usart1CR1".into(), "i2cEn".into()));
+
+    // Acronyms don't change snake case
+    let snake = config(IdentifierFormat::Snake).identifier_acronyms(["USART"]);
+    assert_eq!(names(&snake), ("usart1_cr1".into(), "i2c_en".into()));
+}
+
+#[test]
+fn boundaries() {
+    let snake = config(IdentifierFormat::Snake)
+        .identifier_boundaries([WordBoundary::Underscore, WordBoundary::UpperDigit]);
+    assert_eq!(names(&snake), ("usart_1_cr_1".into(), "i_2c_en".into()));
+    // Underscores are kept if they are not boundaries
+    let pascal = config(IdentifierFormat::Pascal).identifier_boundaries([WordBoundary::Hyphen]);
+    assert_eq!(names(&pascal), ("Usart1_cr1".into(), "I2c_en".into()));
+}
+
+#[test]
+fn functions() {
+    assert_eq!(
+        change_case("USART1_CR1", Some(IdentifierFormat::Pascal)),
+        "Usart1Cr1"
+    );
+    assert_eq!(change_case("USART1_CR1", None), "USART1_CR1");
+    let config = Config::default().identifier_acronyms(["USART", "CR"]);
+    assert_eq!(
+        change_case_with_config("USART1_CR1", Some(IdentifierFormat::Pascal), &config),
+        "USART1CR1"
+    );
+}
+
+#[test]
+fn callback() {
+    let custom = config(IdentifierFormat::Custom);
+    // Without callback names are kept
+    assert_eq!(names(&custom), ("USART1_CR1".into(), "I2C_EN".into()));
+    let custom = custom.identifier_callback(|s| s.replace("USART", "UART").to_lowercase());
+    assert_eq!(names(&custom), ("uart1_cr1".into(), "i2c_en".into()));
+}
+
+#[test]
+fn options() {
+    let config: Config = "register_name = Pascal; identifier_acronyms = USART, CR; identifier_boundaries = Underscore, LowerUpper"
+        .parse()
+        .unwrap();
+    assert_eq!(*config.identifier_acronyms, ["USART", "CR"]);
+    assert_eq!(
+        *config.identifier_boundaries,
+        [WordBoundary::Underscore, WordBoundary::LowerUpper]
+    );
+    assert_eq!(
+        config.get("identifier_boundaries").as_deref(),
+        Some("Underscore,LowerUpper")
+    );
+    assert_eq!(names(&config).0, "USART1CR1");
+    assert!("identifier_boundaries = Underscore, Dot"
+        .parse::<Config>()
+        .is_err());
+}
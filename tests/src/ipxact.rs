@@ -0,0 +1,380 @@
+use super::field;
+use svd_encoder::{encode_ipxact, Config as EncoderConfig, EncodeError};
+use svd_parser::{parse_ipxact, parse_with_config, Config, DiagnosticKind, Limits};
+use svd_rs::{
+    Access, Cluster, Device, Field, ModifiedWriteValues, ReadAction, Register, RegisterCluster,
+    Usage,
+};
+
+fn device() -> Device {
+    let mut device = super::device();
+    device.vendor = Some("ACME".into());
+    device.series = Some("TST".into());
+    let cr = device.peripherals[0].get_mut_register("CR").unwrap();
+    let fields = cr.fields.as_mut().unwrap();
+    fields[0].modified_write_values = Some(ModifiedWriteValues::OneToClear);
+    fields[1].read_action = Some(ReadAction::Clear);
+    fields[1].enumerated_values[0].usage = Some(Usage::Read);
+    device
+}
+
+fn names(registers: &[RegisterCluster]) -> Vec<&str> {
+    registers
+        .iter()
+        .map(|rc| match rc {
+            RegisterCluster::Register(r) => r.name.as_str(),
+            RegisterCluster::Cluster(c) => c.name.as_str(),
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    let device = device();
+    let (xml, unmapped) = encode_ipxact(&device, &EncoderConfig::default()).unwrap();
+    assert!(xml.contains(
+        "<ipxact:component xmlns:ipxact=\"http://www.accellera.org/XMLSchema/IPXACT/1685-2014\""
+    ));
+    let unmapped = unmapped
+        .iter()
+        .filter(|u| u.path.starts_with("TIMER0") || u.path == "TEST")
+        .map(|u| (u.path.as_str(), u.element.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unmapped,
+        [
+            ("TEST", "cpu"),
+            ("TIMER0", "groupName"),
+            ("TIMER0", "interrupt"),
+            ("TIMER0.CR2", "derivedFrom"),
+            ("TIMER0.CNT", "alternateGroup"),
+        ]
+    );
+
+    let (imported, diagnostics) = parse_ipxact(&xml, &Config::default()).unwrap();
+    assert!(diagnostics.is_empty());
+    assert_eq!(imported.name, "TEST");
+    assert_eq!(imported.vendor.as_deref(), Some("ACME"));
+    assert_eq!(imported.series.as_deref(), Some("TST"));
+    // Peripheral arrays are expanded
+    let peripherals = imported
+        .peripherals
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(peripherals, ["TIMER0", "TIMER1", "UART0", "UART1"]);
+
+    let timer0 = &imported.peripherals[0];
+    assert_eq!(timer0.base_address, 0x4000_0000);
+    assert_eq!(timer0.address_block.as_ref().unwrap()[0].size, 0x100);
+    let registers = timer0.registers.as_ref().unwrap();
+    assert_eq!(
+        names(registers),
+        ["CR", "CR2", "SR", "CNT", "CNT_BYTE", "CC[%s]", "CH[%s]"]
+    );
+
+    // Fields and properties are kept
+    let original = device.peripherals[0].get_register("CR").unwrap();
+    let cr = timer0.get_register("CR").unwrap();
+    assert_eq!(cr.properties.size, Some(32));
+    assert_eq!(cr.properties.access, Some(Access::ReadWrite));
+    assert_eq!(cr.properties.reset_value, Some(0x20));
+    assert_eq!(cr.fields, original.fields);
+    // Register is written with fields of its base
+    assert_eq!(timer0.get_register("CR2").unwrap().fields, cr.fields);
+
+    // Register without fields is written with one field
+    let sr = timer0.get_register("SR").unwrap();
+    assert_eq!(sr.properties.size, Some(16));
+    assert_eq!(sr.properties.access, Some(Access::ReadOnly));
+    assert_eq!(sr.properties.reset_value, Some(0x8000));
+    assert!(sr.fields.is_none());
+
+    let RegisterCluster::Register(Register::Array(_, dim)) = &registers[5] else {
+        panic!("CC is not a register array");
+    };
+    assert_eq!((dim.dim, dim.dim_increment), (2, 4));
+    let RegisterCluster::Cluster(Cluster::Array(_, dim)) = &registers[6] else {
+        panic!("CH is not a cluster array");
+    };
+    assert_eq!((dim.dim, dim.dim_increment), (2, 0x10));
+
+    // Derived peripheral has the same structure
+    assert_eq!(imported.peripherals[1].registers, timer0.registers);
+
+    let (again, _) = encode_ipxact(&imported, &EncoderConfig::default()).unwrap();
+    assert_eq!(again, xml);
+}
+
+#[test]
+fn export_without_vendor() {
+    let device = super::device();
+    let (xml, unmapped) = encode_ipxact(&device, &EncoderConfig::default()).unwrap();
+    let unmapped = unmapped
+        .iter()
+        .filter(|u| u.path == "TEST")
+        .map(|u| u.element.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(unmapped, ["vendor", "series", "cpu"]);
+    let (imported, _) = parse_ipxact(&xml, &Config::default()).unwrap();
+    assert_eq!(imported.vendor, None);
+    assert_eq!(imported.series, None);
+}
+
+const IPXACT_2009: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<spirit:component xmlns:spirit="http://www.spiritconsortium.org/XMLSchema/SPIRIT/1.5">
+  <spirit:vendor>acme</spirit:vendor>
+  <spirit:library>ip</spirit:library>
+  <spirit:name>uart</spirit:name>
+  <spirit:version>2.0</spirit:version>
+  <spirit:busInterfaces/>
+  <spirit:memoryMaps>
+    <spirit:memoryMap>
+      <spirit:name>regs</spirit:name>
+      <spirit:addressBlock>
+        <spirit:name>UART</spirit:name>
+        <spirit:baseAddress>0x1000</spirit:baseAddress>
+        <spirit:range>4096</spirit:range>
+        <spirit:width>32</spirit:width>
+        <spirit:register>
+          <spirit:name>DATA</spirit:name>
+          <spirit:dim>4</spirit:dim>
+          <spirit:addressOffset>'h0</spirit:addressOffset>
+          <spirit:size>32</spirit:size>
+          <spirit:volatile>true</spirit:volatile>
+          <spirit:reset>
+            <spirit:value>'h5</spirit:value>
+          </spirit:reset>
+          <spirit:field>
+            <spirit:name>BYTE</spirit:name>
+            <spirit:bitOffset>0</spirit:bitOffset>
+            <spirit:bitWidth>8</spirit:bitWidth>
+          </spirit:field>
+        </spirit:register>
+      </spirit:addressBlock>
+    </spirit:memoryMap>
+  </spirit:memoryMaps>
+</spirit:component>
+"#;
+
+#[test]
+fn import_2009() {
+    let (device, diagnostics) = parse_ipxact(IPXACT_2009, &Config::default()).unwrap();
+    let diagnostics = diagnostics
+        .iter()
+        .map(|d| (d.kind, d.tag.as_str(), d.path.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [
+            (DiagnosticKind::Ignored, "busInterfaces", ""),
+            (DiagnosticKind::Ignored, "volatile", "UART.DATA"),
+        ]
+    );
+    let RegisterCluster::Register(Register::Array(data, dim)) =
+        &device.peripherals[0].registers.as_ref().unwrap()[0]
+    else {
+        panic!("DATA is not a register array");
+    };
+    assert_eq!(data.name, "DATA[%s]");
+    assert_eq!((dim.dim, dim.dim_increment), (4, 4));
+    assert_eq!(data.properties.reset_value, Some(5));
+    assert_eq!(device.peripherals[0].base_address, 0x1000);
+}
+
+const IPXACT_2014: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>acme</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>dma</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>regs</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>DMA</ipxact:name>
+        <ipxact:baseAddress>'h2000</ipxact:baseAddress>
+        <ipxact:range>'h100</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>MUX</ipxact:name>
+          <ipxact:dim>4</ipxact:dim>
+          <ipxact:addressOffset>'h0</ipxact:addressOffset>
+          <ipxact:size>16</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>SEL</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:bitWidth>4</ipxact:bitWidth>
+          </ipxact:field>
+        </ipxact:register>
+        <ipxact:registerFile>
+          <ipxact:name>CH</ipxact:name>
+          <ipxact:dim>3</ipxact:dim>
+          <ipxact:addressOffset>'h20</ipxact:addressOffset>
+          <ipxact:range>'h10</ipxact:range>
+          <ipxact:register>
+            <ipxact:name>CFG</ipxact:name>
+            <ipxact:addressOffset>'h4</ipxact:addressOffset>
+            <ipxact:size>32</ipxact:size>
+            <ipxact:field>
+              <ipxact:name>CFG</ipxact:name>
+              <ipxact:bitOffset>0</ipxact:bitOffset>
+              <ipxact:bitWidth>32</ipxact:bitWidth>
+            </ipxact:field>
+          </ipxact:register>
+        </ipxact:registerFile>
+      </ipxact:addressBlock>
+      <ipxact:addressUnitBits>8</ipxact:addressUnitBits>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>
+"#;
+
+#[test]
+fn import_2014_array() {
+    let (device, diagnostics) = parse_ipxact(IPXACT_2014, &Config::default()).unwrap();
+    assert!(diagnostics.is_empty());
+    let registers = device.peripherals[0].registers.as_ref().unwrap();
+    let RegisterCluster::Register(Register::Array(mux, dim)) = &registers[0] else {
+        panic!("MUX is not a register array");
+    };
+    assert_eq!(mux.name, "MUX[%s]");
+    assert_eq!((dim.dim, dim.dim_increment), (4, 2));
+    let RegisterCluster::Cluster(svd_rs::Cluster::Array(ch, dim)) = &registers[1] else {
+        panic!("CH is not a cluster array");
+    };
+    assert_eq!(ch.name, "CH[%s]");
+    assert_eq!((dim.dim, dim.dim_increment), (3, 0x10));
+}
+
+#[test]
+fn export_array() {
+    let mut device = device();
+    // Array with gaps between registers is expanded
+    let Register::Array(_, dim) = device.peripherals[0].get_mut_register("CC[%s]").unwrap() else {
+        panic!("CC is not a register array");
+    };
+    dim.dim_increment = 8;
+    let (xml, unmapped) = encode_ipxact(&device, &EncoderConfig::default()).unwrap();
+    assert!(unmapped
+        .iter()
+        .any(|u| u.path == "TIMER0.CC[%s]" && u.element == "dim"));
+    let (imported, _) = parse_ipxact(&xml, &Config::default()).unwrap();
+    let timer0 = &imported.peripherals[0];
+    assert_eq!(timer0.get_register("CC0").unwrap().address_offset, 0x20);
+    assert_eq!(timer0.get_register("CC1").unwrap().address_offset, 0x28);
+}
+
+#[test]
+fn derived_from_other_peripheral() {
+    let mut device = device();
+    let dr = device.peripherals[2].get_mut_register("DR").unwrap();
+    dr.derived_from = Some("TIMER0.CR".into());
+    dr.properties.size = None;
+    dr.fields = None;
+    let (xml, _) = encode_ipxact(&device, &EncoderConfig::default()).unwrap();
+    let (imported, _) = parse_ipxact(&xml, &Config::default()).unwrap();
+    let cr = imported.peripherals[0].get_register("CR").unwrap();
+    let dr = imported.peripherals[2].get_register("DR").unwrap();
+    assert_eq!(dr.properties.size, Some(32));
+    assert_eq!(dr.fields, cr.fields);
+
+    device.peripherals[0].registers = None;
+    let err = encode_ipxact(&device, &EncoderConfig::default()).unwrap_err();
+    assert!(
+        matches!(&err, EncodeError::UnknownDerivedFrom(name, base) if name == "DR" && base == "TIMER0.CR"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn expression() {
+    let xml = IPXACT_2009.replace("0x1000", "BASE + 4");
+    let err = parse_ipxact(&xml, &Config::default()).unwrap_err();
+    assert!(format!("{err:#}").contains("Expression `BASE + 4` is not a number"));
+}
+
+#[test]
+fn address_overflow() {
+    for (from, to) in [
+        (
+            "<spirit:dim>4</spirit:dim>",
+            "<spirit:dim>4294967295</spirit:dim>",
+        ),
+        (
+            "'h0</spirit:addressOffset>",
+            "'hFFFFFFF8</spirit:addressOffset>",
+        ),
+        (
+            "0x1000</spirit:baseAddress>",
+            "'hFFFFFFFFFFFFFFFF</spirit:baseAddress>",
+        ),
+    ] {
+        let xml = IPXACT_2009.replace(from, to);
+        let err = parse_ipxact(&xml, &Config::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("overflows"), "{to}: {err:#}");
+    }
+
+    let xml = IPXACT_2009.replace(
+        "<spirit:size>32</spirit:size>",
+        "<spirit:size>0</spirit:size>",
+    );
+    let err = parse_ipxact(&xml, &Config::default()).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("size of `DATA` is 0"),
+        "{err:#}"
+    );
+
+    let config = Config::default().limits(Limits::default().max_dim(2));
+    let err = parse_ipxact(IPXACT_2009, &config).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Array size 4 exceeds limit 2"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn field_outside_register() {
+    for offset in ["70", "25", "0xFFFFFFFF"] {
+        let xml = IPXACT_2009.replace(
+            "<spirit:bitOffset>0</spirit:bitOffset>",
+            &format!("<spirit:bitOffset>{offset}</spirit:bitOffset>"),
+        );
+        let err = parse_ipxact(&xml, &Config::default()).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Bit range invalid, Size"),
+            "{err:#}"
+        );
+    }
+}
+
+#[test]
+fn export_field_outside_reset() {
+    let mut device = device();
+    let cr = device.peripherals[0].get_mut_register("CR").unwrap();
+    cr.fields = Some(vec![Field::Single(field("MODE", 70, 2))]);
+    let (xml, _) = encode_ipxact(&device, &EncoderConfig::default()).unwrap();
+    let mode = &xml[xml.find("<ipxact:name>MODE</ipxact:name>").unwrap()..];
+    let mode = &mode[..mode.find("</ipxact:field>").unwrap()];
+    assert!(mode.contains("<ipxact:bitOffset>70</ipxact:bitOffset>"));
+    assert!(!mode.contains("<ipxact:resets>"));
+}
+
+#[test]
+fn reset_mask() {
+    let xml = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/data/ARM_Sample_faulty.svd"
+    ));
+    let device = parse_with_config(xml, &Config::default().lenient(true)).unwrap();
+    let (xml, _) = encode_ipxact(&device, &EncoderConfig::default()).unwrap();
+    let (imported, _) = parse_ipxact(&xml, &Config::default()).unwrap();
+    let cr = imported
+        .get_peripheral("TIMER0")
+        .unwrap()
+        .get_register("CR")
+        .unwrap();
+    // Fields without `<mask>` are reset as a whole
+    assert_eq!(cr.properties.reset_mask, Some(0x1337F7F));
+    assert_eq!(cr.properties.reset_value, Some(0));
+}
@@ -0,0 +1,77 @@
+use super::row;
+use svd_parser::{Config, DiagnosticKind};
+
+/// [`svd`](super::svd) with invalid elements
+fn svd() -> String {
+    super::svd()
+        .replacen(
+            "<register>",
+            "<register><name>NOOFFSET</name></register>\n<register>",
+            1,
+        )
+        .replacen(
+            "</enumeratedValue>",
+            "</enumeratedValue>\n<enumeratedValue><value>1</value></enumeratedValue>",
+            1,
+        )
+        .replacen(
+            "</fields>",
+            "<field><name>BROKEN</name><bitRange>[1:2</bitRange></field>\n</fields>",
+            1,
+        )
+        .replace(
+            "<access>read-only</access>",
+            "<access>read-writeonce</access>",
+        )
+}
+
+#[test]
+fn strict_fails() {
+    assert!(svd_parser::parse(&svd()).is_err());
+}
+
+#[test]
+fn lenient_recovers() {
+    let svd = svd();
+    let config = Config::default().lenient(true);
+    let (device, diagnostics) = svd_parser::parse_with_diagnostics(&svd, &config).unwrap();
+    // Everything but the invalid elements is kept
+    let mut expected = super::device();
+    let sr = expected.peripherals[0].get_mut_register("SR").unwrap();
+    sr.properties.access = None;
+    assert_eq!(device, expected);
+
+    let found = diagnostics
+        .iter()
+        .map(|d| (d.kind, d.tag.as_str(), d.path.as_str(), d.pos.row))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (
+                DiagnosticKind::Dropped,
+                "register",
+                "TIMER0",
+                row(&svd, "NOOFFSET")
+            ),
+            (
+                DiagnosticKind::Dropped,
+                "enumeratedValue",
+                "TIMER0.CR.MODE",
+                row(&svd, "<enumeratedValue><value>1")
+            ),
+            (
+                DiagnosticKind::Dropped,
+                "field",
+                "TIMER0.CR",
+                row(&svd, "BROKEN")
+            ),
+            (
+                DiagnosticKind::Defaulted,
+                "access",
+                "TIMER0.SR",
+                row(&svd, "read-writeonce")
+            ),
+        ]
+    );
+}
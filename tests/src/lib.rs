@@ -2,6 +2,11 @@
 
 use core::str;
 use roxmltree::Document;
+use svd::{
+    Access, AddressBlock, AddressBlockUsage, ClusterInfo, Cpu, Device, DimElement, Endian, Field,
+    FieldInfo, Interrupt, PeripheralInfo, RegisterCluster, RegisterInfo, RegisterProperties,
+    ValidateLevel,
+};
 use svd_encoder::{Encode, EncodeError};
 use svd_parser::{Config, Parse, SVDErrorAt};
 use svd_rs as svd;
@@ -23,11 +28,12 @@ pub fn run_test<
     parser_config: Option<Config>,
     encoder_config: Option<svd_encoder::Config>,
 ) {
+    let parser_config = parser_config.unwrap_or_default();
     for t in tests {
         let rotree = Document::parse(t.1).unwrap();
         let elem = T::parse(
             &rotree.root().first_element_child().unwrap(),
-            &parser_config.unwrap_or_default(),
+            &parser_config,
         )
         .unwrap();
         assert_eq!(
@@ -37,7 +43,7 @@ pub fn run_test<
 
         let tree1 = Element::parse(t.2.as_bytes()).unwrap();
         let tree2 = elem
-            .encode_with_config(&encoder_config.unwrap_or_default())
+            .encode_with_config(&encoder_config.clone().unwrap_or_default())
             .unwrap();
         assert_eq!(
             tree1, tree2,
@@ -46,18 +52,220 @@ pub fn run_test<
     }
 }
 
+/// Device shared by tests of whole documents.
+///
+/// `TIMER0` has registers of each kind, `TIMER1` is derived from it
+/// and `UART%s` is an array of peripherals
+pub fn device() -> Device {
+    let mut cr = register("CR", 0x0);
+    cr.description = Some("Control".into());
+    cr.properties.reset_value = Some(0x20);
+    let mut en = field("EN", 0, 1);
+    en.description = Some("Enable".into());
+    let mut mode = field("MODE", 4, 2);
+    mode.access = Some(Access::WriteOnly);
+    mode.enumerated_values = vec![svd::EnumeratedValues::builder()
+        .values(vec![svd::EnumeratedValue::builder()
+            .name("ONESHOT".into())
+            .description(Some("One pulse".into()))
+            .value(Some(2))
+            .build(ValidateLevel::Strict)
+            .unwrap()])
+        .build(ValidateLevel::Strict)
+        .unwrap()];
+    cr.fields = Some(vec![Field::Single(en), Field::Single(mode)]);
+    let mut cr2 = register("CR2", 0x4);
+    cr2.derived_from = Some("CR".into());
+    let mut sr = register("SR", 0x8);
+    sr.properties.size = Some(16);
+    sr.properties.access = Some(Access::ReadOnly);
+    sr.properties.reset_value = Some(0x8000);
+    let mut cnt = register("CNT", 0x10);
+    cnt.properties.size = Some(16);
+    let mut cnt_byte = register("CNT", 0x10);
+    cnt_byte.alternate_group = Some("BYTE".into());
+    cnt_byte.properties.size = Some(8);
+    let ch = ClusterInfo::builder()
+        .name("CH[%s]".into())
+        .description(Some("Channel".into()))
+        .address_offset(0x30)
+        .children(vec![RegisterCluster::Register(
+            register("CFG", 0x4).single(),
+        )])
+        .build(ValidateLevel::Strict)
+        .unwrap();
+
+    let mut timer0 = peripheral(
+        "TIMER0",
+        0x4000_0000,
+        vec![
+            cr.single().into(),
+            cr2.single().into(),
+            sr.single().into(),
+            cnt.single().into(),
+            cnt_byte.single().into(),
+            register("CC[%s]", 0x20).array(dim(2, 0x4)).into(),
+            ch.array(dim(2, 0x10)).into(),
+        ],
+    );
+    timer0.description = Some("Timer".into());
+    timer0.group_name = Some("TIMER".into());
+    timer0.address_block = Some(vec![AddressBlock::builder()
+        .offset(0)
+        .size(0x100)
+        .usage(AddressBlockUsage::Registers)
+        .build(ValidateLevel::Strict)
+        .unwrap()]);
+    timer0.interrupt = vec![interrupt("TIMER0", 3)];
+    timer0.interrupt[0].description = Some("Timer 0 interrupt".into());
+
+    let mut timer1 = peripheral("TIMER1", 0x4000_1000, Vec::new());
+    timer1.derived_from = Some("TIMER0".into());
+    timer1.interrupt = vec![interrupt("TIMER1", 1)];
+
+    let mut dr = register("DR", 0x0);
+    dr.fields = Some(vec![Field::Single(field("DATA", 0, 8))]);
+    let mut uart = peripheral("UART%s", 0x4000_2000, vec![dr.single().into()]);
+    uart.group_name = Some("UART".into());
+
+    let cpu = Cpu::builder()
+        .name("CM4".into())
+        .revision("r0p1".into())
+        .endian(Endian::Little)
+        .mpu_present(true)
+        .fpu_present(true)
+        .nvic_priority_bits(4)
+        .has_vendor_systick(false)
+        .build(ValidateLevel::Strict)
+        .unwrap();
+    Device::builder()
+        .schema_version("1.3".into())
+        .no_namespace_schema_location("CMSIS-SVD.xsd".into())
+        .name("TEST".into())
+        .version("1.0".into())
+        .description("Test device".into())
+        .cpu(Some(cpu))
+        .address_unit_bits(8)
+        .width(32)
+        .default_register_properties(
+            RegisterProperties::new()
+                .size(Some(32))
+                .access(Some(Access::ReadWrite))
+                .reset_value(Some(0)),
+        )
+        .peripherals(vec![
+            timer0.single(),
+            timer1.single(),
+            uart.array(dim(2, 0x400)),
+        ])
+        .build(ValidateLevel::Strict)
+        .unwrap()
+}
+
+/// [`device`] encoded as SVD
+pub fn svd() -> String {
+    svd_encoder::encode(&device()).unwrap()
+}
+
+/// Peripheral without `registers` element if `registers` is empty
+pub fn peripheral(
+    name: &str,
+    base_address: u64,
+    registers: Vec<RegisterCluster>,
+) -> PeripheralInfo {
+    PeripheralInfo::builder()
+        .name(name.into())
+        .base_address(base_address)
+        .registers((!registers.is_empty()).then_some(registers))
+        .build(ValidateLevel::Strict)
+        .unwrap()
+}
+
+pub fn register(name: &str, address_offset: u32) -> RegisterInfo {
+    RegisterInfo::builder()
+        .name(name.into())
+        .address_offset(address_offset)
+        .build(ValidateLevel::Strict)
+        .unwrap()
+}
+
+pub fn field(name: &str, offset: u32, width: u32) -> FieldInfo {
+    FieldInfo::builder()
+        .name(name.into())
+        .bit_offset(offset)
+        .bit_width(width)
+        .build(ValidateLevel::Strict)
+        .unwrap()
+}
+
+pub fn interrupt(name: &str, value: u32) -> Interrupt {
+    Interrupt::builder()
+        .name(name.into())
+        .value(value)
+        .build(ValidateLevel::Strict)
+        .unwrap()
+}
+
+pub fn dim(dim: u32, dim_increment: u32) -> DimElement {
+    DimElement::builder()
+        .dim(dim)
+        .dim_increment(dim_increment)
+        .build(ValidateLevel::Strict)
+        .unwrap()
+}
+
+/// Row of the first line containing `pattern`
+pub fn row(text: &str, pattern: &str) -> u32 {
+    pos(text, pattern).0
+}
+
+/// Row and column of the first occurrence of `pattern`
+pub fn pos(text: &str, pattern: &str) -> (u32, u32) {
+    let (line, col) = text
+        .lines()
+        .enumerate()
+        .find_map(|(i, l)| Some((i, l.find(pattern)?)))
+        .unwrap();
+    (line as u32 + 1, col as u32 + 1)
+}
+
 mod access;
 mod addressblock;
+mod archive;
 //mod bitrange;
+mod configfile;
 mod cpu;
 mod dimelement;
+mod docs;
+mod encoding;
 mod endian;
 mod enumeratedvalue;
 //mod enumeratedvalues;
 mod field;
+mod filter;
+mod formatting;
+mod fragment;
+mod header;
+mod identifier;
 mod interrupt;
+mod ipxact;
+mod lenient;
+mod limits;
 mod modifiedwritevalues;
+mod overrides;
+mod parallel;
+mod properties;
 mod register;
+mod robustness;
 //mod registerproperties;
+mod schema;
+mod schemaversion;
+mod sorting;
+mod sourceformat;
+mod spans;
+mod stream;
+mod systemrdl;
+mod unknown;
 mod usage;
 mod writeconstraint;
+mod writer;
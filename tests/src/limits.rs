@@ -0,0 +1,128 @@
+use super::svd;
+use roxmltree::Document;
+use svd_parser::{Config, Limits};
+
+/// Number of elements in `xml`
+fn count(xml: &str) -> usize {
+    let doc = Document::parse(xml).unwrap();
+    doc.descendants().filter(|n| n.is_element()).count()
+}
+
+/// First of the most deeply nested elements in `xml` with its depth
+/// and position
+fn deepest(xml: &str) -> (usize, String) {
+    let doc = Document::parse(xml).unwrap();
+    let (depth, node) = doc
+        .descendants()
+        .filter(|n| n.is_element())
+        .map(|n| (n.ancestors().filter(|a| a.is_element()).count(), n))
+        .rev()
+        .max_by_key(|(depth, _)| *depth)
+        .unwrap();
+    let pos = doc.text_pos_at(node.range().start);
+    let name = node.tag_name().name();
+    (depth, format!("{name} at {}:{}", pos.row, pos.col))
+}
+
+fn parse(xml: &str, limits: Limits) -> anyhow::Result<svd_rs::Device> {
+    svd_parser::parse_with_config(xml, &Config::default().limits(limits))
+}
+
+#[test]
+fn unlimited() {
+    let svd = svd();
+    assert!(parse(&svd, Limits::default()).is_ok());
+    let limits = Limits::default()
+        .max_elements(count(&svd))
+        .max_depth(deepest(&svd).0);
+    assert!(parse(&svd, limits).is_ok());
+}
+
+#[test]
+fn elements() {
+    let svd = svd();
+    let max = count(&svd) - 1;
+    let err = parse(&svd, Limits::default().max_elements(max)).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Document contains more than {max} elements")
+    );
+}
+
+#[test]
+fn elements_before_tree() {
+    // Limit is hit while building the tree, before the syntax error at the end
+    let xml = format!("<device>{}<unclosed>", "<x/>".repeat(1000));
+    let err = parse(&xml, Limits::default().max_elements(10)).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Document contains more than 10 elements"
+    );
+}
+
+#[test]
+fn depth() {
+    let svd = svd();
+    let (depth, element) = deepest(&svd);
+    let err = parse(&svd, Limits::default().max_depth(depth - 1)).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Elements are nested deeper than {} levels", depth - 1)
+    );
+    assert!(format!("{err:#}").contains(&element), "{err:#}");
+}
+
+#[test]
+fn dim() {
+    let err = parse(&svd(), Limits::default().max_dim(1)).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Array size 2 exceeds limit 1");
+
+    let xml = svd().replace(
+        "<dimIncrement>0x4</dimIncrement>",
+        "<dimIncrement>0x4</dimIncrement><dimIndex>0-4294967295</dimIndex>",
+    );
+    let err = parse(&xml, Limits::default().max_dim(1024)).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Array size 4294967296 exceeds limit 1024"
+    );
+}
+
+#[test]
+fn expanded_registers() {
+    let svd = svd();
+    let config = Config::default().expand(true);
+    let device = svd_parser::parse_with_config(&svd, &config).unwrap();
+    let max = device
+        .peripherals
+        .iter()
+        .map(|p| p.all_registers().count())
+        .sum::<usize>();
+    let limits = Limits::default().max_expanded_registers(max);
+    assert!(svd_parser::parse_with_config(&svd, &config.clone().limits(limits)).is_ok());
+
+    let limits = Limits::default().max_expanded_registers(max - 1);
+    let err = svd_parser::parse_with_config(&svd, &config.limits(limits)).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Number of expanded registers exceeds limit {}", max - 1)
+    );
+}
+
+#[test]
+fn stream() {
+    let svd = svd();
+    let max = count(&svd);
+    let config = Config::default().limits(Limits::default().max_elements(max - 1));
+    let mut stream = svd_parser::parse_stream(svd.as_bytes(), &config).unwrap();
+    // Elements are counted across peripherals
+    let err = stream.find_map(Result::err).unwrap();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Document contains more than {} elements", max - 1)
+    );
+
+    let config = Config::default().limits(Limits::default().max_elements(max));
+    let stream = svd_parser::parse_stream(svd.as_bytes(), &config).unwrap();
+    assert!(stream.into_device().is_ok());
+}
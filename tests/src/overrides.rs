@@ -0,0 +1,125 @@
+use super::device;
+use svd_encoder::{
+    encode_with_config, Config as EncoderConfig, ConfigError, IdentifierFormat, NumberFormat,
+    Override,
+};
+use svd_parser::{parse_with_config, Config};
+use svd_rs::Peripheral;
+
+/// Peripheral `name` in `xml`
+fn section<'a>(xml: &'a str, name: &str) -> &'a str {
+    let start = xml.find(&format!("<name>{name}</name>")).unwrap();
+    let end = start + xml[start..].find("</peripheral>").unwrap();
+    &xml[start..end]
+}
+
+fn names(p: &Peripheral) -> Vec<&str> {
+    p.all_registers().map(|r| r.name.as_str()).collect()
+}
+
+#[test]
+fn scoped() {
+    let device = device();
+    let overrides = vec![
+        Override::new("TIMER*")
+            .set("register_address_offset", "Dec")
+            .unwrap(),
+        Override::new("TIMER0.CR")
+            .set("register_name", "Snake")
+            .unwrap()
+            .set("field_name", "Snake")
+            .unwrap(),
+    ];
+    let config = EncoderConfig::default().overrides(overrides);
+    let xml = encode_with_config(&device, &config).unwrap();
+    // Path of the previous device is left
+    assert_eq!(encode_with_config(&device, &config).unwrap(), xml);
+    // Registers of TIMER0 use decimal offsets, other peripherals keep defaults
+    assert!(section(&xml, "TIMER0").contains("<addressOffset>16</addressOffset>"));
+    assert!(section(&xml, "UART%s").contains("<addressOffset>0x0</addressOffset>"));
+
+    // Override applies to the register and its fields only
+    let encoded = parse_with_config(&xml, &Config::default()).unwrap();
+    let timer0 = &encoded.peripherals[0];
+    assert_eq!(
+        names(timer0),
+        ["cr", "CR2", "SR", "CNT", "CNT", "CC[%s]", "CFG"]
+    );
+    let fields = timer0.get_register("cr").unwrap().fields();
+    assert_eq!(
+        fields.map(|f| f.name.as_str()).collect::<Vec<_>>(),
+        ["en", "mode"]
+    );
+    assert_eq!(encoded.peripherals[2], device.peripherals[2]);
+
+    // Without overrides nothing changes
+    let xml = encode_with_config(&device, &EncoderConfig::default()).unwrap();
+    assert_eq!(parse_with_config(&xml, &Config::default()).unwrap(), device);
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        Override::new("TIM*").set("register_name", "Kebab"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        Override::new("TIM*").set("register", "Dec"),
+        Err(ConfigError::UnknownKey(_))
+    ));
+    // Values are kept as parsed
+    let o = Override::new("GPIOA")
+        .set("register_size", "Dec")
+        .unwrap()
+        .set("register_name", "Snake")
+        .unwrap()
+        .set("register_size", "UpperHex")
+        .unwrap();
+    assert_eq!(
+        o.options().collect::<Vec<_>>(),
+        [
+            ("register_size", "UpperHex".to_string()),
+            ("register_name", "Snake".to_string())
+        ]
+    );
+}
+
+#[test]
+fn from_file() {
+    let mut device = device();
+    device.peripherals[0].get_mut_register("SR").unwrap().name = "status".into();
+    let toml = r#"
+[register]
+name = "Constant"
+
+[[overrides]]
+path = "UART*"
+register_name = "Snake"
+register_address_offset = "Dec"
+"#;
+    let config = EncoderConfig::from_toml(toml).unwrap();
+    assert_eq!(config.register_name, Some(IdentifierFormat::Constant));
+    assert_eq!(config.overrides.len(), 1);
+    assert_eq!(config.overrides[0].path, "UART*");
+    let again = EncoderConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+    assert_eq!(again.overrides, config.overrides);
+    let again = EncoderConfig::from_json(&config.to_json().unwrap()).unwrap();
+    assert_eq!(again.overrides, config.overrides);
+    assert_eq!(again.register_address_offset, NumberFormat::UpperHex);
+
+    let xml = encode_with_config(&device, &config).unwrap();
+    let encoded = parse_with_config(&xml, &Config::default()).unwrap();
+    assert_eq!(names(&encoded.peripherals[0])[2], "STATUS");
+    assert_eq!(names(&encoded.peripherals[2]), ["dr"]);
+
+    let err = EncoderConfig::from_toml("[[overrides]]\npath = \"A\"\nregister_size = \"Hex\"\n")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value `Hex` of config key `overrides[0].register_size`"
+    );
+    assert!(matches!(
+        EncoderConfig::from_toml("[[overrides]]\nregister_size = \"Dec\"\n").unwrap_err(),
+        ConfigError::Format(_)
+    ));
+}
@@ -0,0 +1,95 @@
+//! Results must not depend on the `parallel` feature of `svd-parser`.
+//! The tests run with it only in the `--all-features` CI job.
+
+use std::fmt::Write;
+use svd_parser::{Config, Limits};
+
+fn svd() -> String {
+    let mut svd = String::from("<device><name>TEST</name><peripherals>");
+    for i in 0..64 {
+        let base = if i % 10 == 7 { "0xZZ" } else { "0x1000" };
+        write!(
+            svd,
+            "<peripheral><name>P{i}</name><baseAddress>{base}</baseAddress><registers>\
+             <register><name>R</name><addressOffset>0</addressOffset>\
+             <access>rw</access></register></registers></peripheral>"
+        )
+        .unwrap();
+    }
+    svd.push_str(
+        "<peripheral derivedFrom=\"P0\"><name>D</name><baseAddress>0</baseAddress></peripheral>",
+    );
+    svd.push_str("</peripherals></device>");
+    svd
+}
+
+#[test]
+fn lenient_order() {
+    let config = Config::default().lenient(true).expand(true);
+    let (device, diagnostics) = svd_parser::parse_with_diagnostics(&svd(), &config).unwrap();
+    let names = device
+        .peripherals
+        .iter()
+        .map(|p| p.name.clone())
+        .collect::<Vec<_>>();
+    let expected = (0..64)
+        .filter(|i| i % 10 != 7)
+        .map(|i| format!("P{i}"))
+        .chain(["D".to_string()])
+        .collect::<Vec<_>>();
+    assert_eq!(names, expected);
+    assert!(device.peripherals.last().unwrap().registers.is_some());
+
+    let found = diagnostics
+        .iter()
+        .map(|d| format!("{} {}", d.tag, d.name.as_deref().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    let expected = (0..64)
+        .flat_map(|i| {
+            if i % 10 == 7 {
+                vec![format!("peripheral P{i}")]
+            } else {
+                vec![format!("access ")]
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn first_error() {
+    let err = svd_parser::parse(&svd().replace(">rw<", ">read-write<")).unwrap_err();
+    assert_eq!(
+        err.chain().nth(1).unwrap().to_string(),
+        "In peripheral `P7`"
+    );
+}
+
+#[test]
+fn error_before_limit() {
+    let mut svd = String::from("<device><name>TEST</name><peripherals>");
+    for i in 0..64 {
+        let registers: String = match i {
+            0..3 => "<register><name>R</name><addressOffset>0</addressOffset></register>".into(),
+            3 => (0..50)
+                .map(|j| format!("<register><name>R{j}</name><addressOffset>0</addressOffset></register>"))
+                .chain(["<register derivedFrom=\"NOPE\"><name>R</name><addressOffset>0</addressOffset></register>".into()])
+                .collect(),
+            _ => "<register><dim>1000</dim><dimIncrement>4</dimIncrement><name>R%s</name>\
+                  <addressOffset>0</addressOffset></register>"
+                .into(),
+        };
+        write!(
+            svd,
+            "<peripheral><name>P{i}</name><baseAddress>0x1000</baseAddress>\
+             <registers>{registers}</registers></peripheral>"
+        )
+        .unwrap();
+    }
+    svd.push_str("</peripherals></device>");
+    // Later peripherals exceed the limit, but the error in `P3` comes first
+    let limits = Limits::default().max_expanded_registers(100);
+    let config = Config::default().expand(true).limits(limits);
+    let err = svd_parser::parse_with_config(&svd, &config).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "register NOPE not found");
+}
@@ -0,0 +1,74 @@
+use super::device;
+use svd_encoder::{encode_with_config, Config as EncoderConfig, PropertiesMode};
+use svd_parser::{parse_with_config, Config};
+use svd_rs::{Access, Device, RegisterProperties};
+
+fn expanded(xml: &str) -> Device {
+    parse_with_config(xml, &Config::default().expand_properties(true)).unwrap()
+}
+
+/// Properties as they are written
+fn written(xml: &str) -> Device {
+    parse_with_config(xml, &Config::default()).unwrap()
+}
+
+/// Effective properties of all registers
+fn registers(device: &Device) -> Vec<(String, RegisterProperties)> {
+    device
+        .peripherals
+        .iter()
+        .flat_map(|p| {
+            p.all_registers()
+                .map(move |r| (format!("{}.{}", p.name, r.name), r.properties))
+        })
+        .collect()
+}
+
+#[test]
+fn omit_inherited() {
+    let device = expanded(&super::svd());
+    let config = EncoderConfig::default().register_properties(PropertiesMode::OmitInherited);
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert_eq!(expanded(&xml), device);
+
+    let timer0 = &written(&xml).peripherals[0];
+    let cr = timer0.get_register("CR").unwrap();
+    // Reset value differs from the device
+    assert_eq!(
+        cr.properties,
+        RegisterProperties::new().reset_value(Some(0x20))
+    );
+    let sr = timer0.get_register("SR").unwrap();
+    assert_eq!(sr.properties.size, Some(16));
+    assert_eq!(sr.properties.access, Some(Access::ReadOnly));
+    assert_eq!(sr.properties.reset_value, Some(0x8000));
+
+    // Properties are kept by default
+    let xml = encode_with_config(&device, &EncoderConfig::default()).unwrap();
+    let timer0 = &written(&xml).peripherals[0];
+    assert_eq!(timer0.get_register("CR").unwrap().properties.size, Some(32));
+}
+
+#[test]
+fn hoist() {
+    let mut device = device();
+    let dr = device.peripherals[2].get_mut_register("DR").unwrap();
+    dr.properties.access = Some(Access::ReadOnly);
+    let device = expanded(&svd_encoder::encode(&device).unwrap());
+    let config = EncoderConfig::default().register_properties(PropertiesMode::Hoist);
+    let xml = encode_with_config(&device, &config).unwrap();
+    assert_eq!(registers(&expanded(&xml)), registers(&device));
+
+    let written = written(&xml);
+    assert_eq!(written.default_register_properties.size, Some(32));
+    assert_eq!(
+        written.default_register_properties.access,
+        Some(Access::ReadWrite)
+    );
+    let uart = &written.peripherals[2];
+    assert_eq!(
+        uart.default_register_properties.access,
+        Some(Access::ReadOnly)
+    );
+    assert_eq!(uart.get_register("DR").unwrap().properties.access, None);
+}
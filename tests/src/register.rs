@@ -6,7 +6,7 @@ use crate::svd::{
 
 #[test]
 fn decode_encode() {
-    let tests = vec![(
+    let tests = [(
         Register::Array(
             RegisterInfo::builder()
                 .name("MODE%s".to_string())
@@ -44,7 +44,7 @@ fn decode_encode() {
 
 #[test]
 fn decode_encode_info() {
-    let tests = vec![(
+    let tests = [(
         RegisterInfo::builder()
             .name("WRITECTRL".to_string())
             .alternate_group(Some("alternate_group".to_string()))
@@ -131,7 +131,7 @@ fn decode_encode_info() {
     encode_config.update("register_reset_mask", "UpperHex16");
     encode_config.update("field_bit_range", "BitRange");
 
-    let tests = vec![(
+    let tests = [(
         RegisterInfo::builder()
             .name("WRITECTRL".to_string())
             .alternate_group(Some("alternate_group".to_string()))
@@ -0,0 +1,165 @@
+use crate::svd::{
+    BitRange, DimElement, FieldInfo, MaybeArray, RegisterInfo, ValidateLevel, WriteConstraint,
+};
+use roxmltree::Document;
+use svd_parser::{Config, Parse};
+
+fn device(registers: &str) -> String {
+    format!(
+        r#"<device>
+  <name>TEST</name>
+  <peripherals>
+    <peripheral>
+      <name>P</name>
+      <baseAddress>0x40000000</baseAddress>
+      <registers>{registers}</registers>
+    </peripheral>
+  </peripherals>
+</device>"#
+    )
+}
+
+fn expand_err(registers: &str) -> String {
+    let config = Config::default().expand(true);
+    let err = svd_parser::parse_with_config(&device(registers), &config).unwrap_err();
+    format!("{err:#}")
+}
+
+#[test]
+fn empty_write_constraint() {
+    let tree = Document::parse("<writeConstraint></writeConstraint>").unwrap();
+    let err = WriteConstraint::parse(&tree.root_element(), &Config::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Expected content in <writeConstraint> tag, found none"
+    );
+}
+
+#[test]
+fn bit_range_overflow() {
+    let tree =
+        Document::parse("<field><name>F</name><lsb>0</lsb><msb>4294967295</msb></field>").unwrap();
+    assert!(BitRange::parse(&tree.root_element(), &Config::default()).is_err());
+    assert_eq!(BitRange::from_bit_range("[0:1]"), None);
+    assert_eq!(BitRange::try_from_msb_lsb(0, 1), None);
+    assert_eq!(BitRange::try_from_msb_lsb(u32::MAX, 0), None);
+    assert_eq!(BitRange::try_from_msb_lsb(3, 1).map(|r| r.width), Some(3));
+    assert_eq!(BitRange::from_msb_lsb(3, 1).width, 3);
+}
+
+#[test]
+fn field_path() {
+    let err = expand_err(
+        r#"<register>
+          <name>R</name>
+          <addressOffset>0x0</addressOffset>
+          <fields>
+            <field><name>F</name><bitRange>[1:0]</bitRange></field>
+            <field derivedFrom="R.F"><name>G</name><bitRange>[3:2]</bitRange></field>
+          </fields>
+        </register>"#,
+    );
+    assert!(err.contains("Full qualifying field path is expected, found `R.F`"));
+}
+
+#[test]
+fn cyclic_derivation() {
+    let err = expand_err(
+        r#"<register derivedFrom="B">
+          <name>A</name>
+          <addressOffset>0x0</addressOffset>
+        </register>
+        <register derivedFrom="C">
+          <name>B</name>
+          <addressOffset>0x4</addressOffset>
+        </register>
+        <register derivedFrom="B">
+          <name>C</name>
+          <addressOffset>0x8</addressOffset>
+        </register>"#,
+    );
+    assert!(err.contains("has cyclic derivation"));
+}
+
+#[test]
+fn address_overflow() {
+    let err = expand_err(
+        r#"<register>
+          <dim>2</dim>
+          <dimIncrement>0x4</dimIncrement>
+          <name>R%s</name>
+          <addressOffset>0xFFFFFFFC</addressOffset>
+        </register>"#,
+    );
+    assert!(err.contains("Array R%s does not fit in address space"));
+
+    let err = expand_err(
+        r#"<cluster>
+          <name>C</name>
+          <addressOffset>0xFFFFFFFC</addressOffset>
+          <register>
+            <name>R</name>
+            <addressOffset>0x4</addressOffset>
+          </register>
+        </cluster>"#,
+    );
+    assert!(err.contains("Address offset of register C.R overflows"));
+}
+
+#[test]
+fn array_offsets_stop_on_overflow() {
+    let info = RegisterInfo::builder()
+        .name("R%s".into())
+        .address_offset(0xFFFF_FFF8)
+        .build(ValidateLevel::Disabled)
+        .unwrap();
+    let dim = DimElement::builder()
+        .dim(4)
+        .dim_increment(4)
+        .build(ValidateLevel::Disabled)
+        .unwrap();
+    let offsets = svd_rs::register::address_offsets(&info, &dim).collect::<Vec<_>>();
+    assert_eq!(offsets, [0xFFFF_FFF8, 0xFFFF_FFFC]);
+    assert_eq!(svd_rs::register::expand(&info, &dim).count(), 2);
+}
+
+#[test]
+fn dim_index_length() {
+    let dim = DimElement::builder()
+        .dim(3)
+        .dim_increment(4)
+        .dim_index(Some(vec!["A".into(), "B".into()]));
+    let dim = dim.build(ValidateLevel::Weak).unwrap();
+    assert_eq!(dim.indexes().collect::<Vec<_>>(), ["A", "B"]);
+
+    let err = svd_parser::parse(&device(
+        r#"<register>
+          <dim>3</dim>
+          <dimIncrement>4</dimIncrement>
+          <dimIndex>A,B</dimIndex>
+          <name>R%s</name>
+          <addressOffset>0x0</addressOffset>
+        </register>"#,
+    ))
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("dimIndex tag must contain 3 indexes, found 2"));
+}
+
+#[test]
+fn bitmask() {
+    let info = FieldInfo::builder()
+        .name("F%s".into())
+        .bit_range(BitRange::from_offset_width(60, 8))
+        .build(ValidateLevel::Disabled)
+        .unwrap();
+    assert_eq!(info.bitmask(), 0xF000_0000_0000_0000);
+    let dim = DimElement::builder()
+        .dim(3)
+        .dim_increment(32)
+        .build(ValidateLevel::Disabled)
+        .unwrap();
+    assert_eq!(
+        MaybeArray::Array(info, dim).bitmask(),
+        0xF000_0000_0000_0000
+    );
+}
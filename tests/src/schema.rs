@@ -0,0 +1,113 @@
+use super::{pos, row};
+use svd_parser::schema::{check_schema_version, ViolationKind};
+use svd_parser::{check_schema, SchemaVersion};
+
+/// [`svd`](super::svd) with `protection` added in CMSIS-SVD 1.3
+fn svd() -> String {
+    super::svd().replace(
+        "<access>read-only</access>",
+        "<access>read-only</access>\n          <protection>s</protection>",
+    )
+}
+
+#[test]
+fn valid() {
+    assert_eq!(check_schema(&svd()).unwrap(), []);
+}
+
+#[test]
+fn version_1_1() {
+    let svd = svd();
+    let found = check_schema_version(&svd, SchemaVersion::V1_1)
+        .unwrap()
+        .into_iter()
+        .map(|v| (v.pos.row, v.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (
+                row(&svd, "<protection>"),
+                ViolationKind::UnexpectedElement {
+                    tag: "protection".into(),
+                    parent: "register".into()
+                }
+            ),
+            (
+                row(&svd, "<name>UART%s</name>") - 2,
+                ViolationKind::UnexpectedElement {
+                    tag: "dim".into(),
+                    parent: "peripheral".into()
+                }
+            ),
+            (
+                row(&svd, "<name>UART%s</name>") - 1,
+                ViolationKind::UnexpectedElement {
+                    tag: "dimIncrement".into(),
+                    parent: "peripheral".into()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn violations() {
+    let svd = svd()
+        .replace(
+            "<name>TEST</name>\n  <version>1.0</version>",
+            "<version>1.0</version>\n  <name>TEST</name>",
+        )
+        .replace("read-only", "readonly")
+        .replace(
+            "<bitOffset>0</bitOffset>\n              <bitWidth>1</bitWidth>",
+            "<lsb>0</lsb>",
+        );
+    let violations = check_schema(&svd).unwrap();
+    let found = violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let (name_row, name_col) = pos(&svd, "<name>TEST");
+    let (access_row, access_col) = pos(&svd, "<access>readonly");
+    let field = row(&svd, "<name>EN</name>") - 1;
+    assert_eq!(
+        found,
+        [
+            format!("Element <name> is out of order in <device> at {name_row}:{name_col}"),
+            format!("Expected <msb> in <field> at {field}:13 in `TIMER0.CR`"),
+            format!(
+                "Invalid content `readonly` of <access>, expected one of `read-only`, \
+                 `write-only`, `read-write`, `writeOnce`, `read-writeOnce` at {access_row}:{access_col} in `TIMER0.SR`"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn patterns() {
+    let svd = svd()
+        .replace("<name>CC[%s]</name>", "<name>CC[%s]X</name>")
+        .replace(
+            "<dimIncrement>0x4</dimIncrement>",
+            "<dimIncrement>4kk</dimIncrement>",
+        )
+        .replace(
+            "<addressOffset>0x10</addressOffset>",
+            "<addressOffset>0x</addressOffset>",
+        );
+    let found = check_schema(&svd)
+        .unwrap()
+        .into_iter()
+        .map(|v| v.kind.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            "Invalid content `0x` of <addressOffset>, expected scaledNonNegativeInteger",
+            "Invalid content `0x` of <addressOffset>, expected scaledNonNegativeInteger",
+            "Invalid content `4kk` of <dimIncrement>, expected scaledNonNegativeInteger",
+            "Invalid content `CC[%s]X` of <name>, expected dimableIdentifierType",
+        ]
+    );
+}
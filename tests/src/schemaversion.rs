@@ -0,0 +1,91 @@
+use super::register;
+use svd_encoder::{encode_with_config, Config, EncodeError, SchemaVersion, Unsupported};
+use svd_parser::check_schema;
+use svd_rs::{ClusterInfo, Device, Protection, RegisterCluster, ValidateLevel};
+
+/// Device with elements added in CMSIS-SVD 1.3
+fn device() -> Device {
+    let mut device = super::device();
+    let cpu = device.cpu.as_mut().unwrap();
+    cpu.dsp_present = Some(true);
+    cpu.sau_num_regions = Some(8);
+    let ch = device.peripherals[0].get_mut_cluster("CH[%s]").unwrap();
+    let sub = ClusterInfo::builder()
+        .name("SUB".into())
+        .address_offset(0)
+        .children(vec![RegisterCluster::Register(
+            register("DATA", 0).single(),
+        )])
+        .build(ValidateLevel::Strict)
+        .unwrap();
+    ch.children
+        .insert(0, RegisterCluster::Cluster(sub.single()));
+    let cfg = ch.get_mut_register("CFG").unwrap();
+    cfg.properties.protection = Some(Protection::Secure);
+    device
+}
+
+#[test]
+fn unchanged() {
+    let device = device();
+    let xml = encode_with_config(&device, &Config::default()).unwrap();
+    assert!(xml.contains("schemaVersion=\"1.3\""));
+    assert_eq!(svd_parser::parse(&xml).unwrap(), device);
+    let config = Config::default().schema_version(Some(SchemaVersion::V1_3));
+    assert_eq!(encode_with_config(&device, &config).unwrap(), xml);
+}
+
+#[test]
+fn drop_without_version() {
+    let device = device();
+    let config = Config::default().unsupported(Unsupported::Drop);
+    assert_eq!(
+        encode_with_config(&device, &config).unwrap(),
+        encode_with_config(&device, &Config::default()).unwrap()
+    );
+}
+
+#[test]
+fn report() {
+    let config = Config::default().schema_version(Some(SchemaVersion::V1_1));
+    let err = encode_with_config(&device(), &config).unwrap_err();
+    assert!(matches!(err, EncodeError::Unsupported(..)));
+    assert_eq!(
+        err.to_string(),
+        "<dspPresent> in `CM4` is not supported by CMSIS-SVD 1.1"
+    );
+}
+
+#[test]
+fn drop() {
+    let config = Config::default()
+        .schema_version(Some(SchemaVersion::V1_1))
+        .unsupported(Unsupported::Drop);
+    let xml = encode_with_config(&device(), &config).unwrap();
+    assert!(xml.contains(
+        "schemaVersion=\"1.1\" xmlns:xs=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xs:noNamespaceSchemaLocation=\"CMSIS-SVD_Schema_1_1.xsd\""
+    ));
+    assert_eq!(check_schema(&xml).unwrap(), Vec::new());
+
+    let dropped = svd_parser::parse(&xml).unwrap();
+    let cpu = dropped.cpu.as_ref().unwrap();
+    assert_eq!((cpu.dsp_present, cpu.sau_num_regions), (None, None));
+    // Peripheral arrays are expanded
+    let uart1 = dropped.get_peripheral("UART1").unwrap();
+    assert_eq!(uart1.base_address, 0x4000_2400);
+    let ch = dropped.peripherals[0].get_cluster("CH[%s]").unwrap();
+    let cfg = ch.get_register("CFG").unwrap();
+    assert_eq!(cfg.properties.protection, None);
+    // Register is moved before nested cluster
+    assert!(matches!(ch.children[0], RegisterCluster::Register(_)));
+
+    let config = config.schema_version(Some(SchemaVersion::V1_0));
+    let xml = encode_with_config(&device(), &config).unwrap();
+    assert!(xml.contains("schemaVersion=\"1.0\""));
+    let dropped = svd_parser::parse(&xml).unwrap();
+    assert!(dropped.cpu.is_none());
+    let timer0 = &dropped.peripherals[0];
+    assert!(timer0.get_register("CR").is_some());
+    assert_eq!(timer0.clusters().count(), 0);
+}
@@ -0,0 +1,191 @@
+use super::{field, interrupt, peripheral, register};
+use std::cmp::Ordering;
+use svd_encoder::{
+    encode_with_config, Config as EncoderConfig, DerivableSorting, RcSorting, Sortable, Sorting,
+};
+use svd_parser::{parse_with_config, Config};
+use svd_rs::{Device, EnumeratedValue, EnumeratedValues, Field, ValidateLevel};
+
+fn device() -> Device {
+    let mut usart10 = peripheral("USART10", 0x4000_2000, Vec::new());
+    usart10.interrupt = vec![interrupt("USART10_TX", 40), interrupt("USART10_RX", 39)];
+    let usart2 = peripheral("USART2", 0x4000_1000, Vec::new());
+
+    let value = |name: &str, value| {
+        EnumeratedValue::builder()
+            .name(name.into())
+            .value(value)
+            .is_default(value.is_none().then_some(true))
+            .build(ValidateLevel::Strict)
+            .unwrap()
+    };
+    let mut mode = field("MODE", 0, 2);
+    mode.enumerated_values = vec![EnumeratedValues::builder()
+        .values(vec![
+            value("Other", None),
+            value("Fast", Some(2)),
+            value("Off", Some(0)),
+        ])
+        .build(ValidateLevel::Strict)
+        .unwrap()];
+    let mut ccr2 = register("CCR2", 0x4);
+    ccr2.fields = Some(vec![Field::Single(mode)]);
+    let tim1 = peripheral(
+        "TIM1",
+        0x4000_0000,
+        vec![
+            register("CCR10", 0x4).single().into(),
+            ccr2.single().into(),
+            register("CR1", 0x0).single().into(),
+        ],
+    );
+
+    let mut device = super::device();
+    device.peripherals = vec![usart10.single(), usart2.single(), tim1.single()];
+    device
+}
+
+/// Names of peripherals, interrupts, registers, fields and enumerated values
+/// of the encoded device in order
+fn names(config: &EncoderConfig) -> Vec<String> {
+    let xml = encode_with_config(&device(), config).unwrap();
+    let device = parse_with_config(&xml, &Config::default()).unwrap();
+    let mut names = Vec::new();
+    for p in &device.peripherals {
+        names.push(p.name.clone());
+        names.extend(p.interrupt.iter().map(|i| i.name.clone()));
+        for r in p.registers() {
+            names.push(r.name.clone());
+            for f in r.fields() {
+                names.push(f.name.clone());
+                let values = f.enumerated_values.iter().flat_map(|e| &e.values);
+                names.extend(values.map(|v| v.name.clone()));
+            }
+        }
+    }
+    names
+}
+
+fn rc(sorting: Sorting) -> RcSorting {
+    RcSorting::Unchanged(DerivableSorting::Unchanged(Some(sorting)))
+}
+
+#[test]
+fn natural() {
+    let config = EncoderConfig::default()
+        .peripheral_sorting(DerivableSorting::Unchanged(Some(Sorting::Name)))
+        .register_cluster_sorting(rc(Sorting::Name));
+    assert_eq!(
+        names(&config),
+        [
+            "TIM1",
+            "CCR10",
+            "CCR2",
+            "MODE",
+            "Other",
+            "Fast",
+            "Off",
+            "CR1",
+            "USART10",
+            "USART10_TX",
+            "USART10_RX",
+            "USART2"
+        ]
+    );
+    let config = EncoderConfig::default()
+        .peripheral_sorting(DerivableSorting::Unchanged(Some(Sorting::NaturalName)))
+        .register_cluster_sorting(rc(Sorting::NaturalName));
+    assert_eq!(
+        names(&config),
+        [
+            "TIM1",
+            "CCR2",
+            "MODE",
+            "Other",
+            "Fast",
+            "Off",
+            "CCR10",
+            "CR1",
+            "USART2",
+            "USART10",
+            "USART10_TX",
+            "USART10_RX"
+        ]
+    );
+}
+
+#[test]
+fn offset_name() {
+    let config = EncoderConfig::default()
+        .peripheral_sorting(DerivableSorting::Unchanged(Some(Sorting::Offset)))
+        .register_cluster_sorting(rc(Sorting::OffsetName));
+    assert_eq!(
+        names(&config),
+        [
+            "TIM1",
+            "CR1",
+            "CCR2",
+            "MODE",
+            "Other",
+            "Fast",
+            "Off",
+            "CCR10",
+            "USART2",
+            "USART10",
+            "USART10_TX",
+            "USART10_RX"
+        ]
+    );
+}
+
+#[test]
+fn values() {
+    let config: EncoderConfig = "interrupt_sorting = Offset; enumerated_values_sorting = Offset"
+        .parse()
+        .unwrap();
+    assert_eq!(config.interrupt_sorting, Some(Sorting::Offset));
+    assert_eq!(
+        names(&config),
+        [
+            "USART10",
+            "USART10_RX",
+            "USART10_TX",
+            "USART2",
+            "TIM1",
+            "CCR10",
+            "CCR2",
+            "MODE",
+            "Off",
+            "Fast",
+            "Other",
+            "CR1"
+        ]
+    );
+    let config = config.enumerated_values_sorting(Some(Sorting::OffsetReversed));
+    assert_eq!(names(&config)[8..11], ["Fast", "Off", "Other"]);
+    assert_eq!(
+        config.get("enumerated_values_sorting").as_deref(),
+        Some("OffsetReversed")
+    );
+    assert!("enumerated_values_sorting = Offset,Name"
+        .parse::<EncoderConfig>()
+        .is_err());
+}
+
+#[test]
+fn custom() {
+    let sorting = DerivableSorting::Unchanged(Some(Sorting::Custom));
+    let default = names(&EncoderConfig::default());
+    // Without comparator the order is kept
+    let config = EncoderConfig::default().peripheral_sorting(sorting);
+    assert_eq!(names(&config), default);
+
+    // Reverse order of peripheral names, keep other elements
+    let config = config.comparator(|a, b| match (a, b) {
+        (Sortable::Peripheral(_), Sortable::Peripheral(_)) => b.name().cmp(a.name()),
+        _ => Ordering::Equal,
+    });
+    let names = names(&config);
+    assert_eq!(names[..2], ["USART2", "USART10"]);
+    assert_eq!(names[4], "TIM1");
+}
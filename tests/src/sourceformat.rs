@@ -0,0 +1,112 @@
+use super::device;
+use svd_encoder::{
+    encode_with_config, encode_with_source_format, Encoding, FieldBitRangeFormat, Indent,
+    NumberFormat,
+};
+use svd_parser::Config;
+
+/// [`device`] formatted differently from the encoder defaults
+fn svd() -> String {
+    let config = svd_encoder::Config::default()
+        .indent(Indent::Spaces(4))
+        .attribute_order(["xmlns:xs", "schemaVersion"]);
+    let xml = encode_with_config(&device(), &config).unwrap() + "\n";
+    xml.replacen(
+        "?>\n",
+        " standalone=\"no\"?>\n<!-- Copyright (c) Vendor -->\n",
+        1,
+    )
+    .replacen("<peripheral>", "<!-- Timers -->\n        <peripheral>", 1)
+    .replacen(
+        "<bitOffset>0</bitOffset>\n                            <bitWidth>1</bitWidth>",
+        "<lsb>0</lsb>\n                            <msb>0</msb>",
+        1,
+    )
+    .replacen(
+        "<bitOffset>4</bitOffset>\n                            <bitWidth>2</bitWidth>",
+        "<bitRange>[5:4]</bitRange>",
+        1,
+    )
+    .replace("<value>2</value>", "<value>0b10</value>")
+}
+
+fn parse(svd: &str) -> (svd_rs::Device, svd_rs::SourceFormat) {
+    let parsed = svd_parser::parse_full(svd, &Config::default().source_format(true)).unwrap();
+    (parsed.device, parsed.format.unwrap())
+}
+
+#[test]
+fn unchanged() {
+    let svd = svd();
+    let (device, format) = parse(&svd);
+    let xml = encode_with_source_format(&device, &Default::default(), &format).unwrap();
+    assert_eq!(xml, svd);
+
+    let config = svd_encoder::Config::default()
+        .register_address_offset(NumberFormat::Dec)
+        .field_bit_range(Some(FieldBitRangeFormat(svd_rs::BitRangeType::OffsetWidth)));
+    let xml = encode_with_source_format(&device, &config, &format).unwrap();
+    assert_eq!(xml, svd);
+}
+
+#[test]
+fn without_source() {
+    let (device, _) = parse(&svd());
+    let config = svd_encoder::Config::default().declaration_encoding(Encoding::Iso8859(1));
+    let xml = encode_with_source_format(&device, &config, &Default::default()).unwrap();
+    assert_eq!(
+        xml.lines().next(),
+        Some(r#"<?xml version="1.0" encoding="ISO-8859-1"?>"#)
+    );
+    assert_eq!(
+        xml,
+        svd_encoder::encode_with_config(&device, &config).unwrap() + "\n"
+    );
+}
+
+#[test]
+fn changed() {
+    let svd = svd();
+    let (mut device, format) = parse(&svd);
+    let timer0 = device.get_mut_peripheral("TIMER0").unwrap();
+    let sr = timer0.get_mut_register("SR").unwrap();
+    sr.properties.reset_value = Some(0x1);
+    let cr = timer0.get_mut_register("CR").unwrap();
+    cr.get_mut_field("EN").unwrap().bit_range = svd_rs::BitRange::from_msb_lsb(1, 0);
+
+    let xml = encode_with_source_format(&device, &Default::default(), &format).unwrap();
+    let changed = svd
+        .lines()
+        .zip(xml.lines())
+        .filter(|(a, b)| a != b)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changed,
+        [
+            (
+                "                            <msb>0</msb>",
+                "                            <msb>1</msb>"
+            ),
+            (
+                "                    <resetValue>0x00008000</resetValue>",
+                "                    <resetValue>0x00000001</resetValue>"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn lossless() {
+    let svd = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/data/ARM_Sample_faulty.svd"
+    ));
+    let config = Config::default().source_format(true).lenient(true);
+    let parsed = svd_parser::parse_full(svd, &config).unwrap();
+    let format = parsed.format.unwrap();
+    let xml = encode_with_source_format(&parsed.device, &Default::default(), &format).unwrap();
+    // Only the invalid value skipped by the lenient parser is lost
+    let invalid = "\t      <modifiedWriteValues>oneToFail</modifiedWriteValues>\r\n";
+    assert_eq!(svd.matches(invalid).count(), 1);
+    assert_eq!(xml, svd.replace(invalid, ""));
+}
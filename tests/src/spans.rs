@@ -0,0 +1,99 @@
+use super::{row, svd};
+use svd_parser::{parse_full, Config, PeripheralFilter};
+
+#[test]
+fn disabled_by_default() {
+    let parsed = svd_parser::parse_full(&svd(), &Config::default()).unwrap();
+    assert!(parsed.spans.is_none());
+}
+
+#[test]
+fn spans() {
+    let svd = svd();
+    let config = Config::default().source_spans(true);
+    let parsed = svd_parser::parse_full(&svd, &config).unwrap();
+    assert!(parsed.device.get_peripheral("TIMER0").is_some());
+    let spans = parsed.spans.unwrap();
+
+    let field = spans.get("TIMER0.CR.EN").unwrap();
+    assert_eq!(field.start.row, row(&svd, "<name>EN</name>") - 1);
+    assert_eq!(field.start.col, 13);
+    let text = &svd[field.range.clone()];
+    assert!(text.starts_with("<field>") && text.ends_with("</field>"));
+    assert!(text.contains("<name>EN</name>"));
+
+    let base = spans.child("TIMER0", "baseAddress").unwrap();
+    assert_eq!(
+        &svd[base.range.clone()],
+        "<baseAddress>0x40000000</baseAddress>"
+    );
+    assert_eq!(
+        spans.child("TIMER0", "interrupt").unwrap().start.row,
+        row(&svd, "<interrupt>")
+    );
+    assert_eq!(spans.get("").unwrap().start.row, 2);
+    assert!(spans.get("TIMER0.CR.DIS").is_none());
+
+    // Values of unnamed enumeratedValues are under the field
+    let value = spans.get("TIMER0.CR.MODE.ONESHOT").unwrap();
+    assert_eq!(value.start.row, row(&svd, "<name>ONESHOT</name>") - 1);
+    assert!(spans.child("TIMER0.CR.MODE", "enumeratedValues").is_some());
+
+    // Alternate register has own path
+    let cnt = &svd[spans.get("TIMER0.CNT").unwrap().range.clone()];
+    assert!(!cnt.contains("<alternateGroup>"));
+    let byte = &svd[spans.get("TIMER0.CNT_BYTE").unwrap().range.clone()];
+    assert!(byte.contains("<alternateGroup>BYTE</alternateGroup>"));
+}
+
+#[test]
+fn named_enumerated_values() {
+    let svd = svd().replacen(
+        "<enumeratedValues>",
+        "<enumeratedValues><name>MODE_E</name>",
+        1,
+    );
+    let config = Config::default().source_spans(true);
+    let spans = svd_parser::parse_full(&svd, &config)
+        .unwrap()
+        .spans
+        .unwrap();
+    assert!(spans.get("TIMER0.CR.MODE.MODE_E").is_some());
+    assert!(spans.get("TIMER0.CR.MODE.MODE_E.ONESHOT").is_some());
+    assert!(spans.get("TIMER0.CR.MODE.ONESHOT").is_none());
+}
+
+#[test]
+fn byte_order_mark() {
+    let svd = format!("\u{feff}{}", svd());
+    let config = Config::default().source_spans(true);
+    let spans = svd_parser::parse_full(&svd, &config)
+        .unwrap()
+        .spans
+        .unwrap();
+    let base = spans.child("TIMER0", "baseAddress").unwrap();
+    assert_eq!(
+        &svd[base.range.clone()],
+        "<baseAddress>0x40000000</baseAddress>"
+    );
+}
+
+#[test]
+fn parsed_elements_only() {
+    let svd = svd().replacen(
+        "</fields>",
+        "<field><name>BAD</name><bitRange>[x]</bitRange></field></fields>",
+        1,
+    );
+    let config = Config::default()
+        .source_spans(true)
+        .lenient(true)
+        .peripheral_filter(PeripheralFilter::new().exclude("UART%s"));
+    let parsed = parse_full(&svd, &config).unwrap();
+    assert_eq!(parsed.diagnostics.len(), 1);
+    let spans = parsed.spans.unwrap();
+    assert!(spans.get("TIMER0.CR.EN").is_some());
+    assert!(spans.get("TIMER0.CR.BAD").is_none());
+    assert!(spans.get("TIMER1").is_some());
+    assert!(spans.get("UART%s").is_none());
+}
@@ -0,0 +1,103 @@
+use std::io::Read;
+use svd_parser::{parse_stream, Config, DiagnosticKind, PeripheralFilter};
+
+/// [`svd`] with markup in comments and CDATA
+fn svd() -> String {
+    super::svd()
+        .replacen("<device ", "<!-- <peripheral> in comment -->\n<device ", 1)
+        .replace(
+            "<description>Test device</description>",
+            "<description><![CDATA[Device <with> markup]]></description>",
+        )
+        .replace(
+            "<peripheral derivedFrom",
+            "<!-- </peripherals> -->\n    <peripheral derivedFrom",
+        )
+}
+
+/// Reader returning one byte at a time
+struct Bytes<'a>(&'a [u8]);
+
+impl Read for Bytes<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some((first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        buf[0] = *first;
+        self.0 = rest;
+        Ok(1)
+    }
+}
+
+#[test]
+fn stream() {
+    let svd = svd();
+    let stream = parse_stream(Bytes(svd.as_bytes()), &Config::default()).unwrap();
+    assert_eq!(stream.device().name, "TEST");
+    assert_eq!(stream.device().description, "Device <with> markup");
+    assert!(stream.device().peripherals.is_empty());
+    let names = stream.map(|p| p.unwrap().name.clone()).collect::<Vec<_>>();
+    assert_eq!(names, ["TIMER0", "TIMER1", "UART%s"]);
+
+    let device = parse_stream(svd.as_bytes(), &Config::default())
+        .unwrap()
+        .into_device()
+        .unwrap();
+    assert_eq!(device, svd_parser::parse(&svd).unwrap());
+}
+
+#[test]
+fn filter() {
+    let config = Config::default().peripheral_filter(PeripheralFilter::new().include("TIMER*"));
+    let peripherals = parse_stream(svd().as_bytes(), &config)
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(peripherals.len(), 2);
+    assert_eq!(peripherals[1].derived_from.as_deref(), Some("TIMER0"));
+}
+
+#[test]
+fn error() {
+    let svd = svd().replace("0x40002000", "0xZZ");
+    let mut stream = parse_stream(svd.as_bytes(), &Config::default()).unwrap();
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_ok());
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "In peripheral `UART%s`");
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn filter_before_parsing() {
+    // Undefined entity would fail XML parsing of the skipped peripheral
+    let svd = svd().replace(
+        "<name>UART%s</name>",
+        "<name>UART%s</name><description>&bad;</description>",
+    );
+    let config = Config::default().peripheral_filter(PeripheralFilter::new().exclude("UART%s"));
+    let peripherals = parse_stream(svd.as_bytes(), &config)
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(peripherals.len(), 2);
+}
+
+#[test]
+fn lenient() {
+    let svd = svd().replace("0x40002000", "0xZZ");
+    let mut stream = parse_stream(svd.as_bytes(), &Config::default().lenient(true)).unwrap();
+    let names = (&mut stream)
+        .map(|p| p.unwrap().name.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["TIMER0", "TIMER1"]);
+    let diagnostics = stream
+        .diagnostics()
+        .iter()
+        .map(|d| (d.kind, d.tag.as_str(), d.name.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [(DiagnosticKind::Dropped, "peripheral", Some("UART%s"))]
+    );
+}
@@ -0,0 +1,358 @@
+use super::field;
+use svd_encoder::{
+    encode_systemrdl, encode_systemrdl_peripheral, Config as EncoderConfig, EncodeError, Indent,
+};
+use svd_parser::{parse_systemrdl, Config, DiagnosticKind, Limits};
+use svd_rs::{
+    Access, Cluster, Device, Field, ModifiedWriteValues, ReadAction, Register, RegisterCluster,
+};
+
+fn device() -> Device {
+    let mut device = super::device();
+    let cr = device.peripherals[0].get_mut_register("CR").unwrap();
+    let fields = cr.fields.as_mut().unwrap();
+    fields[0].modified_write_values = Some(ModifiedWriteValues::OneToClear);
+    fields[1].read_action = Some(ReadAction::Clear);
+    device
+}
+
+#[test]
+fn export() {
+    let device = device();
+    let (rdl, unmapped) = encode_systemrdl(&device, &EncoderConfig::default()).unwrap();
+    assert!(rdl.starts_with("addrmap TEST {\n  desc = \"Test device\";\n"));
+    let unmapped = unmapped
+        .iter()
+        .filter(|u| u.path.starts_with("TIMER0") || u.path == "TEST")
+        .map(|u| (u.path.as_str(), u.element.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unmapped,
+        [
+            ("TEST", "version"),
+            ("TEST", "cpu"),
+            ("TIMER0", "groupName"),
+            ("TIMER0", "addressBlock"),
+            ("TIMER0", "interrupt"),
+            ("TIMER0.CR2", "derivedFrom"),
+            ("TIMER0.CNT", "alternateGroup"),
+        ]
+    );
+
+    let config = EncoderConfig::default().indent(Indent::Tab);
+    let (peripheral, unmapped) =
+        encode_systemrdl_peripheral(&device.peripherals[0], &config).unwrap();
+    assert!(peripheral.starts_with("addrmap TIMER0 {\n\tdesc = \"Timer\";\n"));
+    assert_eq!(
+        unmapped[0].to_string(),
+        "<groupName> of `TIMER0` has no SystemRDL equivalent"
+    );
+}
+
+#[test]
+fn export_field_outside_reset() {
+    let mut device = device();
+    let cr = device.peripherals[0].get_mut_register("CR").unwrap();
+    cr.fields = Some(vec![Field::Single(field("MODE", 70, 2))]);
+    let (rdl, _) = encode_systemrdl(&device, &EncoderConfig::default()).unwrap();
+    assert!(rdl.contains("} MODE[71:70];\n"), "{rdl}");
+}
+
+#[test]
+fn derived_from_other_peripheral() {
+    let mut device = device();
+    let dr = device.peripherals[2].get_mut_register("DR").unwrap();
+    dr.derived_from = Some("TIMER0.CR".into());
+    dr.fields = None;
+    let (rdl, _) = encode_systemrdl(&device, &EncoderConfig::default()).unwrap();
+    let (imported, _) = parse_systemrdl(&rdl, &Config::default()).unwrap();
+    let cr = imported.peripherals[0].get_register("CR").unwrap();
+    let dr = imported.peripherals[2].get_register("DR").unwrap();
+    assert_eq!(dr.fields, cr.fields);
+
+    device.peripherals[0].registers = None;
+    let err = encode_systemrdl(&device, &EncoderConfig::default()).unwrap_err();
+    assert!(
+        matches!(&err, EncodeError::UnknownDerivedFrom(name, base) if name == "DR" && base == "TIMER0.CR"),
+        "{err:?}"
+    );
+
+    let mut device = self::device();
+    device.peripherals[1].derived_from = Some("TIMER9".into());
+    let err = encode_systemrdl(&device, &EncoderConfig::default()).unwrap_err();
+    assert!(
+        matches!(&err, EncodeError::UnknownDerivedFrom(name, base) if name == "TIMER1" && base == "TIMER9"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn round_trip() {
+    let device = device();
+    let (rdl, _) = encode_systemrdl(&device, &EncoderConfig::default()).unwrap();
+    let (imported, diagnostics) = parse_systemrdl(&rdl, &Config::default()).unwrap();
+    assert!(diagnostics.is_empty());
+    assert_eq!(imported.name, "TEST");
+    // Peripheral arrays are expanded
+    let peripherals = imported
+        .peripherals
+        .iter()
+        .map(|p| (p.name.as_str(), p.base_address))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        peripherals,
+        [
+            ("TIMER0", 0x4000_0000),
+            ("TIMER1", 0x4000_1000),
+            ("UART0", 0x4000_2000),
+            ("UART1", 0x4000_2400),
+        ]
+    );
+
+    let timer0 = &imported.peripherals[0];
+    let cr = timer0.get_register("CR").unwrap();
+    assert_eq!(cr.properties.reset_value, Some(0x20));
+    let fields = cr.fields.as_ref().unwrap();
+    assert_eq!(fields[0].access, Some(Access::ReadWrite));
+    assert_eq!(fields[1].access, Some(Access::WriteOnly));
+    assert_eq!(
+        fields[0].modified_write_values,
+        Some(ModifiedWriteValues::OneToClear)
+    );
+    assert_eq!(fields[1].read_action, Some(ReadAction::Clear));
+    let value = &fields[1].enumerated_values[0].values[0];
+    assert_eq!(value.name, "ONESHOT");
+    assert_eq!(value.description.as_deref(), Some("One pulse"));
+    // Register is written with fields of its base
+    assert_eq!(timer0.get_register("CR2").unwrap().fields, cr.fields);
+
+    let sr = timer0.get_register("SR").unwrap();
+    assert_eq!(sr.properties.size, Some(16));
+    assert_eq!(sr.properties.access, Some(Access::ReadOnly));
+    assert_eq!(sr.properties.reset_value, Some(0x8000));
+    assert!(sr.fields.is_none());
+
+    // Alternate register of other width is skipped
+    assert!(timer0.get_register("CNT_BYTE").is_none());
+
+    let Some(Register::Array(_, dim)) = timer0.get_register("CC[%s]") else {
+        panic!("CC is not a register array");
+    };
+    assert_eq!((dim.dim, dim.dim_increment), (2, 4));
+    let Some(Cluster::Array(ch, dim)) = timer0.get_cluster("CH[%s]") else {
+        panic!("CH is not a cluster array");
+    };
+    assert_eq!((ch.address_offset, dim.dim_increment), (0x30, 0x10));
+
+    // Derived peripheral has the same structure
+    assert_eq!(imported.peripherals[1].registers, timer0.registers);
+
+    let (again, _) = encode_systemrdl(&imported, &EncoderConfig::default()).unwrap();
+    assert_eq!(again, rdl);
+}
+
+#[test]
+fn export_alias() {
+    let mut device = device();
+    let byte = &mut device.peripherals[0].registers.as_mut().unwrap()[4];
+    let RegisterCluster::Register(byte) = byte else {
+        panic!("CNT_BYTE is not a register");
+    };
+    byte.properties.size = Some(16);
+    let (rdl, unmapped) = encode_systemrdl(&device, &EncoderConfig::default()).unwrap();
+    assert!(!unmapped.iter().any(|u| u.element == "alternateGroup"));
+    let timer0 = &rdl[..rdl.find("} TIMER0 @").unwrap()];
+    let alias = &timer0[timer0.find("alias CNT reg {").unwrap()..];
+    assert!(alias.contains("} CNT_BYTE;\n"), "{alias}");
+    // Aliases follow the other registers
+    assert!(!alias.contains("CH["), "{alias}");
+}
+
+#[test]
+fn string_escape() {
+    let mut device = device();
+    device.description = r#"Path C:\dev, "quoted""#.into();
+    let (rdl, _) = encode_systemrdl(&device, &EncoderConfig::default()).unwrap();
+    assert!(
+        rdl.contains(r#"desc = "Path C:\dev, \"quoted\"";"#),
+        "{rdl}"
+    );
+    let (imported, _) = parse_systemrdl(&rdl, &Config::default()).unwrap();
+    assert_eq!(imported.description, device.description);
+}
+
+const RDL: &str = r#"
+// Hand written description
+property my_prop { type = boolean; component = reg; };
+
+reg ctrl_t {
+    default sw = rw;
+    field { desc = "Enable"; } EN[1] = 1;
+    field { sw = r; } BUSY[4:4];
+    field { rclr; } FLAG @ 8;
+};
+
+regfile dma_t {
+    ctrl_t CTRL;
+    reg { field {} ADDR[32]; } ADDR;
+};
+
+addrmap soc {
+    desc = "System
+            on chip";
+    signal { activehigh; } irq;
+    addrmap {
+        ctrl_t CTRL @ 0x100;
+        dma_t DMA[4] += 0x20;
+        mem { mementries = 16; memwidth = 32; } buffer;
+        CTRL->my_prop = true;
+    } periph @ 0x1000;
+    reg { regwidth = 8; field { sw = w; swmod; } GO[7:0]; } GO @ 0x40;
+};
+"#;
+
+#[test]
+fn import() {
+    let (device, diagnostics) = parse_systemrdl(RDL, &Config::default()).unwrap();
+    let diagnostics = diagnostics
+        .iter()
+        .map(|d| (d.kind, d.tag.as_str(), d.path.as_str(), d.pos.row))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [
+            (DiagnosticKind::Ignored, "property", "", 3),
+            (DiagnosticKind::Ignored, "signal", "soc", 20),
+            (DiagnosticKind::Ignored, "mem", "soc", 24),
+            (DiagnosticKind::Ignored, "dynamic assignment", "soc", 25),
+            (DiagnosticKind::Ignored, "swmod", "GO.GO", 27),
+        ]
+    );
+    assert_eq!(device.name, "soc");
+    assert_eq!(device.description, "System on chip");
+    let names = device
+        .peripherals
+        .iter()
+        .map(|p| (p.name.as_str(), p.base_address))
+        .collect::<Vec<_>>();
+    assert_eq!(names, [("soc", 0), ("periph", 0x1000)]);
+
+    let registers = device.peripherals[1].registers.as_ref().unwrap();
+    let RegisterCluster::Register(ctrl) = &registers[0] else {
+        panic!("CTRL is not a register");
+    };
+    assert_eq!(ctrl.address_offset, 0x100);
+    assert_eq!(ctrl.properties.reset_value, Some(1));
+    let fields = ctrl.fields.as_ref().unwrap();
+    let bits = fields
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.bit_range.offset,
+                f.bit_range.width,
+                f.access,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bits,
+        [
+            ("EN", 0, 1, Some(Access::ReadWrite)),
+            ("BUSY", 4, 1, Some(Access::ReadOnly)),
+            ("FLAG", 8, 1, Some(Access::ReadWrite)),
+        ]
+    );
+    assert_eq!(fields[0].description.as_deref(), Some("Enable"));
+    assert_eq!(fields[2].read_action, Some(ReadAction::Clear));
+
+    // Placed after CTRL, aligned to its size
+    let RegisterCluster::Cluster(dma) = &registers[1] else {
+        panic!("DMA is not a cluster");
+    };
+    assert_eq!(dma.name, "DMA[%s]");
+    assert_eq!(dma.address_offset, 0x108);
+
+    let RegisterCluster::Register(go) = &device.peripherals[0].registers.as_ref().unwrap()[0]
+    else {
+        panic!("GO is not a register");
+    };
+    assert_eq!(go.address_offset, 0x40);
+    assert_eq!(go.properties.size, Some(8));
+    assert_eq!(go.properties.access, Some(Access::WriteOnly));
+    assert!(go.fields.is_none());
+}
+
+#[test]
+fn syntax_error() {
+    let rdl = RDL.replace("BUSY[4:4];", "BUSY[4:4]");
+    let err = parse_systemrdl(&rdl, &Config::default()).unwrap_err();
+    let err = format!("{err:#}");
+    assert!(err.starts_with("at 9:"), "{err}");
+    assert!(
+        err.contains("Invalid SystemRDL: expected `,` or `;`"),
+        "{err}"
+    );
+
+    let rdl = RDL.replace("ctrl_t CTRL @", "ctrl CTRL @");
+    let err = parse_systemrdl(&rdl, &Config::default()).unwrap_err();
+    assert!(format!("{err:#}").contains("unknown component `ctrl`"));
+}
+
+#[test]
+fn nesting_depth() {
+    let rdl = format!(
+        "addrmap top {{ {} }};",
+        "regfile {".repeat(100_000) + &"} r;".repeat(100_000)
+    );
+    let err = parse_systemrdl(&rdl, &Config::default()).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("nested deeper than 256 levels"),
+        "{err:#}"
+    );
+
+    let rdl = "addrmap top { regfile { regfile { reg { field {} f; } r; } b; } a; };";
+    let config = Config::default().limits(Limits::default().max_depth(2));
+    let err = parse_systemrdl(rdl, &config).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("nested deeper than 2 levels"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn address_overflow() {
+    for inst in [
+        "reg_t R @ 0xFFFFFFFFFFFFFFFF;",
+        "reg_t R @ 0x100000000;",
+        "reg_t R[2] @ 0 += 0x8000000000000000;",
+        "reg_t R[2] @ 0 += 0x100000000;",
+        "reg_t A @ 0xFFFFFFFFFFFFFFF0; reg_t B %= 0x100;",
+    ] {
+        let rdl = format!("addrmap top {{ reg reg_t {{ field {{}} f; }}; {inst} }};");
+        let err = parse_systemrdl(&rdl, &Config::default()).unwrap_err();
+        assert!(format!("{err:#}").contains("overflows"), "{inst}: {err:#}");
+    }
+}
+
+#[test]
+fn field_overflow() {
+    for fields in [
+        "field {} f[70:70] = 1;",
+        "field {} f[0xFFFFFFFFFFFFFFFF:0];",
+        "field {} f @ 0xFFFFFFFF; field {} g;",
+        "field {} f[31:0]; field {} g;",
+    ] {
+        let rdl = format!("addrmap top {{ reg {{ {fields} }} R; }};");
+        let err = parse_systemrdl(&rdl, &Config::default()).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("doesn't fit in 32 bits"),
+            "{fields}: {err:#}"
+        );
+    }
+
+    let rdl = "addrmap top { reg { regwidth = 64; field {} f[63:32] = 1; } R; };";
+    let (device, _) = parse_systemrdl(rdl, &Config::default()).unwrap();
+    let r = device.peripherals[0].get_register("R").unwrap();
+    assert_eq!(r.properties.reset_value, Some(1 << 32));
+}
@@ -0,0 +1,74 @@
+use super::pos;
+use svd_parser::{Config, DiagnosticKind, SVDError, UnknownElements};
+
+/// [`svd`](super::svd) with misspelled elements
+fn svd() -> String {
+    super::svd()
+        .replace(
+            "<description>Control</description>",
+            "<description>Control</description><resetvalue>0x1</resetvalue>",
+        )
+        .replace(
+            "<description>Enable</description>",
+            "<description>Enable</description><acess>read-only</acess>",
+        )
+}
+
+#[test]
+fn ignore_by_default() {
+    let (_, diagnostics) = svd_parser::parse_with_diagnostics(&svd(), &Config::default()).unwrap();
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn warn() {
+    let config = Config::default().unknown_elements(UnknownElements::Warn);
+    let svd = svd();
+    let (device, diagnostics) = svd_parser::parse_with_diagnostics(&svd, &config).unwrap();
+    assert_eq!(device, super::device());
+    let (row, col) = pos(&svd, "<acess>");
+    let found = diagnostics
+        .iter()
+        .map(|d| (d.kind, d.tag.as_str(), d.path.as_str(), d.pos.row, &d.error))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (
+                DiagnosticKind::Ignored,
+                "resetvalue",
+                "TIMER0.CR",
+                pos(&svd, "<resetvalue>").0,
+                &SVDError::UnknownElement(
+                    "resetvalue".into(),
+                    "register".into(),
+                    Some("resetValue".into())
+                )
+            ),
+            (
+                DiagnosticKind::Ignored,
+                "acess",
+                "TIMER0.CR.EN",
+                row,
+                &SVDError::UnknownElement("acess".into(), "field".into(), Some("access".into()))
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostics[1].to_string(),
+        format!(
+            "ignored acess at {row}:{col} in `TIMER0.CR.EN`: \
+             Unknown element <acess> in <field>, did you mean <access>? at {row}:{col}"
+        )
+    );
+}
+
+#[test]
+fn error() {
+    let config = Config::default().unknown_elements(UnknownElements::Error);
+    let err = svd_parser::parse_with_config(&svd(), &config).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Unknown element <resetvalue> in <register>, did you mean <resetValue>?"
+    );
+}
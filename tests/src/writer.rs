@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+use svd_encoder::{encode_to_writer, Config, Encode, EncodeError};
+use svd_rs::Device;
+use xmltree::EmitterConfig;
+
+fn device() -> Device {
+    let mut device = super::device();
+    device.description = "Device <with> markup".into();
+    device
+}
+
+/// Writer failing after given number of bytes
+struct Failing(usize);
+
+impl Write for Failing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.0 {
+            return Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"));
+        }
+        self.0 -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn same_as_tree() {
+    let device = device();
+    let config = Config::default();
+    let mut streamed = Vec::new();
+    encode_to_writer(&device, &config, &mut streamed).unwrap();
+    let streamed = String::from_utf8(streamed).unwrap();
+    assert_eq!(streamed, svd_encoder::encode(&device).unwrap());
+
+    let mut emitter = EmitterConfig::new();
+    emitter.perform_indent = true;
+    emitter.pad_self_closing = false;
+    let mut tree = Vec::new();
+    device
+        .encode_with_config(&config)
+        .unwrap()
+        .write_with_config(&mut tree, emitter)
+        .unwrap();
+    assert_eq!(streamed.as_bytes(), tree);
+}
+
+#[test]
+fn errors() {
+    let mut device = device();
+    let err = encode_to_writer(&device, &Config::default(), Failing(200)).unwrap_err();
+    match err {
+        EncodeError::Io(e) => assert_eq!(e.kind(), io::ErrorKind::StorageFull),
+        e => panic!("unexpected error {e}"),
+    }
+
+    device.peripherals[0].description = Some("Bell \u{7}".into());
+    let err = svd_encoder::encode(&device).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Value of <description> contains character '\\u{7}' which is not allowed in XML"
+    );
+}